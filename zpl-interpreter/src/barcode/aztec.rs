@@ -0,0 +1,63 @@
+use std::error::Error;
+
+use rxing::aztec::encoder::aztec_encoder::{DEFAULT_EC_PERCENT, encode};
+
+use crate::{BarcodeContent, barcode::bitmap_from_bitmatrix};
+
+pub(super) fn generate_aztec(
+    magnification: Option<u8>,
+    contents: &str,
+    size: u16,
+) -> Result<BarcodeContent, Box<dyn Error>> {
+    // negative layer counts select compact symbols, 0 the smallest fitting size
+    let (ec_percent, layers) = match size {
+        1..=99 => (size as u32, 0),
+        101..=104 => (DEFAULT_EC_PERCENT, -(size as i32 - 100)),
+        201..=232 => (DEFAULT_EC_PERCENT, size as i32 - 200),
+        300 => return Err("Aztec Runes are not supported".into()),
+        _ => (DEFAULT_EC_PERCENT, 0),
+    };
+
+    let aztec = encode(contents, ec_percent, layers)?;
+    let module_size = magnification.unwrap_or(2) as usize;
+    let bitmap = bitmap_from_bitmatrix(aztec.getMatrix().clone())?.scale(module_size, module_size);
+
+    Ok(BarcodeContent {
+        font_width: 0.,
+        text_elements: vec![],
+        bitmap,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rxing::BarcodeFormat;
+
+    use crate::barcode::{aztec::generate_aztec, decode};
+
+    #[test]
+    fn should_generate_aztec() {
+        let barcode = generate_aztec(Some(3), "Transit ticket 4711", 0).unwrap();
+        assert_eq!(
+            decode(&barcode.bitmap, BarcodeFormat::AZTEC),
+            "Transit ticket 4711"
+        );
+        assert_eq!(barcode.bitmap.width % 3, 0);
+        assert_eq!(barcode.bitmap.width, barcode.bitmap.height);
+    }
+
+    #[test]
+    fn should_use_requested_layers() {
+        // compact symbols with 2 layers are 19 modules, full range with 5 layers 37
+        let barcode = generate_aztec(Some(1), "AZTEC", 102).unwrap();
+        assert_eq!(barcode.bitmap.width, 19);
+        let barcode = generate_aztec(Some(3), "AZTEC", 205).unwrap();
+        assert_eq!(barcode.bitmap.width, 37 * 3);
+        assert_eq!(decode(&barcode.bitmap, BarcodeFormat::AZTEC), "AZTEC");
+    }
+
+    #[test]
+    fn should_reject_runes() {
+        assert!(generate_aztec(None, "1", 300).is_err());
+    }
+}
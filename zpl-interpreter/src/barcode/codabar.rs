@@ -0,0 +1,102 @@
+use std::error::Error;
+
+use rxing::oned::CodaBarReader;
+
+use crate::BarcodeContent;
+
+use super::{element_widths, linear_barcode, row_from_elements};
+
+pub(super) fn generate_codabar(
+    narrow_width: Option<u8>,
+    width_ratio: Option<f32>,
+    content: &str,
+    height: Option<usize>,
+    check_digit: bool,
+    (start, stop): (char, char),
+    text_above: bool,
+) -> Result<BarcodeContent, Box<dyn Error>> {
+    let value_of = |ch: char| CodaBarReader::ALPHABET.iter().position(|&c| c == ch);
+
+    // the data characters come before the start/stop characters A to D
+    let mut values = content
+        .chars()
+        .map(|ch| {
+            value_of(ch)
+                .filter(|&value| value < 16)
+                .ok_or(format!("invalid Codabar character {ch:?}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let start = value_of(start).ok_or(format!("invalid Codabar start {start:?}"))?;
+    let stop = value_of(stop).ok_or(format!("invalid Codabar stop {stop:?}"))?;
+    values.insert(0, start);
+    values.push(stop);
+
+    if check_digit {
+        let check = (16 - values.iter().sum::<usize>() % 16) % 16;
+        values.insert(values.len() - 1, check);
+    }
+
+    // 7 elements per character, separated by a narrow space
+    let mut elements = Vec::new();
+    for (idx, &value) in values.iter().enumerate() {
+        if idx > 0 {
+            elements.push(false);
+        }
+        let encoding = CodaBarReader::CHARACTER_ENCODINGS[value];
+        elements.extend((0..7).rev().map(|element| (encoding >> element) & 1 == 1));
+    }
+
+    let (narrow, wide) = element_widths(narrow_width, width_ratio);
+    let row = row_from_elements(elements, narrow, wide);
+    let text = values
+        .iter()
+        .map(|&value| CodaBarReader::ALPHABET[value])
+        .collect();
+
+    Ok(linear_barcode(
+        &row,
+        height,
+        narrow as f32 * 9.,
+        text,
+        text_above,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use rxing::BarcodeFormat;
+
+    use crate::barcode::{codabar::generate_codabar, decode};
+
+    #[test]
+    fn should_generate_codabar() {
+        let barcode = generate_codabar(
+            Some(2),
+            Some(3.),
+            "40156",
+            Some(50),
+            false,
+            ('B', 'D'),
+            false,
+        )
+        .unwrap();
+        assert_eq!(decode(&barcode.bitmap, BarcodeFormat::CODABAR), "40156");
+        assert_eq!(barcode.text_elements()[0].text, "B40156D");
+        assert_eq!(barcode.bitmap.height, 50);
+    }
+
+    #[test]
+    fn should_add_mod16_check_digit() {
+        // A=16 3 7 8 A=16 → 50 % 16 = 2 → 14 = '.'
+        let barcode =
+            generate_codabar(Some(2), Some(2.5), "378", None, true, ('A', 'A'), false).unwrap();
+        assert_eq!(barcode.text_elements()[0].text, "A378.A");
+        assert_eq!(decode(&barcode.bitmap, BarcodeFormat::CODABAR), "378.");
+    }
+
+    #[test]
+    fn should_reject_invalid_characters() {
+        assert!(generate_codabar(None, None, "12A3", None, false, ('A', 'A'), false).is_err());
+        assert!(generate_codabar(None, None, "123", None, false, ('E', 'A'), false).is_err());
+    }
+}
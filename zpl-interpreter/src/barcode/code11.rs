@@ -0,0 +1,116 @@
+use std::error::Error;
+
+use crate::BarcodeContent;
+
+use super::{element_widths, linear_barcode, row_from_elements};
+
+const ALPHABET: &str = "0123456789-";
+
+/// Narrow and wide elements of the characters 0 to 9 and `-`, 3 bars and 2
+/// spaces alternating
+const CHARACTER_ENCODINGS: [u8; 11] = [
+    0b00001, 0b10001, 0b01001, 0b11000, 0b00101, 0b10100, 0b01100, 0b00011, 0b10010, 0b10000,
+    0b00100,
+];
+
+const START_STOP_ENCODING: u8 = 0b00110;
+
+pub(super) fn generate_code11(
+    narrow_width: Option<u8>,
+    width_ratio: Option<f32>,
+    content: &str,
+    height: Option<usize>,
+    single_check_digit: bool,
+    text_above: bool,
+) -> Result<BarcodeContent, Box<dyn Error>> {
+    let mut values = content
+        .chars()
+        .map(|ch| {
+            ALPHABET
+                .find(ch)
+                .ok_or(format!("invalid Code 11 character {ch:?}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // check digits C and K, weighted from the right
+    let max_weights: &[usize] = match single_check_digit {
+        true => &[10],
+        false => &[10, 9],
+    };
+    for max_weight in max_weights {
+        let check = values
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(idx, value)| (idx % max_weight + 1) * value)
+            .sum::<usize>()
+            % 11;
+        values.push(check);
+    }
+
+    // start and stop character, characters are separated by a narrow space
+    let encodings = std::iter::once(START_STOP_ENCODING)
+        .chain(values.iter().map(|&value| CHARACTER_ENCODINGS[value]))
+        .chain(std::iter::once(START_STOP_ENCODING));
+    let mut elements = Vec::new();
+    for (idx, encoding) in encodings.enumerate() {
+        if idx > 0 {
+            elements.push(false);
+        }
+        elements.extend((0..5).rev().map(|element| (encoding >> element) & 1 == 1));
+    }
+
+    let (narrow, wide) = element_widths(narrow_width, width_ratio);
+    let row = row_from_elements(elements, narrow, wide);
+    let text = values
+        .iter()
+        .map(|&value| ALPHABET.as_bytes()[value] as char)
+        .collect();
+
+    Ok(linear_barcode(
+        &row,
+        height,
+        narrow as f32 * 9.,
+        text,
+        text_above,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::barcode::code11::generate_code11;
+
+    #[test]
+    fn should_add_two_check_digits() {
+        // C: 4*1 + 3*2 + 2*3 + 1*4 + 10*5 + 9*6 = 124 % 11 = 3
+        // K: 3*1 + 4*2 + 3*3 + 2*4 + 1*5 + 10*6 + 9*7 = 156 % 11 = 2
+        let barcode = generate_code11(Some(2), None, "9-1234", Some(40), false, false).unwrap();
+        assert_eq!(barcode.text_elements()[0].text, "9-123432");
+        assert_eq!(barcode.bitmap.height, 40);
+
+        let barcode = generate_code11(Some(2), None, "9-1234", None, true, false).unwrap();
+        assert_eq!(barcode.text_elements()[0].text, "9-12343");
+    }
+
+    #[test]
+    fn should_encode_wide_and_narrow_elements() {
+        let barcode = generate_code11(Some(1), Some(2.), "0", None, true, false).unwrap();
+        let row = &barcode.bitmap.pixels[..barcode.bitmap.width];
+        let runs = row
+            .chunk_by(|a, b| a == b)
+            .map(|run| run.len())
+            .collect::<Vec<_>>();
+        // start, 0, check digit 0 and stop, separated by narrow spaces
+        assert_eq!(
+            runs,
+            [
+                1, 1, 2, 2, 1, 1, 1, 1, 1, 1, 2, 1, 1, 1, 1, 1, 2, 1, 1, 1, 2, 2, 1
+            ]
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_characters() {
+        assert!(generate_code11(None, None, "12A", None, false, false).is_err());
+    }
+}
@@ -1,47 +1,441 @@
-use std::error::Error;
+use std::{error::Error, iter};
 
-use rxing::{BarcodeFormat, EncodeHintValue, EncodeHints, Writer, oned::Code128Writer};
-use zpl_parser::Justification;
+use rxing::oned::CODE_PATTERNS;
+use zpl_parser::Code128Mode;
 
-use crate::{BarcodeContent, barcode::bitmap_from_bitmatrix};
+use crate::BarcodeContent;
+
+use super::{
+    databar::{FNC1, element_data, human_readable, parse_element_string},
+    ean13::ean_check_digit,
+    linear_barcode,
+};
+
+const SHIFT: u8 = 98;
+const CODE_C: u8 = 99;
+const CODE_B: u8 = 100;
+const CODE_A: u8 = 101;
+const FNC1_VALUE: u8 = 102;
+const START_A: u8 = 103;
+const STOP: usize = 106;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Subset {
+    A,
+    B,
+    C,
+}
+
+impl Subset {
+    fn start(self) -> u8 {
+        START_A + self as u8
+    }
+
+    /// Value of an ASCII character in subset A or B
+    fn value(self, ch: char) -> Option<u8> {
+        match (self, ch as u32) {
+            (Subset::A, 0..=31) => Some(ch as u8 + 64),
+            (Subset::A, 32..=95) | (Subset::B, 32..=127) => Some(ch as u8 - 32),
+            _ => None,
+        }
+    }
+}
+
+/// Data character or FNC1 for the automatic subset selection
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Item {
+    Char(char),
+    Fnc1,
+}
 
 pub(super) fn generate_code_128(
     width: Option<u8>,
     contents: &str,
     height: Option<usize>,
+    mode: Code128Mode,
+    check_digit: bool,
+    text_above: bool,
 ) -> Result<BarcodeContent, Box<dyn Error>> {
-    let writer = Code128Writer::default();
-    let modules = estimate_code128_modules(contents);
-    let width = width.unwrap_or(2) as usize * modules / 2;
-    let height = height.unwrap_or(10);
-    let bit_matrix = writer.encode_with_hints(
-        contents,
-        &BarcodeFormat::CODE_128,
-        width as i32,
-        height as i32,
-        &EncodeHints::default().with(EncodeHintValue::Margin("0".into())),
-    )?;
-    let bitmap = bitmap_from_bitmatrix(bit_matrix)?;
-    let font_width = (bitmap.width / contents.chars().count()) as f32 * 0.8;
-    let mut barcode_content = BarcodeContent {
-        font_width,
-        text_elements: vec![],
-        bitmap,
+    let (values, text) = match mode {
+        Code128Mode::Normal => {
+            let (values, text) = subset_values(contents)?;
+            match check_digit {
+                true => subset_values(&format!("{contents}{}", mod10(&text)))?,
+                false => (values, text),
+            }
+        }
+        Code128Mode::Auto => {
+            let mut items = invocation_items(contents);
+            if check_digit {
+                items.push(Item::Char(mod10(&printable(&items))));
+            }
+            let text = printable(&items);
+            (auto_values(&items)?, text)
+        }
+        Code128Mode::Ucc => ucc_case_values(contents)?,
+        Code128Mode::Ean => {
+            let fields = parse_element_string(&contents.replace(' ', ""))?;
+            let items = iter::once(Item::Fnc1)
+                .chain(element_data(&fields).chars().map(|ch| match ch {
+                    FNC1 => Item::Fnc1,
+                    ch => Item::Char(ch),
+                }))
+                .collect::<Vec<_>>();
+            (auto_values(&items)?, human_readable(&fields))
+        }
+    };
+
+    let module_width = width.unwrap_or(2) as usize;
+    let row = symbol_row(&values, module_width);
+    Ok(linear_barcode(
+        &row,
+        height,
+        module_width as f32 * 9.,
+        text,
+        text_above,
+    ))
+}
+
+/// Symbol values and human readable text of data with explicit subsets.
+/// Subset B is used unless the data starts with `>9` (A) or `>;` (C),
+/// invocation codes switch subsets and insert function characters.
+fn subset_values(data: &str) -> Result<(Vec<u8>, String), Box<dyn Error>> {
+    let (mut subset, data) = match data.get(..2) {
+        Some(">9") => (Subset::A, &data[2..]),
+        Some(">:") => (Subset::B, &data[2..]),
+        Some(">;") => (Subset::C, &data[2..]),
+        _ => (Subset::B, data),
+    };
+    let mut values = vec![subset.start()];
+    let mut text = String::new();
+    let mut shifted = false;
+
+    let chars = data.chars().collect::<Vec<_>>();
+    let mut idx = 0;
+    while idx < chars.len() {
+        let ch = chars[idx];
+        let invocation = match (ch, chars.get(idx + 1)) {
+            ('>', Some(&code)) => invocation_value(code),
+            _ => None,
+        };
+        // a single shifted character is taken from the other of subsets A and B
+        let current = match (shifted, subset) {
+            (true, Subset::A) => Subset::B,
+            (true, _) => Subset::A,
+            (false, subset) => subset,
+        };
+        shifted = false;
+
+        if let Some(value) = invocation {
+            idx += 2;
+            match (current, value) {
+                (Subset::C, 30 | 94..=CODE_C) => {
+                    return Err(format!("invalid invocation code in subset C: {data:?}").into());
+                }
+                (_, 30) => text.push('>'),
+                (_, 94) => text.push('~'),
+                (_, SHIFT) => shifted = true,
+                (Subset::A | Subset::B, CODE_C) => subset = Subset::C,
+                (Subset::A | Subset::C, CODE_B) => subset = Subset::B,
+                (Subset::B | Subset::C, CODE_A) => subset = Subset::A,
+                _ => {}
+            }
+            values.push(value);
+        } else if current == Subset::C {
+            match chars.get(idx + 1).and_then(|next| pair_value(ch, *next)) {
+                Some(value) => values.push(value),
+                // a trailing odd digit is encoded in subset B
+                None if ch.is_ascii_digit() && idx + 1 == chars.len() => {
+                    values.extend([CODE_B, Subset::B.value(ch).unwrap_or_default()]);
+                    text.push(ch);
+                    break;
+                }
+                None => return Err(format!("invalid Code 128 subset C data {data:?}").into()),
+            }
+            text.extend(&chars[idx..idx + 2]);
+            idx += 2;
+        } else {
+            let value = current.value(ch).ok_or(format!(
+                "character {ch:?} is not in Code 128 subset {current:?}"
+            ))?;
+            values.push(value);
+            if !ch.is_ascii_control() {
+                text.push(ch);
+            }
+            idx += 1;
+        }
+    }
+    Ok((values, text))
+}
+
+/// Symbol value of the invocation code `>` followed by `code`
+fn invocation_value(code: char) -> Option<u8> {
+    match code {
+        '<' | '0' => Some(30),
+        '=' => Some(94),
+        '1'..='8' => Some(code as u8 - b'1' + 95),
+        _ => None,
+    }
+}
+
+fn pair_value(first: char, second: char) -> Option<u8> {
+    let tens = first.to_digit(10)?;
+    let ones = second.to_digit(10)?;
+    Some((tens * 10 + ones) as u8)
+}
+
+/// Data of the automatic mode, where `>8` is the only invocation code
+fn invocation_items(data: &str) -> Vec<Item> {
+    data.split(">8")
+        .enumerate()
+        .flat_map(|(idx, part)| {
+            let fnc1 = (idx > 0).then_some(Item::Fnc1);
+            fnc1.into_iter().chain(part.chars().map(Item::Char))
+        })
+        .collect()
+}
+
+fn printable(items: &[Item]) -> String {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Char(ch) if !ch.is_ascii_control() => Some(*ch),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Symbol values with the subsets picked from the data, runs of four or
+/// more digits, or data consisting of digits only, are packed in subset C
+fn auto_values(items: &[Item]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let is_digit = |item: &Item| matches!(item, Item::Char(ch) if ch.is_ascii_digit());
+    let mut values = Vec::new();
+    let mut subset = None;
+    let mut idx = 0;
+    while idx < items.len() {
+        let digits = items[idx..]
+            .iter()
+            .take_while(|item| is_digit(item))
+            .count();
+        let all_digits = idx + digits == items.len() && subset.is_none();
+        if subset != Some(Subset::C) && digits >= 2 && (digits >= 4 || all_digits) {
+            if digits % 2 == 1 {
+                // the odd digit goes first so the rest ends up in pairs
+                let Item::Char(ch) = items[idx] else {
+                    unreachable!()
+                };
+                let current = *subset.get_or_insert(Subset::B);
+                if values.is_empty() {
+                    values.push(current.start());
+                }
+                values.extend(current.value(ch));
+                idx += 1;
+            }
+            match subset {
+                Some(_) => values.push(CODE_C),
+                None => values.push(Subset::C.start()),
+            }
+            subset = Some(Subset::C);
+        }
+
+        match items[idx] {
+            Item::Fnc1 => {
+                if subset.is_none() {
+                    // FNC1 right after the start code marks GS1-128, data follows in C
+                    let next = if digits_follow(&items[idx + 1..], is_digit) {
+                        Subset::C
+                    } else {
+                        Subset::B
+                    };
+                    subset = Some(next);
+                    values.push(next.start());
+                }
+                values.push(FNC1_VALUE);
+                idx += 1;
+            }
+            Item::Char(ch) if subset == Some(Subset::C) && digits >= 2 => {
+                let Item::Char(next) = items[idx + 1] else {
+                    unreachable!()
+                };
+                values.extend(pair_value(ch, next));
+                idx += 2;
+            }
+            Item::Char(ch) => {
+                let needed = match ch as u32 {
+                    0..=31 => Subset::A,
+                    96..=127 => Subset::B,
+                    32..=95 => match subset {
+                        Some(Subset::A) => Subset::A,
+                        _ => Subset::B,
+                    },
+                    _ => {
+                        return Err(format!("character {ch:?} can't be encoded in Code 128").into());
+                    }
+                };
+                match subset {
+                    None => values.push(needed.start()),
+                    Some(current) if current != needed => values.push(match needed {
+                        Subset::A => CODE_A,
+                        _ => CODE_B,
+                    }),
+                    _ => {}
+                }
+                subset = Some(needed);
+                values.extend(needed.value(ch));
+                idx += 1;
+            }
+        }
+    }
+    if values.is_empty() {
+        values.push(Subset::B.start());
+    }
+    Ok(values)
+}
+
+fn digits_follow(items: &[Item], is_digit: impl Fn(&Item) -> bool) -> bool {
+    items.len() >= 2 && items[..2].iter().all(is_digit)
+}
+
+/// UCC case mode encodes 19 digits in subset C after FNC1 and a mod 10
+/// check digit, longer data is cut off and shorter data padded with zeros
+fn ucc_case_values(data: &str) -> Result<(Vec<u8>, String), Box<dyn Error>> {
+    if !data.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("UCC case mode only encodes digits, got {data:?}").into());
+    }
+    let mut digits = format!("{:0<19}", &data[..data.len().min(19)]);
+    digits.push(mod10(&digits));
+
+    let mut values = vec![Subset::C.start(), FNC1_VALUE];
+    for pair in digits.as_bytes().chunks(2) {
+        values.extend(pair_value(pair[0] as char, pair[1] as char));
+    }
+    Ok((values, format!("({}){}", &digits[..2], &digits[2..])))
+}
+
+/// UCC mod 10 check digit over the digits of the data
+fn mod10(data: &str) -> char {
+    let digits = data
+        .chars()
+        .filter(|c| c.is_ascii_digit())
+        .collect::<String>();
+    ean_check_digit(&digits)
+        .ok()
+        .and_then(|digit| char::from_digit(digit as u32, 10))
+        .unwrap_or('0')
+}
+
+/// Modules of the symbol with check character and stop pattern, every
+/// module `module_width` dots wide
+fn symbol_row(values: &[u8], module_width: usize) -> Vec<u8> {
+    let checksum = values
+        .iter()
+        .enumerate()
+        .map(|(idx, &value)| idx.max(1) * value as usize)
+        .sum::<usize>()
+        % 103;
+
+    let mut row = Vec::new();
+    let symbols = values.iter().map(|&value| value as usize);
+    for symbol in symbols.chain([checksum, STOP]) {
+        for (idx, &modules) in CODE_PATTERNS[symbol].iter().enumerate() {
+            let color = (idx % 2 == 0) as u8;
+            row.extend(iter::repeat_n(color, modules as usize * module_width));
+        }
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use rxing::BarcodeFormat;
+    use zpl_parser::Code128Mode;
+
+    use crate::barcode::{
+        code128::{Item, auto_values, generate_code_128, subset_values},
+        decode,
     };
-    let text_x = barcode_content.bitmap.width as isize / 2;
-    let text_y = { font_width * 0.2 } as isize;
-    barcode_content.add_text_element(text_x, text_y, contents.to_string(), Justification::Auto);
-    Ok(barcode_content)
-}
-
-fn estimate_code128_modules(data: &str) -> usize {
-    // Code128 structure:
-    // - Start code: 11 modules
-    // - Each character: 11 modules
-    // - Checksum: 11 modules
-    // - Stop pattern: 13 modules
-    // - Quiet zone: typically 10 modules on each side
-
-    let char_count = data.len();
-    11 + (char_count * 11) + 11 + 13 + 20 // +20 for quiet zones
+
+    fn generate(contents: &str, mode: Code128Mode, check_digit: bool) -> (String, String) {
+        let barcode =
+            generate_code_128(Some(2), contents, Some(40), mode, check_digit, false).unwrap();
+        let text = barcode.text_elements()[0].text.clone();
+        (decode(&barcode.bitmap, BarcodeFormat::CODE_128), text)
+    }
+
+    #[test]
+    fn should_interpret_invocation_codes() {
+        let (values, text) = subset_values(">;123456>6AB>8C").unwrap();
+        assert_eq!(values, [105, 12, 34, 56, 100, 33, 34, 102, 35]);
+        assert_eq!(text, "123456ABC");
+
+        // subset A control characters, FNC4 and a shifted lowercase letter
+        let (values, text) = subset_values(">9A\tB>4c>7").unwrap();
+        assert_eq!(values, [103, 33, 73, 34, 98, 67, 101]);
+        assert_eq!(text, "ABc");
+
+        let (values, text) = subset_values("A>0B><").unwrap();
+        assert_eq!(values, [104, 33, 30, 34, 30]);
+        assert_eq!(text, "A>B>");
+
+        assert!(subset_values(">;12AB").is_err());
+    }
+
+    #[test]
+    fn should_decode_normal_mode() {
+        assert_eq!(
+            generate(">:ABC>5123456", Code128Mode::Normal, false),
+            ("ABC123456".into(), "ABC123456".into())
+        );
+        assert_eq!(
+            generate(">;12345", Code128Mode::Normal, false),
+            ("12345".into(), "12345".into())
+        );
+        assert_eq!(
+            generate("12345", Code128Mode::Normal, true),
+            ("123457".into(), "123457".into())
+        );
+    }
+
+    #[test]
+    fn should_pick_subsets_in_auto_mode() {
+        let items = "AB1234567c\n".chars().map(Item::Char).collect::<Vec<_>>();
+        assert_eq!(
+            auto_values(&items).unwrap(),
+            [104, 33, 34, 17, 99, 23, 45, 67, 100, 67, 101, 74]
+        );
+        assert_eq!(
+            generate("Lot>842 1234", Code128Mode::Auto, false),
+            ("Lot42 1234".into(), "Lot42 1234".into())
+        );
+    }
+
+    #[test]
+    fn should_encode_ucc_case_mode() {
+        let (decoded, text) = generate("0012345555555555", Code128Mode::Ucc, false);
+        assert_eq!(decoded, "00123455555555550002");
+        assert_eq!(text, "(00)123455555555550002");
+    }
+
+    #[test]
+    fn should_encode_gs1_element_strings() {
+        let (decoded, text) = generate("(01)98898765432106 (10)AB12", Code128Mode::Ean, false);
+        assert_eq!(decoded, "019889876543210610AB12");
+        assert_eq!(text, "(01)98898765432106(10)AB12");
+    }
+
+    #[test]
+    fn should_use_exact_module_width() {
+        let barcode = generate_code_128(
+            Some(3),
+            ">;1234",
+            Some(20),
+            Code128Mode::Normal,
+            false,
+            false,
+        )
+        .unwrap();
+        // start, two pairs and check character of 11 modules, stop of 13
+        assert_eq!(barcode.bitmap.width, (4 * 11 + 13) * 3);
+        assert_eq!(barcode.bitmap.height, 20);
+    }
 }
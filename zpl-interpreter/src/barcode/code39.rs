@@ -0,0 +1,113 @@
+use std::{error::Error, iter};
+
+use rxing::oned::Code39Reader;
+
+use crate::BarcodeContent;
+
+use super::{element_widths, linear_barcode};
+
+pub(super) fn generate_code39(
+    narrow_width: Option<u8>,
+    width_ratio: Option<f32>,
+    content: &str,
+    height: Option<usize>,
+    check_digit: bool,
+    text_above: bool,
+) -> Result<BarcodeContent, Box<dyn Error>> {
+    let alphabet = Code39Reader::ALPHABET_STRING;
+    let mut text = content.to_uppercase();
+    let mut values = text
+        .chars()
+        .map(|ch| {
+            alphabet
+                .find(ch)
+                .ok_or(format!("invalid Code 39 character {ch:?}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if check_digit {
+        let check = values.iter().sum::<usize>() % 43;
+        values.push(check);
+        text.push_str(&alphabet[check..=check]);
+    }
+
+    let (narrow, wide) = element_widths(narrow_width, width_ratio);
+
+    // start and stop character `*`, characters are separated by a narrow space
+    let encodings = iter::once(Code39Reader::ASTERISK_ENCODING)
+        .chain(
+            values
+                .iter()
+                .map(|&value| Code39Reader::CHARACTER_ENCODINGS[value]),
+        )
+        .chain(iter::once(Code39Reader::ASTERISK_ENCODING));
+    let mut row = Vec::new();
+    for (idx, encoding) in encodings.enumerate() {
+        if idx > 0 {
+            row.extend(iter::repeat_n(0, narrow));
+        }
+        // 9 elements, bars and spaces alternating, a set bit is a wide element
+        for element in 0..9 {
+            let color = (element % 2 == 0) as u8;
+            let width = match (encoding >> (8 - element)) & 1 {
+                1 => wide,
+                _ => narrow,
+            };
+            row.extend(iter::repeat_n(color, width));
+        }
+    }
+
+    Ok(linear_barcode(
+        &row,
+        height,
+        narrow as f32 * 9.,
+        format!("*{text}*"),
+        text_above,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use rxing::BarcodeFormat;
+
+    use crate::barcode::{code39::generate_code39, decode};
+
+    #[test]
+    fn should_generate_code39() {
+        let barcode =
+            generate_code39(Some(2), Some(3.), "code-39", Some(50), false, false).unwrap();
+        assert_eq!(decode(&barcode.bitmap, BarcodeFormat::CODE_39), "CODE-39");
+        assert_eq!(barcode.text_elements()[0].text, "*CODE-39*");
+        // 9 characters of 3 wide (6) and 6 narrow (2) elements, 8 gaps
+        assert_eq!(barcode.bitmap.width, 9 * (3 * 6 + 6 * 2) + 8 * 2);
+        assert_eq!(barcode.bitmap.height, 50);
+    }
+
+    #[test]
+    fn should_add_mod43_check_digit() {
+        let barcode = generate_code39(Some(3), Some(2.5), "CODE39", None, true, false).unwrap();
+        // C=12 O=24 D=13 E=14 3 9 → 75 % 43 = 32 → 'W'
+        assert_eq!(barcode.text_elements()[0].text, "*CODE39W*");
+        assert_eq!(decode(&barcode.bitmap, BarcodeFormat::CODE_39), "CODE39W");
+    }
+
+    #[test]
+    fn should_place_text_above() {
+        let barcode = generate_code39(Some(2), None, "1", Some(30), false, true).unwrap();
+        let text = &barcode.text_elements()[0];
+        assert_eq!(text.text_y, -(barcode.bitmap.height as isize));
+        assert!(barcode.bitmap.height > 30);
+        // the text band is blank, the bars start below it
+        assert!(
+            barcode.bitmap.pixels[..barcode.bitmap.width]
+                .iter()
+                .all(|&p| p == 0)
+        );
+        assert_eq!(barcode.bitmap.pixels.last(), Some(&1));
+    }
+
+    #[test]
+    fn should_reject_invalid_characters() {
+        assert!(generate_code39(None, None, "ÄÖÜ", None, false, false).is_err());
+    }
+}
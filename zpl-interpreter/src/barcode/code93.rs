@@ -0,0 +1,113 @@
+use std::error::Error;
+
+use rxing::oned::Code93Reader;
+
+use crate::BarcodeContent;
+
+use super::linear_barcode;
+
+/// The shift characters ($), (%), (/) and (+) of the full ASCII mode are
+/// written as `&`, `'`, `(` and `)` in the field data
+const SHIFT_CHARACTERS: &str = "&'()";
+
+pub(super) fn generate_code93(
+    module_width: Option<u8>,
+    content: &str,
+    height: Option<usize>,
+    check_digit: bool,
+    text_above: bool,
+) -> Result<BarcodeContent, Box<dyn Error>> {
+    let alphabet = &Code93Reader::ALPHABET_STRING[..43];
+    let mut values = content
+        .chars()
+        .map(|ch| {
+            alphabet
+                .find(ch)
+                .or_else(|| SHIFT_CHARACTERS.find(ch).map(|shift| 43 + shift))
+                .ok_or(format!("invalid Code 93 character {ch:?}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // check characters C and K, weighted from the right
+    let mut text = content.to_string();
+    for max_weight in [20, 15] {
+        let check = values
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(idx, value)| (idx % max_weight + 1) * value)
+            .sum::<usize>()
+            % 47;
+        values.push(check);
+        if check_digit {
+            text.push(match check {
+                0..43 => alphabet.as_bytes()[check] as char,
+                _ => SHIFT_CHARACTERS.as_bytes()[check - 43] as char,
+            });
+        }
+    }
+
+    // start and stop character `*`, every character is 9 modules wide and
+    // the stop character is followed by a termination bar
+    let asterisk = Code93Reader::CHARACTER_ENCODINGS[47];
+    let encodings = std::iter::once(asterisk)
+        .chain(
+            values
+                .iter()
+                .map(|&value| Code93Reader::CHARACTER_ENCODINGS[value]),
+        )
+        .chain(std::iter::once(asterisk));
+    let module_width = module_width.unwrap_or(2) as usize;
+    let mut row = Vec::new();
+    for encoding in encodings {
+        for module in (0..9).rev() {
+            let color = ((encoding >> module) & 1) as u8;
+            row.extend(std::iter::repeat_n(color, module_width));
+        }
+    }
+    row.extend(std::iter::repeat_n(1, module_width));
+
+    Ok(linear_barcode(
+        &row,
+        height,
+        module_width as f32 * 9.,
+        text,
+        text_above,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use rxing::BarcodeFormat;
+
+    use crate::barcode::{code93::generate_code93, decode};
+
+    #[test]
+    fn should_generate_code93() {
+        let barcode = generate_code93(Some(2), "CODE 93", Some(40), false, false).unwrap();
+        assert_eq!(decode(&barcode.bitmap, BarcodeFormat::CODE_93), "CODE 93");
+        assert_eq!(barcode.text_elements()[0].text, "CODE 93");
+        // start, 7 characters, 2 check characters and stop, termination bar
+        assert_eq!(barcode.bitmap.width, (11 * 9 + 1) * 2);
+        assert_eq!(barcode.bitmap.height, 40);
+    }
+
+    #[test]
+    fn should_print_check_characters() {
+        // the check characters of TEST93 are + and 6
+        let barcode = generate_code93(Some(1), "TEST93", None, true, false).unwrap();
+        assert_eq!(barcode.text_elements()[0].text, "TEST93+6");
+    }
+
+    #[test]
+    fn should_encode_shift_characters() {
+        // (+)A is a lowercase a in full ASCII mode
+        let barcode = generate_code93(Some(2), ")A)B", None, false, false).unwrap();
+        assert_eq!(decode(&barcode.bitmap, BarcodeFormat::CODE_93), "ab");
+    }
+
+    #[test]
+    fn should_reject_invalid_characters() {
+        assert!(generate_code93(None, "code", None, false, false).is_err());
+    }
+}
@@ -0,0 +1,266 @@
+use std::error::Error;
+
+use rxing::{
+    BarcodeFormat, Dimension, EncodeHintValue, EncodeHints, Writer,
+    datamatrix::{
+        DataMatrixWriter,
+        encoder::{DefaultPlacement, SymbolInfoLookup, SymbolShapeHint, error_correction},
+    },
+};
+
+use crate::{BarcodeContent, DecodedBitmap, barcode::bitmap_from_bitmatrix};
+
+/// Group separator, encoded as FNC1 in GS1 symbols
+const FNC1: char = '\x1d';
+
+pub(super) fn generate_data_matrix(
+    element_height: Option<usize>,
+    symbol_height: Option<usize>,
+    contents: &str,
+    columns: Option<usize>,
+    rows: Option<usize>,
+    escape_char: char,
+    rectangular: bool,
+) -> Result<BarcodeContent, Box<dyn Error>> {
+    let data = unescape(contents, escape_char)?;
+    // a leading FNC1 marks GS1 data, the encoder adds it by itself
+    let (data, gs1) = match data.strip_prefix(FNC1) {
+        Some(data) => (data.to_string(), true),
+        None => (data, false),
+    };
+
+    let shape = match rectangular {
+        true => SymbolShapeHint::FORCE_RECTANGLE,
+        false => SymbolShapeHint::FORCE_SQUARE,
+    };
+    let min_size = columns
+        .zip(rows)
+        .map(|(columns, rows)| Dimension::new(columns, rows));
+
+    // one dot per module, enlarged by the element height afterwards
+    let bitmap = match gs1 {
+        true => encode_gs1(&data, shape, min_size)?,
+        false => {
+            let mut hints = EncodeHints::default()
+                .with(EncodeHintValue::DataMatrixShape(shape))
+                .with(EncodeHintValue::Margin("0".into()));
+            if let Some(min_size) = min_size {
+                #[allow(deprecated)]
+                let min_size = EncodeHintValue::MinSize(min_size);
+                hints = hints.with(min_size);
+            }
+            let bit_matrix = DataMatrixWriter.encode_with_hints(
+                &data,
+                &BarcodeFormat::DATA_MATRIX,
+                0,
+                0,
+                &hints,
+            )?;
+            bitmap_from_bitmatrix(bit_matrix)?
+        }
+    };
+    let module_size = element_height
+        .unwrap_or(symbol_height.unwrap_or(10) / bitmap.height)
+        .max(1);
+
+    Ok(BarcodeContent {
+        font_width: 0.,
+        text_elements: vec![],
+        bitmap: bitmap.scale(module_size, module_size),
+    })
+}
+
+/// Encode GS1 data with a leading FNC1 in ASCII encodation.
+///
+/// rxing's high level encoders have no working FNC1 support, so only the
+/// symbol lookup, error correction and module placement come from rxing.
+fn encode_gs1(
+    data: &str,
+    shape: SymbolShapeHint,
+    min_size: Option<Dimension>,
+) -> Result<DecodedBitmap, Box<dyn Error>> {
+    let mut codewords = vec![232];
+    let mut chars = data.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            FNC1 => codewords.push(232),
+            '0'..='9' if chars.peek().is_some_and(char::is_ascii_digit) => {
+                let next = chars.next().unwrap_or_default();
+                let pair = (ch as u8 - b'0') * 10 + (next as u8 - b'0');
+                codewords.push(130 + pair);
+            }
+            '\0'..='\x7f' => codewords.push(ch as u8 + 1),
+            '\u{80}'..='\u{ff}' => codewords.extend([235, ch as u8 - 127]),
+            _ => return Err(format!("invalid GS1 Data Matrix character {ch:?}").into()),
+        }
+    }
+
+    let symbol = SymbolInfoLookup::new()
+        .lookup_with_codewords_shape_size_fail(
+            codewords.len() as u32,
+            shape,
+            &min_size,
+            &None,
+            true,
+        )?
+        .ok_or("data too long for Data Matrix")?;
+    if codewords.len() < symbol.getDataCapacity() as usize {
+        codewords.push(129);
+    }
+    while codewords.len() < symbol.getDataCapacity() as usize {
+        // 253 state randomized padding
+        let pseudo_random = (149 * (codewords.len() + 1)) % 253 + 1;
+        let pad = 129 + pseudo_random;
+        codewords.push(if pad > 254 { pad - 254 } else { pad } as u8);
+    }
+    let codewords = codewords.into_iter().map(char::from).collect::<String>();
+    let codewords = error_correction::encodeECC200(&codewords, symbol)?;
+
+    let data_width = symbol.getSymbolDataWidth()? as usize;
+    let data_height = symbol.getSymbolDataHeight()? as usize;
+    let mut placement = DefaultPlacement::new(codewords, data_width, data_height);
+    placement.place()?;
+
+    // every data region is framed by a solid left and bottom edge and an
+    // alternating top and right edge
+    let width = symbol.getSymbolWidth()? as usize;
+    let height = symbol.getSymbolHeight()? as usize;
+    let region_width = data_width / ((width - data_width) / 2);
+    let region_height = data_height / ((height - data_height) / 2);
+    let mut pixels = Vec::with_capacity(width * height);
+    for y in 0..data_height {
+        if y % region_height == 0 {
+            pixels.extend((0..width).map(|x| (x % 2 == 0) as u8));
+        }
+        for x in 0..data_width {
+            if x % region_width == 0 {
+                pixels.push(1);
+            }
+            pixels.push(placement.getBit(x, y) as u8);
+            if x % region_width == region_width - 1 {
+                pixels.push((y % 2 == 0) as u8);
+            }
+        }
+        if y % region_height == region_height - 1 {
+            pixels.extend(std::iter::repeat_n(1, width));
+        }
+    }
+
+    Ok(DecodedBitmap {
+        width,
+        height,
+        pixels,
+    })
+}
+
+/// Replace the ECC 200 escape sequences of the field data, shown here with
+/// `_` as escape character:
+///
+/// - `_1` FNC1, `_2` and `_3` (structured append, reader programming) are dropped
+/// - `_5NNN` code page NNN, dropped as the data is already Unicode
+/// - `_dNNN` the character with decimal value NNN
+/// - `_@` to `_Z` and `_[` to `__` control characters NUL to US
+/// - `_0` padding, dropped
+///
+/// A doubled escape character stands for itself.
+fn unescape(contents: &str, escape_char: char) -> Result<String, Box<dyn Error>> {
+    let mut data = String::with_capacity(contents.len());
+    let mut chars = contents.chars();
+    let digits = |chars: &mut std::str::Chars, count| -> Result<u32, Box<dyn Error>> {
+        let digits = chars.by_ref().take(count).collect::<String>();
+        digits
+            .parse()
+            .map_err(|_| format!("invalid Data Matrix escape sequence {digits:?}").into())
+    };
+
+    while let Some(ch) = chars.next() {
+        if ch != escape_char {
+            data.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some(ch) if ch == escape_char => data.push(ch),
+            Some('1') => data.push(FNC1),
+            Some('0' | '2' | '3') => {}
+            Some('5') => {
+                digits(&mut chars, 3)?;
+            }
+            Some('d') => {
+                let value = digits(&mut chars, 3)?;
+                data.push(char::from_u32(value).ok_or("invalid Data Matrix character")?);
+            }
+            Some(ch @ '@'..='_') => data.push((ch as u8 - b'@') as char),
+            Some(ch) => {
+                data.push(escape_char);
+                data.push(ch);
+            }
+            None => data.push(escape_char),
+        }
+    }
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use rxing::BarcodeFormat;
+
+    use crate::barcode::{
+        data_matrix::{generate_data_matrix, unescape},
+        decode,
+    };
+
+    #[test]
+    fn should_unescape_field_data() {
+        assert_eq!(
+            unescape("_10101234_110AB", '_').unwrap(),
+            "\x1d0101234\x1d10AB"
+        );
+        assert_eq!(unescape("a__b_d065_G", '_').unwrap(), "a_bA\x07");
+        assert_eq!(unescape("~1x_1", '~').unwrap(), "\x1dx_1");
+        assert!(unescape("_dxyz", '_').is_err());
+    }
+
+    #[test]
+    fn should_generate_data_matrix() {
+        let barcode =
+            generate_data_matrix(Some(5), None, "Hello Data Matrix", None, None, '~', false)
+                .unwrap();
+        assert_eq!(barcode.bitmap.width, barcode.bitmap.height);
+        assert_eq!(barcode.bitmap.width % 5, 0);
+        assert_eq!(
+            decode(&barcode.bitmap, BarcodeFormat::DATA_MATRIX),
+            "Hello Data Matrix"
+        );
+    }
+
+    #[test]
+    fn should_honour_size_and_shape() {
+        let barcode =
+            generate_data_matrix(None, Some(100), "1", Some(20), Some(20), '~', false).unwrap();
+        // 20 modules of 100 / 20 dots
+        assert_eq!(barcode.bitmap.width, 100);
+
+        let barcode = generate_data_matrix(Some(2), None, "12345", None, None, '~', true).unwrap();
+        assert!(barcode.bitmap.width > barcode.bitmap.height);
+    }
+
+    #[test]
+    fn should_encode_gs1_data() {
+        let barcode = generate_data_matrix(
+            Some(4),
+            None,
+            "_10101234567890128_110AB1",
+            None,
+            None,
+            '_',
+            false,
+        )
+        .unwrap();
+        // readers report FNC1 separators as GS
+        assert_eq!(
+            decode(&barcode.bitmap, BarcodeFormat::DATA_MATRIX),
+            "0101234567890128\x1d10AB1"
+        );
+    }
+}
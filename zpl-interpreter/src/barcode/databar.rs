@@ -0,0 +1,1075 @@
+use std::{error::Error, iter, ops::Range};
+
+use zpl_parser::{Code128Mode, DataBarType, Justification};
+
+use crate::{BarcodeContent, DecodedBitmap};
+
+use super::{
+    code128::generate_code_128,
+    ean8::generate_ean8,
+    ean13::{ean_check_digit, generate_ean13},
+    upc::{generate_upc_a, generate_upc_e},
+};
+
+/// Field separator within GS1 element strings
+pub(super) const FNC1: char = '\x1d';
+
+const OUTSIDE_GSUM: [u32; 5] = [0, 161, 961, 2015, 2715];
+const OUTSIDE_EVEN_TOTAL_SUBSET: [u32; 5] = [1, 10, 34, 70, 126];
+const OUTSIDE_ODD_WIDEST: [u32; 5] = [8, 6, 4, 3, 1];
+const INSIDE_GSUM: [u32; 4] = [0, 336, 1036, 1516];
+const INSIDE_ODD_TOTAL_SUBSET: [u32; 4] = [4, 20, 48, 81];
+const INSIDE_ODD_WIDEST: [u32; 4] = [2, 4, 6, 8];
+
+const FINDER_PATTERNS: [[u8; 5]; 9] = [
+    [3, 8, 2, 1, 1],
+    [3, 5, 5, 1, 1],
+    [3, 3, 7, 1, 1],
+    [3, 1, 9, 1, 1],
+    [2, 7, 4, 1, 1],
+    [2, 5, 6, 1, 1],
+    [2, 3, 8, 1, 1],
+    [1, 5, 7, 1, 1],
+    [1, 3, 9, 1, 1],
+];
+
+const LIMITED_GSUM: [u32; 7] = [0, 183064, 820064, 1000776, 1491021, 1979845, 1996939];
+const LIMITED_EVEN_TOTAL_SUBSET: [u32; 7] = [28, 728, 6454, 203, 2408, 1, 16632];
+const LIMITED_ODD_MODULES: [u32; 7] = [17, 13, 9, 15, 11, 19, 7];
+const LIMITED_ODD_WIDEST: [u32; 7] = [6, 5, 3, 5, 4, 8, 1];
+
+/// Largest 13 digit GTIN of DataBar Limited, the indicator digit is 0 or 1
+const LIMITED_MAX_GTIN: u64 = 1_999_999_999_999;
+
+const EXPANDED_GSUM: [u32; 5] = [0, 348, 1388, 2948, 3988];
+const EXPANDED_EVEN_TOTAL_SUBSET: [u32; 5] = [4, 20, 52, 104, 204];
+const EXPANDED_ODD_WIDEST: [u32; 5] = [7, 5, 4, 3, 1];
+
+/// Finder patterns A to F of DataBar Expanded
+const EXPANDED_FINDER_PATTERNS: [[u8; 5]; 6] = [
+    [1, 8, 4, 1, 1],
+    [3, 6, 4, 1, 1],
+    [3, 4, 6, 1, 1],
+    [3, 2, 8, 1, 1],
+    [2, 6, 5, 1, 1],
+    [2, 2, 9, 1, 1],
+];
+
+/// Finder pattern of every pair, by the number of pairs in the symbol
+const EXPANDED_FINDER_SEQUENCES: [&[usize]; 10] = [
+    &[0, 0],
+    &[0, 1, 1],
+    &[0, 2, 1, 3],
+    &[0, 4, 1, 3, 2],
+    &[0, 4, 1, 3, 3, 5],
+    &[0, 4, 1, 3, 4, 5, 5],
+    &[0, 0, 1, 1, 2, 2, 3, 3],
+    &[0, 0, 1, 1, 2, 2, 3, 4, 4],
+    &[0, 0, 1, 1, 2, 2, 3, 4, 5, 5],
+    &[0, 0, 1, 1, 2, 3, 3, 4, 4, 5, 5],
+];
+
+/// Checksum weights of the data characters, by finder pattern, finder
+/// direction and side of the finder
+const EXPANDED_WEIGHTS: [[u32; 8]; 23] = [
+    [1, 3, 9, 27, 81, 32, 96, 77],
+    [20, 60, 180, 118, 143, 7, 21, 63],
+    [189, 145, 13, 39, 117, 140, 209, 205],
+    [193, 157, 49, 147, 19, 57, 171, 91],
+    [62, 186, 136, 197, 169, 85, 44, 132],
+    [185, 133, 188, 142, 4, 12, 36, 108],
+    [113, 128, 173, 97, 80, 29, 87, 50],
+    [150, 28, 84, 41, 123, 158, 52, 156],
+    [46, 138, 203, 187, 139, 206, 196, 166],
+    [76, 17, 51, 153, 37, 111, 122, 155],
+    [43, 129, 176, 106, 107, 110, 119, 146],
+    [16, 48, 144, 10, 30, 90, 59, 177],
+    [109, 116, 137, 200, 178, 112, 125, 164],
+    [70, 210, 208, 202, 184, 130, 179, 115],
+    [134, 191, 151, 31, 93, 68, 204, 190],
+    [148, 22, 66, 198, 172, 94, 71, 2],
+    [6, 18, 54, 162, 64, 192, 154, 40],
+    [120, 149, 25, 75, 14, 42, 126, 167],
+    [79, 26, 78, 23, 69, 207, 199, 175],
+    [103, 98, 83, 38, 114, 131, 182, 124],
+    [161, 61, 183, 127, 170, 88, 53, 159],
+    [55, 165, 73, 8, 24, 72, 5, 15],
+    [45, 135, 194, 160, 58, 174, 100, 89],
+];
+
+/// Data bits of the largest DataBar Expanded symbol, 21 data characters
+const EXPANDED_MAX_BITS: usize = 252;
+
+/// Rows of modules with their height in modules
+type SymbolRows = Vec<(Vec<bool>, usize)>;
+
+/// Generate any ^BR symbology. The composite symbologies only print their
+/// linear component, data with a 2D component after `|` is rejected rather
+/// than drawn as a partial symbol.
+pub(super) fn generate_databar(
+    symbology: DataBarType,
+    magnification: u8,
+    separator_height: u8,
+    height: usize,
+    segment_width: u8,
+    content: &str,
+) -> Result<BarcodeContent, Box<dyn Error>> {
+    let module_width = magnification as usize;
+    let separator_height = separator_height as usize;
+    let composite = matches!(
+        symbology,
+        DataBarType::UpcA
+            | DataBarType::UpcE
+            | DataBarType::Ean13
+            | DataBarType::Ean8
+            | DataBarType::Gs1128CcAb
+            | DataBarType::Gs1128CcC
+    );
+    if composite && content.contains('|') {
+        return Err("the 2D component of composite symbols is not supported".into());
+    }
+
+    match symbology {
+        DataBarType::Omnidirectional
+        | DataBarType::Truncated
+        | DataBarType::Stacked
+        | DataBarType::StackedOmnidirectional => {
+            let (gtin, text) = parse_gtin(content)?;
+            let elements = databar_elements(gtin);
+            let rows = match symbology {
+                DataBarType::Omnidirectional => vec![(modules(&elements, false), 33)],
+                DataBarType::Truncated => vec![(modules(&elements, false), 13)],
+                _ => {
+                    let top = modules(elements[..23].iter().chain(&[1, 1]), false);
+                    let bottom = modules([1, 1].iter().chain(&elements[23..]), true);
+                    if symbology == DataBarType::Stacked {
+                        let separator = stacked_separator(&top, &bottom);
+                        vec![(top, 5), (separator, separator_height), (bottom, 7)]
+                    } else {
+                        let top_separator = separator(&top, iter::once(18..33));
+                        let bottom_separator = separator(&bottom, iter::once(17..32));
+                        let middle = checkerboard(top.len());
+                        vec![
+                            (top, 33),
+                            (top_separator, separator_height),
+                            (middle, separator_height),
+                            (bottom_separator, separator_height),
+                            (bottom, 33),
+                        ]
+                    }
+                }
+            };
+            Ok(databar_symbol(&rows, module_width, text))
+        }
+        DataBarType::Limited => {
+            let (gtin, text) = parse_gtin(content)?;
+            if gtin > LIMITED_MAX_GTIN {
+                return Err(
+                    format!("GS1 DataBar Limited needs a GTIN below 2, got {content:?}").into(),
+                );
+            }
+            let rows = vec![(modules(&limited_elements(gtin), false), 10)];
+            Ok(databar_symbol(&rows, module_width, text))
+        }
+        DataBarType::Expanded => {
+            let fields = parse_element_string(content)?;
+            let rows = expanded_rows(&fields, segment_width as usize / 2, separator_height)?;
+            Ok(databar_symbol(&rows, module_width, human_readable(&fields)))
+        }
+        DataBarType::UpcA => generate_upc_a(Some(magnification), content, Some(height), true),
+        DataBarType::UpcE => generate_upc_e(Some(magnification), content, Some(height), true),
+        DataBarType::Ean13 => generate_ean13(Some(magnification), content, Some(height)),
+        DataBarType::Ean8 => generate_ean8(Some(magnification), content, Some(height)),
+        DataBarType::Gs1128CcAb | DataBarType::Gs1128CcC => generate_code_128(
+            Some(magnification),
+            content,
+            Some(height),
+            Code128Mode::Ean,
+            false,
+            false,
+        ),
+    }
+}
+
+/// 13 digit GTIN without check digit and the human readable `(01)` line.
+/// Shorter numbers are padded, a 14th digit has to be the check digit.
+fn parse_gtin(content: &str) -> Result<(u64, String), Box<dyn Error>> {
+    let digits = content.strip_prefix("(01)").unwrap_or(content);
+    if digits.is_empty() || digits.len() > 14 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("GS1 DataBar encodes up to 14 digits, got {content:?}").into());
+    }
+
+    let gtin = format!("{:0>13}", &digits[..digits.len().min(13)]);
+    let check_digit = ean_check_digit(&gtin)?;
+    if digits.len() == 14 && digits[13..] != check_digit.to_string() {
+        return Err(format!("invalid GTIN check digit in {content:?}").into());
+    }
+
+    Ok((gtin.parse()?, format!("(01){gtin}{check_digit}")))
+}
+
+/// Application Identifiers and their data, written as `(01)...(10)...` or
+/// as plain digits split by the predefined AI lengths
+pub(super) fn parse_element_string(content: &str) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let mut fields = Vec::new();
+    if content.starts_with('(') {
+        for field in content.split('(').skip(1) {
+            let (ai, data) = field.split_once(')').ok_or(format!(
+                "unterminated Application Identifier in {content:?}"
+            ))?;
+            fields.push((ai.to_string(), data.to_string()));
+        }
+    } else {
+        let mut rest = content;
+        while !rest.is_empty() {
+            let ai_length = rest
+                .get(..2)
+                .and_then(ai_length)
+                .ok_or(format!("unknown Application Identifier in {content:?}"))?;
+            let length = predefined_length(rest)
+                .unwrap_or(rest.len())
+                .min(rest.len());
+            if length < ai_length {
+                return Err(format!("missing Application Identifier data in {content:?}").into());
+            }
+            fields.push((
+                rest[..ai_length].to_string(),
+                rest[ai_length..length].to_string(),
+            ));
+            rest = &rest[length..];
+        }
+    }
+
+    for (ai, data) in &fields {
+        if !(2..=4).contains(&ai.len()) || !ai.chars().all(|c| c.is_ascii_digit()) {
+            return Err(format!("invalid Application Identifier {ai:?}").into());
+        }
+        let valid_length = match predefined_length(ai) {
+            Some(length) => data.len() == length - ai.len(),
+            None => !data.is_empty(),
+        };
+        if !valid_length {
+            return Err(format!("invalid data length for AI ({ai}): {data:?}").into());
+        }
+    }
+    if fields.is_empty() {
+        return Err("missing GS1 element string".into());
+    }
+    Ok(fields)
+}
+
+/// Number of digits of the Application Identifiers starting with `prefix`
+fn ai_length(prefix: &str) -> Option<usize> {
+    match prefix.parse::<u8>().ok()? {
+        0..=22 | 30 | 37 | 90..=99 => Some(2),
+        23..=25 | 40..=43 => Some(3),
+        31..=36 | 39 | 70..=72 | 80..=82 => Some(4),
+        _ => None,
+    }
+}
+
+/// Length of AI and data for the AIs that need no FNC1 separator
+fn predefined_length(ai: &str) -> Option<usize> {
+    match ai.get(..2)?.parse::<u8>().ok()? {
+        0 => Some(20),
+        1..=3 => Some(16),
+        4 => Some(18),
+        11..=19 => Some(8),
+        20 => Some(4),
+        31..=36 => Some(10),
+        41 => Some(16),
+        _ => None,
+    }
+}
+
+/// Encoded element string, variable length fields are terminated by FNC1
+pub(super) fn element_data(fields: &[(String, String)]) -> String {
+    let mut data = String::new();
+    for (idx, (ai, value)) in fields.iter().enumerate() {
+        data.push_str(ai);
+        data.push_str(value);
+        if predefined_length(ai).is_none() && idx + 1 < fields.len() {
+            data.push(FNC1);
+        }
+    }
+    data
+}
+
+pub(super) fn human_readable(fields: &[(String, String)]) -> String {
+    fields
+        .iter()
+        .map(|(ai, value)| format!("({ai}){value}"))
+        .collect()
+}
+
+/// Element widths of a value, the inverse of the RSS value computation in
+/// ISO/IEC 24724 with the `no_narrow` flag as used by the rxing readers
+fn rss_widths(value: u32, modules: u32, elements: u32, max_width: u32, no_narrow: bool) -> Vec<u8> {
+    let (mut value, mut modules) = (value as i64, modules as i64);
+    let (elements, max_width) = (elements as i64, max_width as i64);
+    let mut widths = Vec::new();
+    let mut narrow_mask = 0;
+    for bar in 0..elements - 1 {
+        let mut width = 1;
+        narrow_mask |= 1 << bar;
+        loop {
+            let mut sub_value = combinations(modules - width - 1, elements - bar - 2);
+            if no_narrow
+                && narrow_mask == 0
+                && modules - width - (elements - bar - 1) >= elements - bar - 1
+            {
+                sub_value -= combinations(modules - width - (elements - bar), elements - bar - 2);
+            }
+            if elements - bar - 1 > 1 {
+                let mut less_value = 0;
+                let mut widest = modules - width - (elements - bar - 2);
+                while widest > max_width {
+                    less_value += combinations(modules - width - widest - 1, elements - bar - 3);
+                    widest -= 1;
+                }
+                sub_value -= less_value * (elements - 1 - bar);
+            } else if modules - width > max_width {
+                sub_value -= 1;
+            }
+            if value < sub_value {
+                break;
+            }
+            value -= sub_value;
+            width += 1;
+            narrow_mask &= !(1 << bar);
+        }
+        modules -= width;
+        widths.push(width as u8);
+    }
+    widths.push(modules as u8);
+    widths
+}
+
+fn combinations(n: i64, r: i64) -> i64 {
+    if r < 0 || n < r {
+        return 0;
+    }
+    (0..r.min(n - r)).fold(1, |acc, k| acc * (n - k) / (k + 1))
+}
+
+/// Odd and even element widths interleaved, starting with the first odd
+/// element
+fn interleave(odd: &[u8], even: &[u8]) -> Vec<u8> {
+    odd.iter().zip(even).flat_map(|(&o, &e)| [o, e]).collect()
+}
+
+/// Weighted sum of the odd elements plus three times the even elements, with
+/// weights of powers of 9
+fn checksum_portion(widths: &[u8]) -> u32 {
+    let portion = |offset: usize| {
+        widths[offset..]
+            .iter()
+            .step_by(2)
+            .rev()
+            .fold(0, |acc, &width| acc * 9 + width as u32)
+    };
+    portion(0) + 3 * portion(1)
+}
+
+/// 16 module character on the outer side of a finder pattern
+fn outside_character(value: u32) -> Vec<u8> {
+    let group = OUTSIDE_GSUM
+        .iter()
+        .rposition(|&sum| value >= sum)
+        .unwrap_or(0);
+    let value = value - OUTSIDE_GSUM[group];
+    let total_even = OUTSIDE_EVEN_TOTAL_SUBSET[group];
+    let odd_modules = 12 - 2 * group as u32;
+    let odd_widest = OUTSIDE_ODD_WIDEST[group];
+    let odd = rss_widths(value / total_even, odd_modules, 4, odd_widest, false);
+    let even = rss_widths(
+        value % total_even,
+        16 - odd_modules,
+        4,
+        9 - odd_widest,
+        true,
+    );
+    interleave(&odd, &even)
+}
+
+/// 15 module character on the inner side of a finder pattern
+fn inside_character(value: u32) -> Vec<u8> {
+    let group = INSIDE_GSUM
+        .iter()
+        .rposition(|&sum| value >= sum)
+        .unwrap_or(0);
+    let value = value - INSIDE_GSUM[group];
+    let total_odd = INSIDE_ODD_TOTAL_SUBSET[group];
+    let even_modules = 10 - 2 * group as u32;
+    let odd_widest = INSIDE_ODD_WIDEST[group];
+    let odd = rss_widths(value % total_odd, 15 - even_modules, 4, odd_widest, true);
+    let even = rss_widths(value / total_odd, even_modules, 4, 9 - odd_widest, false);
+    interleave(&odd, &even)
+}
+
+/// The 46 elements of a DataBar Omnidirectional symbol, starting with a
+/// space. Characters on the right of a finder pattern are mirrored.
+fn databar_elements(gtin: u64) -> Vec<u8> {
+    let (left, right) = ((gtin / 4537077) as u32, (gtin % 4537077) as u32);
+    let characters = [
+        outside_character(left / 1597),
+        inside_character(left % 1597),
+        outside_character(right / 1597),
+        inside_character(right % 1597),
+    ];
+
+    let pair_portion =
+        |outside: &[u8], inside: &[u8]| checksum_portion(outside) + 4 * checksum_portion(inside);
+    let mut checksum = (pair_portion(&characters[0], &characters[1])
+        + 16 * pair_portion(&characters[2], &characters[3]))
+        % 79;
+    // finder combinations 8 and 72 are not used
+    if checksum >= 8 {
+        checksum += 1;
+    }
+    if checksum >= 72 {
+        checksum += 1;
+    }
+    let left_finder = FINDER_PATTERNS[checksum as usize / 9];
+    let right_finder = FINDER_PATTERNS[checksum as usize % 9];
+
+    let mut elements = vec![1, 1];
+    elements.extend(&characters[0]);
+    elements.extend(left_finder);
+    elements.extend(characters[1].iter().rev());
+    elements.extend(&characters[3]);
+    elements.extend(right_finder.iter().rev());
+    elements.extend(characters[2].iter().rev());
+    elements.extend([1, 1]);
+    elements
+}
+
+/// 26 module character of DataBar Limited
+fn limited_character(value: u32) -> Vec<u8> {
+    let group = LIMITED_GSUM
+        .iter()
+        .rposition(|&sum| value >= sum)
+        .unwrap_or(0);
+    let value = value - LIMITED_GSUM[group];
+    let total_even = LIMITED_EVEN_TOTAL_SUBSET[group];
+    let odd_modules = LIMITED_ODD_MODULES[group];
+    let odd_widest = LIMITED_ODD_WIDEST[group];
+    let odd = rss_widths(value / total_even, odd_modules, 7, odd_widest, false);
+    let even = rss_widths(
+        value % total_even,
+        26 - odd_modules,
+        7,
+        9 - odd_widest,
+        true,
+    );
+    interleave(&odd, &even)
+}
+
+/// 18 module check character of DataBar Limited, one of 89. The odd and
+/// even elements each spread 8 modules over 6 elements and end narrow.
+fn limited_check_character(checksum: u32) -> Vec<u8> {
+    let mut odd = rss_widths(checksum / 21, 8, 6, 3, false);
+    let mut even = rss_widths(checksum % 21, 8, 6, 3, false);
+    odd.push(1);
+    even.push(1);
+    interleave(&odd, &even)
+}
+
+/// The 46 elements of a DataBar Limited symbol, starting with a space. The
+/// check character between the two data characters replaces the finder.
+fn limited_elements(gtin: u64) -> Vec<u8> {
+    let characters = [
+        limited_character((gtin / 2013571) as u32),
+        limited_character((gtin % 2013571) as u32),
+    ];
+
+    // the weights are the powers of 3 modulo 89
+    let mut weight = 1;
+    let mut checksum = 0;
+    for &width in characters.iter().flatten() {
+        checksum += weight * width as u32;
+        weight = weight * 3 % 89;
+    }
+
+    let mut elements = vec![1, 1];
+    elements.extend(&characters[0]);
+    elements.extend(limited_check_character(checksum % 89));
+    elements.extend(&characters[1]);
+    elements.extend([1, 1]);
+    elements
+}
+
+/// Modules of alternating elements, `dark` selects the color of the first
+fn modules<'a>(elements: impl IntoIterator<Item = &'a u8>, dark: bool) -> Vec<bool> {
+    let mut modules = Vec::new();
+    for (idx, &width) in elements.into_iter().enumerate() {
+        modules.extend(iter::repeat_n(dark ^ (idx % 2 == 1), width as usize));
+    }
+    modules
+}
+
+/// Separator of DataBar Stacked, dark where both rows are light and
+/// alternating where they differ
+fn stacked_separator(top: &[bool], bottom: &[bool]) -> Vec<bool> {
+    let mut separator = vec![false; top.len()];
+    for idx in 4..top.len() - 4 {
+        separator[idx] = match top[idx] == bottom[idx] {
+            true => !top[idx],
+            false => !separator[idx - 1],
+        };
+    }
+    separator
+}
+
+/// Separator next to a row of a stacked symbol, the complement of the row
+/// with alternating modules above the finder patterns
+fn separator(row: &[bool], finders: impl IntoIterator<Item = Range<usize>>) -> Vec<bool> {
+    let mut separator = row.iter().map(|dark| !dark).collect::<Vec<_>>();
+    for finder in finders {
+        let mut dark = true;
+        for idx in finder {
+            separator[idx] = !row[idx] && dark;
+            dark = row[idx] || !dark;
+        }
+    }
+    let len = separator.len();
+    for idx in (0..4).chain(len.saturating_sub(4)..len) {
+        separator[idx] = false;
+    }
+    separator
+}
+
+fn checkerboard(width: usize) -> Vec<bool> {
+    (0..width)
+        .map(|idx| idx % 2 == 1 && (5..width.saturating_sub(4)).contains(&idx))
+        .collect()
+}
+
+/// Rows of a DataBar Expanded symbol with their height in modules, stacked
+/// when there are more than `pairs_per_row` character pairs
+fn expanded_rows(
+    fields: &[(String, String)],
+    pairs_per_row: usize,
+    separator_height: usize,
+) -> Result<SymbolRows, Box<dyn Error>> {
+    let bits = expanded_bits(fields)?;
+    let data_characters = bits
+        .chunks(12)
+        .map(|chunk| chunk.iter().fold(0, |acc, &bit| acc << 1 | bit as u32))
+        .collect::<Vec<_>>();
+    let symbol_characters = data_characters.len() + 1;
+    let pairs = symbol_characters.div_ceil(2);
+    let finders = EXPANDED_FINDER_SEQUENCES[pairs - 2];
+
+    // the check character is the left character of the first pair
+    let mut widths = vec![Vec::new()];
+    let mut checksum = 0;
+    for (idx, &value) in data_characters.iter().enumerate() {
+        let position = idx + 1;
+        let pair = position / 2;
+        let row = 4 * finders[pair] + 2 * (pair % 2) + position % 2;
+        let character = expanded_character(value);
+        checksum += character
+            .iter()
+            .zip(EXPANDED_WEIGHTS[row - 1])
+            .map(|(&width, weight)| width as u32 * weight)
+            .sum::<u32>();
+        widths.push(character);
+    }
+    widths[0] = expanded_character(211 * (symbol_characters as u32 - 4) + checksum % 211);
+
+    // left characters read forwards, right characters and every second
+    // finder pattern mirrored
+    let pair_elements = widths
+        .chunks(2)
+        .zip(finders)
+        .enumerate()
+        .map(|(pair, (characters, &finder))| {
+            let mut elements = characters[0].clone();
+            let finder = EXPANDED_FINDER_PATTERNS[finder];
+            match pair % 2 {
+                0 => elements.extend(finder),
+                _ => elements.extend(finder.iter().rev()),
+            }
+            if let Some(right) = characters.get(1) {
+                elements.extend(right.iter().rev());
+            }
+            elements
+        })
+        .collect::<Vec<_>>();
+
+    let row_count = pairs.div_ceil(pairs_per_row);
+    let mut rows = Vec::new();
+    for (row, row_pairs) in pair_elements.chunks(pairs_per_row).enumerate() {
+        let first_pair = row * pairs_per_row;
+        // even rows are read right to left when a row starts with the same
+        // finder direction as the previous one, except for a last row with
+        // an odd number of pairs which is shifted by a module instead
+        let even_row = pairs_per_row.is_multiple_of(2) && row % 2 == 1;
+        let shifted = even_row && row + 1 == row_count && row_pairs.len() % 2 == 1;
+        let reversed = even_row && !shifted;
+
+        let guard = 1 + shifted as usize;
+        let mut elements = vec![guard as u8, 1];
+        let mut finders = Vec::new();
+        for (idx, pair) in row_pairs.iter().enumerate() {
+            let start = guard + 1 + 49 * idx + 17;
+            finders.push(start..start + 15);
+            elements.extend(pair);
+        }
+        elements.extend([1, 1]);
+
+        let mut row_modules = modules(&elements, first_pair % 2 == 1);
+        if reversed {
+            row_modules.reverse();
+            let len = row_modules.len();
+            finders = finders
+                .into_iter()
+                .map(|finder| len - finder.end..len - finder.start)
+                .collect();
+        }
+        rows.push((row_modules, finders, reversed));
+    }
+
+    // reversed rows are aligned to the right edge they are read from
+    let width = rows
+        .iter()
+        .map(|(modules, ..)| modules.len())
+        .max()
+        .unwrap_or(0);
+    let align = |row: Vec<bool>, reversed: bool| match reversed {
+        true => iter::repeat_n(false, width - row.len())
+            .chain(row)
+            .collect(),
+        false => row,
+    };
+    let mut symbol = Vec::new();
+    for (idx, (modules, finders, reversed)) in rows.iter().enumerate() {
+        if idx > 0 {
+            let (previous, previous_finders, previous_reversed) = &rows[idx - 1];
+            let above = separator(previous, previous_finders.iter().cloned());
+            symbol.push((align(above, *previous_reversed), separator_height));
+            symbol.push((checkerboard(width), separator_height));
+            let below = separator(modules, finders.iter().cloned());
+            symbol.push((align(below, *reversed), separator_height));
+        }
+        symbol.push((align(modules.clone(), *reversed), 34));
+    }
+    Ok(symbol)
+}
+
+/// 17 module character of DataBar Expanded
+fn expanded_character(value: u32) -> Vec<u8> {
+    let group = EXPANDED_GSUM
+        .iter()
+        .rposition(|&sum| value >= sum)
+        .unwrap_or(0);
+    let value = value - EXPANDED_GSUM[group];
+    let total_even = EXPANDED_EVEN_TOTAL_SUBSET[group];
+    let odd_modules = 12 - 2 * group as u32;
+    let odd_widest = EXPANDED_ODD_WIDEST[group];
+    let odd = rss_widths(value / total_even, odd_modules, 4, odd_widest, true);
+    let even = rss_widths(
+        value % total_even,
+        17 - odd_modules,
+        4,
+        9 - odd_widest,
+        false,
+    );
+    interleave(&odd, &even)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Encodation {
+    Numeric,
+    Alphanumeric,
+    IsoIec646,
+}
+
+fn push_bits(bits: &mut Vec<bool>, value: u32, count: usize) {
+    bits.extend((0..count).rev().map(|bit| (value >> bit) & 1 == 1));
+}
+
+/// Data bits of a DataBar Expanded symbol, padded to whole symbol characters.
+/// A leading (01) GTIN is compressed, everything else uses the general
+/// purpose encodation.
+fn expanded_bits(fields: &[(String, String)]) -> Result<Vec<bool>, Box<dyn Error>> {
+    // linkage flag and encodation method, the symbol size bits are set last
+    let mut bits = vec![false];
+    let (gtin, rest) = match fields.split_first() {
+        Some(((ai, gtin), rest)) if ai == "01" => (Some(gtin), rest),
+        _ => (None, fields),
+    };
+    let size_bits = match gtin {
+        Some(gtin) => {
+            if ean_check_digit(&gtin[..13])?.to_string() != gtin[13..] {
+                return Err(format!("invalid GTIN check digit in (01){gtin}").into());
+            }
+            bits.extend([true, false, false]);
+            push_bits(&mut bits, gtin[..1].parse()?, 4);
+            for block in 0..4 {
+                let start = 1 + 3 * block;
+                push_bits(&mut bits, gtin[start..start + 3].parse()?, 10);
+            }
+            2
+        }
+        None => {
+            bits.extend([false, false, false, false]);
+            3
+        }
+    };
+
+    let data = element_data(rest).chars().collect::<Vec<_>>();
+    let encodation = general_purpose_bits(&data, &mut bits)?;
+
+    let symbol_bits = bits.len().div_ceil(12).max(3) * 12;
+    if symbol_bits > EXPANDED_MAX_BITS {
+        return Err("too much data for GS1 DataBar Expanded".into());
+    }
+    // latch to alphanumeric and repeated latches to ISO/IEC 646 as padding
+    let mut padding = Vec::new();
+    if encodation == Encodation::Numeric {
+        padding.extend([false; 4]);
+    }
+    while padding.len() < symbol_bits - bits.len() {
+        padding.extend([false, false, true, false, false]);
+    }
+    padding.truncate(symbol_bits - bits.len());
+    bits.extend(padding);
+
+    let symbol_characters = symbol_bits / 12 + 1;
+    bits[size_bits] = symbol_characters % 2 == 1;
+    bits[size_bits + 1] = symbol_characters > 14;
+    Ok(bits)
+}
+
+/// Encode the general purpose data, returning the final encodation mode
+fn general_purpose_bits(data: &[char], bits: &mut Vec<bool>) -> Result<Encodation, Box<dyn Error>> {
+    let numeric_value = |ch: Option<&char>| match ch {
+        Some(&FNC1) => Some(10),
+        Some(ch) => ch.to_digit(10),
+        None => None,
+    };
+    // digits until the next FNC1 or other character
+    let digit_run = |idx: usize| {
+        data[idx..]
+            .iter()
+            .take_while(|ch| ch.is_ascii_digit())
+            .count()
+    };
+
+    let mut encodation = Encodation::Numeric;
+    let mut idx = 0;
+    while idx < data.len() {
+        let ch = data[idx];
+        match encodation {
+            Encodation::Numeric => {
+                let first = numeric_value(data.get(idx));
+                let second = match data.get(idx + 1) {
+                    // a last single digit is followed by FNC1
+                    None if ch.is_ascii_digit() => Some(10),
+                    next => numeric_value(next),
+                };
+                match (first, second) {
+                    (Some(first), Some(second)) if first + second < 20 => {
+                        push_bits(bits, 8 + 11 * first + second, 7);
+                        idx += 2;
+                    }
+                    _ => {
+                        push_bits(bits, 0, 4);
+                        encodation = Encodation::Alphanumeric;
+                    }
+                }
+            }
+            _ if ch == FNC1 || digit_run(idx) >= 4 => {
+                push_bits(bits, 0, 3);
+                encodation = Encodation::Numeric;
+            }
+            Encodation::Alphanumeric => {
+                let (value, count) = match ch {
+                    '0'..='9' => (ch as u32 - '0' as u32 + 5, 5),
+                    'A'..='Z' => (ch as u32 - 33, 6),
+                    '*' => (58, 6),
+                    ',' => (59, 6),
+                    '-' => (60, 6),
+                    '.' => (61, 6),
+                    '/' => (62, 6),
+                    _ => {
+                        push_bits(bits, 0b00100, 5);
+                        encodation = Encodation::IsoIec646;
+                        continue;
+                    }
+                };
+                push_bits(bits, value, count);
+                idx += 1;
+            }
+            Encodation::IsoIec646 => {
+                const SPECIAL: &str = "!\"%&'()*+,-./:;<=>?_ ";
+                let (value, count) = match ch {
+                    '0'..='9' => (ch as u32 - '0' as u32 + 5, 5),
+                    'A'..='Z' => (ch as u32 - 1, 7),
+                    'a'..='z' => (ch as u32 - 7, 7),
+                    _ => match SPECIAL.find(ch) {
+                        Some(offset) => (232 + offset as u32, 8),
+                        None => return Err(format!("invalid GS1 character {ch:?}").into()),
+                    },
+                };
+                push_bits(bits, value, count);
+                idx += 1;
+            }
+        }
+    }
+    Ok(encodation)
+}
+
+/// Scale rows of modules by the module width and center the human readable
+/// line below the symbol
+fn databar_symbol(
+    rows: &[(Vec<bool>, usize)],
+    module_width: usize,
+    text: String,
+) -> BarcodeContent {
+    let width = rows.iter().map(|(row, _)| row.len()).max().unwrap_or(0) * module_width;
+    let mut pixels = Vec::new();
+    for (row, height) in rows {
+        let line = row
+            .iter()
+            .flat_map(|&dark| iter::repeat_n(dark as u8, module_width))
+            .chain(iter::repeat(0))
+            .take(width)
+            .collect::<Vec<_>>();
+        for _ in 0..height * module_width {
+            pixels.extend_from_slice(&line);
+        }
+    }
+    let bitmap = DecodedBitmap {
+        width,
+        height: pixels.len() / width.max(1),
+        pixels,
+    };
+
+    // the GTIN line is about as wide as an omnidirectional symbol
+    let font_width = module_width as f32 * 5.;
+    let mut barcode_content = BarcodeContent {
+        font_width,
+        text_elements: vec![],
+        bitmap,
+    };
+    barcode_content.add_text_element(
+        width as isize / 2,
+        (font_width * 0.2) as isize,
+        text,
+        Justification::Auto,
+    );
+    barcode_content
+}
+
+#[cfg(test)]
+mod tests {
+    use rxing::{BarcodeFormat, oned::rss::rss_utils::getRSSvalue};
+    use zpl_parser::DataBarType;
+
+    use crate::barcode::{
+        databar::{
+            LIMITED_EVEN_TOTAL_SUBSET, LIMITED_GSUM, LIMITED_ODD_MODULES, LIMITED_ODD_WIDEST,
+            expanded_character, generate_databar, limited_character, limited_check_character,
+            parse_element_string, rss_widths,
+        },
+        decode,
+    };
+
+    #[test]
+    fn should_invert_rss_values() {
+        for value in 0..4096 {
+            let widths = expanded_character(value);
+            assert_eq!(widths.iter().map(|&w| w as u32).sum::<u32>(), 17);
+        }
+        for value in 0..70 {
+            let widths = rss_widths(value, 10, 4, 6, true);
+            let widths: [u32; 4] = std::array::from_fn(|idx| widths[idx] as u32);
+            assert_eq!(getRSSvalue(&widths, 6, true), value);
+        }
+    }
+
+    #[test]
+    fn should_generate_omnidirectional() {
+        let barcode = generate_databar(
+            DataBarType::Omnidirectional,
+            2,
+            1,
+            25,
+            22,
+            "(01)00012345678905",
+        )
+        .unwrap();
+        assert_eq!(
+            decode(&barcode.bitmap, BarcodeFormat::RSS_14),
+            "00012345678905"
+        );
+        assert_eq!(barcode.bitmap.width, 96 * 2);
+        assert_eq!(barcode.bitmap.height, 33 * 2);
+        assert_eq!(barcode.text_elements()[0].text, "(01)00012345678905");
+
+        // the check digit is calculated for 13 digits
+        let barcode =
+            generate_databar(DataBarType::Truncated, 2, 1, 25, 22, "2001234567890").unwrap();
+        assert_eq!(
+            decode(&barcode.bitmap, BarcodeFormat::RSS_14),
+            "20012345678909"
+        );
+        assert_eq!(barcode.bitmap.height, 13 * 2);
+    }
+
+    #[test]
+    fn should_stack_omnidirectional() {
+        let barcode =
+            generate_databar(DataBarType::Stacked, 1, 1, 25, 22, "0001234567890").unwrap();
+        assert_eq!(barcode.bitmap.width, 50);
+        assert_eq!(barcode.bitmap.height, 5 + 1 + 7);
+
+        let barcode = generate_databar(
+            DataBarType::StackedOmnidirectional,
+            1,
+            2,
+            25,
+            22,
+            "0001234567890",
+        )
+        .unwrap();
+        assert_eq!(barcode.bitmap.height, 33 + 3 * 2 + 33);
+    }
+
+    #[test]
+    fn should_invert_limited_values() {
+        let values = [
+            0, 183063, 183064, 820064, 1000776, 1234567, 1491021, 1979845, 1996939,
+        ];
+        for value in values.into_iter().chain([2013570]) {
+            let widths = limited_character(value);
+            let odd: [u32; 7] = std::array::from_fn(|idx| widths[2 * idx] as u32);
+            let even: [u32; 7] = std::array::from_fn(|idx| widths[2 * idx + 1] as u32);
+            let odd_modules = odd.iter().sum::<u32>();
+            let group = LIMITED_ODD_MODULES
+                .iter()
+                .position(|&modules| modules == odd_modules)
+                .unwrap();
+            let odd_value = getRSSvalue(&odd, LIMITED_ODD_WIDEST[group], false);
+            let even_value = getRSSvalue(&even, 9 - LIMITED_ODD_WIDEST[group], true);
+            assert_eq!(
+                LIMITED_GSUM[group] + odd_value * LIMITED_EVEN_TOTAL_SUBSET[group] + even_value,
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn should_generate_limited_check_characters() {
+        assert_eq!(
+            limited_check_character(0),
+            [1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 3, 3, 1, 1]
+        );
+        assert_eq!(
+            limited_check_character(1),
+            [1, 1, 1, 1, 1, 1, 1, 1, 1, 2, 3, 2, 1, 1]
+        );
+        let characters = (0..89).map(limited_check_character).collect::<Vec<_>>();
+        for (idx, character) in characters.iter().enumerate() {
+            assert_eq!(character.iter().map(|&w| w as u32).sum::<u32>(), 18);
+            assert!(!characters[..idx].contains(character));
+        }
+    }
+
+    #[test]
+    fn should_generate_limited() {
+        let data = "(01)15012345678907";
+        let barcode = generate_databar(DataBarType::Limited, 2, 1, 25, 22, data).unwrap();
+        assert_eq!((barcode.bitmap.width, barcode.bitmap.height), (148, 20));
+        assert_eq!(barcode.text_elements()[0].text, data);
+
+        // the indicator digit of a Limited GTIN is 0 or 1
+        assert!(generate_databar(DataBarType::Limited, 2, 1, 25, 22, "2001234567890").is_err());
+    }
+
+    #[test]
+    fn should_generate_expanded() {
+        let data = "(01)98898765432106(3202)012345(15)991231";
+        let barcode = generate_databar(DataBarType::Expanded, 2, 1, 25, 22, data).unwrap();
+        assert_eq!(decode(&barcode.bitmap, BarcodeFormat::RSS_EXPANDED), data);
+        assert_eq!(barcode.text_elements()[0].text, data);
+
+        let data = "(10)ABC-123(21)xyz/7(90)12345678";
+        let barcode = generate_databar(DataBarType::Expanded, 2, 1, 25, 22, data).unwrap();
+        assert_eq!(decode(&barcode.bitmap, BarcodeFormat::RSS_EXPANDED), data);
+    }
+
+    #[test]
+    fn should_stack_expanded() {
+        let data = "(01)98898765432106(3202)012345(15)991231";
+        let barcode = generate_databar(DataBarType::Expanded, 2, 1, 25, 4, data).unwrap();
+        let single_row = generate_databar(DataBarType::Expanded, 2, 1, 25, 22, data).unwrap();
+        assert!(barcode.bitmap.width < single_row.bitmap.width);
+        assert!(barcode.bitmap.height > 2 * single_row.bitmap.height);
+
+        let barcode = generate_databar(DataBarType::Expanded, 2, 1, 25, 6, data).unwrap();
+        assert_eq!(decode(&barcode.bitmap, BarcodeFormat::RSS_EXPANDED), data);
+    }
+
+    #[test]
+    fn should_parse_element_strings() {
+        let fields = parse_element_string("0198898765432106310300123410ABC").unwrap();
+        assert_eq!(
+            fields,
+            [
+                ("01".into(), "98898765432106".into()),
+                ("3103".into(), "001234".into()),
+                ("10".into(), "ABC".into()),
+            ]
+        );
+        assert!(parse_element_string("(01)1234").is_err());
+        assert!(parse_element_string("(10").is_err());
+        assert!(parse_element_string("(01)98898765432107").is_ok());
+        assert!(
+            generate_databar(DataBarType::Expanded, 2, 1, 25, 22, "(01)98898765432107").is_err()
+        );
+    }
+
+    #[test]
+    fn should_generate_linear_components() {
+        let barcode = generate_databar(DataBarType::Ean13, 2, 1, 50, 22, "2112345678900").unwrap();
+        assert_eq!(
+            decode(&barcode.bitmap, BarcodeFormat::EAN_13),
+            "2112345678900"
+        );
+        assert_eq!(barcode.bitmap.height, 50);
+
+        let barcode = generate_databar(
+            DataBarType::Gs1128CcAb,
+            2,
+            1,
+            50,
+            22,
+            "(01)98898765432106(10)ABC",
+        )
+        .unwrap();
+        assert_eq!(barcode.text_elements()[0].text, "(01)98898765432106(10)ABC");
+    }
+
+    #[test]
+    fn should_reject_composite_data() {
+        // the linear component alone would be a partial symbol
+        assert!(
+            generate_databar(DataBarType::Ean13, 2, 1, 50, 22, "2112345678900|(21)12").is_err()
+        );
+        assert!(
+            generate_databar(
+                DataBarType::Gs1128CcAb,
+                2,
+                1,
+                50,
+                22,
+                "(01)98898765432106(10)ABC|(21)12",
+            )
+            .is_err()
+        );
+    }
+}
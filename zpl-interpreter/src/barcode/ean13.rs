@@ -1,33 +1,34 @@
 use std::error::Error;
 
-use rxing::{BarcodeFormat, EncodeHintValue, EncodeHints, Writer, oned::EAN13Writer};
+use rxing::{
+    BarcodeFormat, EncodeHintValue, EncodeHints, Writer,
+    oned::{EAN13Writer, L_AND_G_PATTERNS},
+};
 use zpl_parser::Justification;
 
-use crate::{BarcodeContent, barcode::bitmap_from_bitmatrix};
+use crate::{BarcodeContent, DecodedBitmap, barcode::bitmap_from_bitmatrix};
 
-const EAN_WIDTH_CORRECTION: f32 = 5. / 6.;
+/// Guards, 12 encoded digits and center guard
+const EAN13_MODULES: usize = 95;
+
+/// Odd (L) and even (G) parity of the 5 digit add-on by its checksum
+const EXTENSION5_PARITIES: [&str; 10] = [
+    "GGLLL", "GLGLL", "GLLGL", "GLLLG", "LGGLL", "LLGGL", "LLLGG", "LGLGL", "LGLLG", "LLGLG",
+];
 
 pub(super) fn generate_ean13(
     module_width: Option<u8>,
     content: &str,
     height: Option<usize>,
 ) -> Result<BarcodeContent, Box<dyn Error>> {
-    let content = check_ean_content(content)?;
-    let writer = EAN13Writer::default();
-
+    let content = check_ean_content(content, 12)?;
     let module_width = module_width.unwrap_or(2);
 
-    let total_width = {
-        let modules = ean13_modules(&content);
-        let width = module_width as f32 * modules as f32 * EAN_WIDTH_CORRECTION;
-        width as i32
-    };
-
     let height = height.unwrap_or(10);
-    let bitmatrix = writer.encode_with_hints(
+    let bitmatrix = EAN13Writer.encode_with_hints(
         &content,
         &BarcodeFormat::EAN_13,
-        total_width,
+        (EAN13_MODULES * module_width as usize) as i32,
         height as i32,
         &EncodeHints::default().with(EncodeHintValue::Margin("0".into())),
     )?;
@@ -42,7 +43,7 @@ pub(super) fn generate_ean13(
     };
 
     let y_shift = {
-        let rel = -0.4 * font_width as f32;
+        let rel = -0.4 * font_width;
         rel as isize
     };
 
@@ -60,29 +61,119 @@ pub(super) fn generate_ean13(
     barcode_content.add_text_element(x_shift, y_shift, text2, Justification::Left);
 
     // third part in right barcode area
-    let x_shift = {
-        let shift = module_width as isize * 51;
-        shift
-    };
+    let x_shift = { module_width as isize * 51 };
     let text3 = padd_text(&text[7..]);
     barcode_content.add_text_element(x_shift, y_shift, text3, Justification::Left);
 
     Ok(barcode_content)
 }
 
-fn padd_text(text: &str) -> String {
+/// UPC/EAN 2 or 5 digit add-on. With the text above the bars the text band
+/// is part of `height`, so the bars end with the bars of a main symbol of
+/// the same height.
+pub(super) fn generate_ean_extension(
+    module_width: Option<u8>,
+    content: &str,
+    height: Option<usize>,
+    text_above: bool,
+) -> Result<BarcodeContent, Box<dyn Error>> {
+    let content: String = content
+        .chars()
+        .map(|ch| if ch.is_ascii_digit() { ch } else { '0' })
+        .collect();
+    let digits = match content.len() {
+        ..=2 => 2,
+        _ => 5,
+    };
+    // add-ons have no check digit, short data is padded with leading zeros
+    let content = format!("{content:0>digits$}")[..digits].to_string();
+    let values = content
+        .chars()
+        .filter_map(|ch| ch.to_digit(10))
+        .collect::<Vec<_>>();
+
+    let parities = match digits {
+        2 => ["LL", "LG", "GL", "GG"][(values[0] * 10 + values[1]) as usize % 4],
+        _ => {
+            let checksum = values
+                .iter()
+                .enumerate()
+                .map(|(idx, value)| value * if idx % 2 == 0 { 3 } else { 9 })
+                .sum::<u32>();
+            EXTENSION5_PARITIES[checksum as usize % 10]
+        }
+    };
+
+    // start pattern 1011, digits separated by 01
+    let mut modules = vec![1, 0, 1, 1];
+    for (idx, (value, parity)) in values.iter().zip(parities.chars()).enumerate() {
+        if idx > 0 {
+            modules.extend([0, 1]);
+        }
+        let pattern = match parity {
+            'G' => L_AND_G_PATTERNS[*value as usize + 10],
+            _ => L_AND_G_PATTERNS[*value as usize],
+        };
+        for (element, width) in pattern.iter().enumerate() {
+            modules.extend(std::iter::repeat_n((element % 2) as u8, *width as usize));
+        }
+    }
+
+    let module_width = module_width.unwrap_or(2);
+    let font_width = { module_width as f32 * 3.95 };
+    let height = height.unwrap_or(10);
+    let text_band = match text_above {
+        true => ((font_width * 1.2).ceil() as usize).min(height.saturating_sub(1)),
+        false => 0,
+    };
+    let row = modules
+        .iter()
+        .flat_map(|&module| std::iter::repeat_n(module, module_width as usize))
+        .collect::<Vec<_>>();
+    let mut pixels = vec![0; row.len() * text_band];
+    for _ in text_band..height.max(1) {
+        pixels.extend_from_slice(&row);
+    }
+    let bitmap = DecodedBitmap {
+        width: row.len(),
+        height: height.max(1),
+        pixels,
+    };
+
+    let text_x = bitmap.width as isize / 2;
+    let text_y = match text_above {
+        true => -(bitmap.height as isize),
+        false => (font_width * 0.2) as isize,
+    };
+    let mut barcode_content = BarcodeContent {
+        font_width,
+        text_elements: Vec::new(),
+        bitmap,
+    };
+    let text = padd_text(&content).trim_end().to_string();
+    barcode_content.add_text_element(text_x, text_y, text, Justification::Auto);
+
+    Ok(barcode_content)
+}
+
+pub(super) fn padd_text(text: &str) -> String {
     let mut text1 = String::new();
     let last_idx = text.len() - 1;
     for (idx, ch) in text.chars().enumerate() {
         text1.push(ch);
         if idx != 0 || idx != last_idx {
-            text1.push_str(" ");
+            text1.push(' ');
         }
     }
     text1
 }
 
-fn check_ean_content(input: &str) -> Result<String, Box<dyn Error + 'static>> {
+/// Pad or cut the data to `digits` digits and append the check digit,
+/// data with the check digit already included is kept as it is
+pub(super) fn check_ean_content(
+    input: &str,
+    digits: usize,
+) -> Result<String, Box<dyn Error + 'static>> {
     let content_len = input.len();
     let mut content = String::new();
     for ch in input.chars() {
@@ -90,45 +181,34 @@ fn check_ean_content(input: &str) -> Result<String, Box<dyn Error + 'static>> {
         content.push(ch);
     }
 
-    if content_len != 13 {
+    if content_len != digits + 1 {
         match content.len() {
-            12 => {}
-            c if c < 12 => {
-                let remaining = 12 - c;
+            c if c == digits => {}
+            c if c < digits => {
+                let remaining = digits - c;
                 let mut filled = (0..remaining).map(|_| "0").collect::<String>();
                 filled.push_str(&content);
-                content = filled.into();
+                content = filled;
             }
-            c if c > 12 => {
-                let (part, _) = content.split_at(12);
-                content = part.to_owned().into();
+            c if c > digits => {
+                let (part, _) = content.split_at(digits);
+                content = part.to_owned();
             }
             _ => panic!("should not happen or I did something wrong"),
         };
-        let check_digit = ean13_check_digit(&content)?;
+        let check_digit = ean_check_digit(&content)?;
         content.push_str(&check_digit.to_string());
     };
     Ok(content)
 }
 
-fn ean13_modules(_data: &str) -> usize {
-    // EAN-13 structure:
-    // - Left guard: 3 modules
-    // - Left digits (6 × 7): 42 modules
-    // - Center guard: 5 modules
-    // - Right digits (6 × 7): 42 modules
-    // - Right guard: 3 modules
-    // - Quiet zones: typically 11 modules on each side
-
-    95 + 22 // 95 for barcode + 22 for quiet zones
-}
-
-fn ean13_check_digit(ean12: &str) -> Result<u8, &'static str> {
-    if ean12.len() != 12 || !ean12.chars().all(|c| c.is_ascii_digit()) {
-        return Err("EAN-12 muss genau 12 numerische Ziffern enthalten");
+/// Modulo 10 check digit of EAN and UPC data without the check digit
+pub(super) fn ean_check_digit(data: &str) -> Result<u8, &'static str> {
+    if data.is_empty() || !data.chars().all(|c| c.is_ascii_digit()) {
+        return Err("EAN darf nur numerische Ziffern enthalten");
     }
 
-    let sum: u32 = ean12
+    let sum: u32 = data
         .chars()
         .rev() // von rechts nach links
         .enumerate()
@@ -144,12 +224,84 @@ fn ean13_check_digit(ean12: &str) -> Result<u8, &'static str> {
 
 #[cfg(test)]
 mod tests {
-    use crate::barcode::ean13::ean13_check_digit;
+    use rxing::BarcodeFormat;
+
+    use crate::barcode::{
+        decode,
+        ean13::{ean_check_digit, generate_ean_extension, generate_ean13},
+    };
 
     #[test]
     fn ean13_check_digit_test() {
         let ean12 = "000012345678";
-        let check = ean13_check_digit(ean12).unwrap();
+        let check = ean_check_digit(ean12).unwrap();
         assert_eq!(check, 4);
+
+        // EAN-8 and UPC-A
+        assert_eq!(ean_check_digit("9638507").unwrap(), 4);
+        assert_eq!(ean_check_digit("03600029145").unwrap(), 2);
+    }
+
+    #[test]
+    fn should_generate_ean13_with_exact_modules() {
+        for module_width in 1..=4 {
+            let barcode = generate_ean13(Some(module_width), "400638133393", Some(50)).unwrap();
+            assert_eq!(barcode.bitmap.width, 95 * module_width as usize);
+            assert_eq!(
+                decode(&barcode.bitmap, BarcodeFormat::EAN_13),
+                "4006381333931"
+            );
+        }
+    }
+
+    fn extension_modules(content: &str) -> String {
+        let barcode = generate_ean_extension(Some(1), content, Some(40), true).unwrap();
+        let bottom_row =
+            &barcode.bitmap.pixels[(barcode.bitmap.height - 1) * barcode.bitmap.width..];
+        bottom_row.iter().map(|pixel| pixel.to_string()).collect()
+    }
+
+    #[test]
+    fn should_generate_ean_extensions() {
+        // 12 % 4 = 0 → LL
+        assert_eq!(
+            extension_modules("12"),
+            "1011".to_owned() + "0011001" + "01" + "0010011"
+        );
+        // 3 * (5 + 4 + 5) + 9 * (2 + 9) = 141 → GLGLL
+        assert_eq!(
+            extension_modules("52495"),
+            "1011".to_owned()
+                + "0111001"
+                + "01"
+                + "0010011"
+                + "01"
+                + "0011101"
+                + "01"
+                + "0001011"
+                + "01"
+                + "0110001"
+        );
+
+        let barcode = generate_ean_extension(Some(2), "12", Some(40), true).unwrap();
+        assert_eq!(barcode.bitmap.height, 40);
+        assert_eq!(barcode.bitmap.width, 20 * 2);
+        assert!(
+            barcode.bitmap.pixels[..barcode.bitmap.width]
+                .iter()
+                .all(|&p| p == 0)
+        );
+        assert_eq!(barcode.text_elements()[0].text, "1 2");
+        assert_eq!(barcode.text_elements()[0].text_y, -40);
+    }
+
+    #[test]
+    fn should_pad_short_ean_extensions() {
+        assert_eq!(extension_modules("1"), extension_modules("01"));
+        assert_eq!(extension_modules("1234"), extension_modules("01234"));
+        assert_eq!(extension_modules("123456"), extension_modules("12345"));
+
+        let barcode = generate_ean_extension(Some(2), "1234", Some(40), false).unwrap();
+        assert_eq!(barcode.text_elements()[0].text, "0 1 2 3 4");
     }
 }
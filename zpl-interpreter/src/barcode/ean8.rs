@@ -0,0 +1,76 @@
+use std::error::Error;
+
+use rxing::{BarcodeFormat, EncodeHintValue, EncodeHints, Writer, oned::EAN8Writer};
+use zpl_parser::Justification;
+
+use crate::{
+    BarcodeContent,
+    barcode::{
+        bitmap_from_bitmatrix,
+        ean13::{check_ean_content, padd_text},
+    },
+};
+
+/// Guards, 8 digits and center guard
+const EAN8_MODULES: usize = 67;
+
+pub(super) fn generate_ean8(
+    module_width: Option<u8>,
+    content: &str,
+    height: Option<usize>,
+) -> Result<BarcodeContent, Box<dyn Error>> {
+    let content = check_ean_content(content, 7)?;
+    let module_width = module_width.unwrap_or(2);
+
+    let height = height.unwrap_or(10);
+    let bitmatrix = EAN8Writer.encode_with_hints(
+        &content,
+        &BarcodeFormat::EAN_8,
+        (EAN8_MODULES * module_width as usize) as i32,
+        height as i32,
+        &EncodeHints::default().with(EncodeHintValue::Margin("0".into())),
+    )?;
+    let bitmap = bitmap_from_bitmatrix(bitmatrix)?;
+    let font_width = { module_width as f32 * 3.95 };
+
+    let mut barcode_content = BarcodeContent {
+        font_width,
+        text_elements: Vec::new(),
+        bitmap,
+    };
+
+    let y_shift = (-0.4 * font_width) as isize;
+
+    // first part in left barcode area
+    let x_shift = module_width as isize * 5;
+    let text1 = padd_text(&content[..4]);
+    barcode_content.add_text_element(x_shift, y_shift, text1, Justification::Left);
+
+    // second part in right barcode area
+    let x_shift = module_width as isize * 38;
+    let text2 = padd_text(&content[4..]);
+    barcode_content.add_text_element(x_shift, y_shift, text2, Justification::Left);
+
+    Ok(barcode_content)
+}
+
+#[cfg(test)]
+mod tests {
+    use rxing::BarcodeFormat;
+
+    use crate::barcode::{decode, ean8::generate_ean8};
+
+    #[test]
+    fn should_generate_ean8_with_check_digit() {
+        let barcode = generate_ean8(Some(3), "9638507", Some(50)).unwrap();
+        assert_eq!(decode(&barcode.bitmap, BarcodeFormat::EAN_8), "96385074");
+        assert_eq!(barcode.bitmap.width, 67 * 3);
+
+        let texts = barcode
+            .text_elements()
+            .iter()
+            .map(|elem| elem.text.replace(' ', ""))
+            .collect::<Vec<_>>();
+        assert_eq!(texts, ["9638", "5074"]);
+    }
+}
@@ -0,0 +1,452 @@
+use std::{error::Error, sync::LazyLock};
+
+use rxing::{
+    common::{
+        BitMatrix,
+        reedsolomon::{PredefinedGenericGF, ReedSolomonEncoder, get_predefined_genericgf},
+    },
+    maxicode::decoder::BitMatrixParser,
+};
+
+use crate::{BarcodeContent, DecodedBitmap};
+
+const COLUMNS: usize = 30;
+const ROWS: usize = 33;
+
+/// Nominal symbol size in mm, independent of the print resolution
+const SYMBOL_WIDTH: f32 = 28.14;
+const SYMBOL_HEIGHT: f32 = 26.91;
+
+/// Orientation modules around the finder pattern, always dark
+const ORIENTATION_MODULES: [(usize, usize); 13] = [
+    (28, 0),
+    (29, 0),
+    (10, 9),
+    (11, 9),
+    (11, 10),
+    (7, 15),
+    (8, 16),
+    (20, 16),
+    (20, 17),
+    (10, 22),
+    (10, 23),
+    (17, 22),
+    (17, 23),
+];
+
+/// Outer radii of the finder rings in module widths, dark and light
+/// alternating from the outside
+const FINDER_RADII: [f32; 6] = [4.4, 3.64, 2.88, 2.11, 1.35, 0.59];
+
+const PAD: u8 = 33;
+const NUMERIC_SHIFT: u8 = 31;
+const SHIFT: u8 = 59;
+const LATCH: u8 = 63;
+
+/// Codeword and bit of every data module, taken from the module layout of
+/// the rxing decoder
+static MODULE_BITS: LazyLock<Vec<(usize, usize, usize, u8)>> = LazyLock::new(|| {
+    let mut matrix = BitMatrix::new(COLUMNS as u32, ROWS as u32).unwrap();
+    let mut modules = Vec::new();
+    for y in 0..ROWS {
+        for x in 0..COLUMNS {
+            matrix.set(x as u32, y as u32);
+            let codewords = BitMatrixParser::new(&matrix).readCodewords();
+            if let Some(codeword) = codewords.iter().position(|&codeword| codeword != 0) {
+                modules.push((x, y, codeword, codewords[codeword]));
+            }
+            matrix.unset(x as u32, y as u32);
+        }
+    }
+    modules
+});
+
+/// Latin-1 characters of the code sets C, D and E, from value 32 on
+const SET_C_HIGH: &str =
+    "ÛÜÝÞßª¬±²³µ¹º¼½¾\u{80}\u{81}\u{82}\u{83}\u{84}\u{85}\u{86}\u{87}\u{88}\u{89}";
+const SET_D_HIGH: &str =
+    "ûüýþÿ¡¨«¯°´·¸»¿\u{8A}\u{8B}\u{8C}\u{8D}\u{8E}\u{8F}\u{90}\u{91}\u{92}\u{93}\u{94}";
+const SET_E_HIGH: &str =
+    "\u{1F}\u{9F}\u{A0}¢£¤¥¦§©\u{AD}®¶\u{95}\u{96}\u{97}\u{98}\u{99}\u{9A}\u{9B}\u{9C}\u{9D}\u{9E}";
+
+/// Value of a character in code set A or B
+type CodeSetValue = fn(char) -> Option<u8>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CodeSet {
+    A,
+    B,
+}
+
+pub(super) fn generate_maxicode(
+    mode: u8,
+    symbol_number: u8,
+    symbol_count: u8,
+    contents: &str,
+    dots_per_mm: f32,
+) -> Result<BarcodeContent, Box<dyn Error>> {
+    let codewords = encode_codewords(mode, symbol_number, symbol_count, contents)?;
+    let bitmap = draw_symbol(&module_matrix(&codewords), dots_per_mm);
+
+    Ok(BarcodeContent {
+        font_width: 0.,
+        text_elements: vec![],
+        bitmap,
+    })
+}
+
+/// All 144 codewords with error correction.
+///
+/// In mode 2 and 3 the field data starts with the structured carrier
+/// message: the three digit class of service, the three digit country code
+/// and the postal code, 9 digits in mode 2 or 6 characters in mode 3.
+fn encode_codewords(
+    mode: u8,
+    symbol_number: u8,
+    symbol_count: u8,
+    contents: &str,
+) -> Result<[u8; 144], Box<dyn Error>> {
+    let mut codewords = [0u8; 144];
+    let (primary, message) = match mode {
+        2 | 3 => {
+            let postal_length = if mode == 2 { 9 } else { 6 };
+            let split = 6 + postal_length;
+            if contents.len() < split || !contents.is_char_boundary(split) {
+                return Err("MaxiCode carrier message is incomplete".into());
+            }
+            let (primary, message) = contents.split_at(split);
+            (encode_primary(mode, primary)?, message)
+        }
+        4..=6 => ([mode; 10], contents),
+        _ => return Err(format!("unsupported MaxiCode mode {mode}").into()),
+    };
+
+    let mut message_codewords = Vec::new();
+    if symbol_count > 1 {
+        message_codewords.extend([PAD, ((symbol_number - 1) << 3) | (symbol_count - 1)]);
+    }
+    message_codewords.extend(encode_message(message)?);
+
+    // modes 4 to 6 continue the message in the primary codewords
+    let (data_len, ec_len) = if mode == 5 { (68, 56) } else { (84, 40) };
+    let capacity = match mode {
+        2 | 3 => data_len,
+        _ => data_len + 9,
+    };
+    if message_codewords.len() > capacity {
+        return Err("data too long for a MaxiCode symbol".into());
+    }
+    message_codewords.resize(capacity, PAD);
+
+    codewords[..10].copy_from_slice(&primary);
+    let secondary = match mode {
+        2 | 3 => &message_codewords[..],
+        _ => {
+            codewords[1..10].copy_from_slice(&message_codewords[..9]);
+            &message_codewords[9..]
+        }
+    };
+    codewords[20..20 + data_len].copy_from_slice(secondary);
+
+    let primary_ec = error_correction(&codewords[..10], 10)?;
+    codewords[10..20].copy_from_slice(&primary_ec);
+
+    // the secondary message is protected in two interleaved halves
+    for half in 0..2 {
+        let data = codewords[20 + half..20 + data_len]
+            .iter()
+            .step_by(2)
+            .copied()
+            .collect::<Vec<_>>();
+        let ec = error_correction(&data, ec_len / 2)?;
+        for (idx, codeword) in ec.into_iter().enumerate() {
+            codewords[20 + data_len + half + 2 * idx] = codeword;
+        }
+    }
+
+    Ok(codewords)
+}
+
+/// Pack the class of service, country code and postal code of mode 2 and 3
+fn encode_primary(mode: u8, primary: &str) -> Result<[u8; 10], Box<dyn Error>> {
+    let (service, rest) = primary.split_at(3);
+    let (country, postal_code) = rest.split_at(3);
+    let service = service
+        .parse::<u32>()
+        .map_err(|_| format!("invalid MaxiCode class of service {service:?}"))?;
+    let country = country
+        .parse::<u32>()
+        .map_err(|_| format!("invalid MaxiCode country code {country:?}"))?;
+
+    let mut codewords = [0u8; 10];
+    if mode == 2 {
+        let postal = postal_code
+            .parse::<u32>()
+            .ok()
+            .filter(|_| postal_code.bytes().all(|b| b.is_ascii_digit()))
+            .ok_or(format!("invalid MaxiCode postal code {postal_code:?}"))?;
+        let length = postal_code.len() as u32;
+        codewords[0] = (((postal & 0x03) << 4) | 2) as u8;
+        codewords[1] = ((postal & 0xfc) >> 2) as u8;
+        codewords[2] = ((postal & 0x3f00) >> 8) as u8;
+        codewords[3] = ((postal & 0xfc000) >> 14) as u8;
+        codewords[4] = ((postal & 0x3f00000) >> 20) as u8;
+        codewords[5] = (((postal & 0x3c000000) >> 26) | ((length & 0x03) << 4)) as u8;
+        codewords[6] = (((length & 0x3c) >> 2) | ((country & 0x03) << 4)) as u8;
+    } else {
+        // code set A values of the upper case postal code
+        let postal = postal_code
+            .to_uppercase()
+            .chars()
+            .map(|ch| match ch {
+                'A'..='Z' | '0'..='9' | ' ' => Ok(ch as u8 & 0x3f),
+                _ => Err(format!("invalid MaxiCode postal code {postal_code:?}")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        codewords[0] = ((postal[5] & 0x03) << 4) | 3;
+        for idx in 1..6 {
+            codewords[idx] = ((postal[5 - idx] & 0x03) << 4) | ((postal[6 - idx] & 0x3c) >> 2);
+        }
+        codewords[6] = ((postal[0] & 0x3c) >> 2) | ((country as u8 & 0x03) << 4);
+    }
+    codewords[7] = ((country & 0xfc) >> 2) as u8;
+    codewords[8] = (((country & 0x300) >> 8) | ((service & 0x0f) << 2)) as u8;
+    codewords[9] = ((service & 0x3f0) >> 4) as u8;
+    Ok(codewords)
+}
+
+/// Encode the message in code set A and B, other characters are shifted
+/// in and 9 digit runs are compressed
+fn encode_message(message: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let chars = message.chars().collect::<Vec<_>>();
+    let mut set = CodeSet::A;
+    let mut codewords = Vec::new();
+    let mut idx = 0;
+    while idx < chars.len() {
+        if let Some(digits) = chars.get(idx..idx + 9)
+            && digits.iter().all(char::is_ascii_digit)
+        {
+            let value = digits.iter().collect::<String>().parse::<u32>()?;
+            codewords.push(NUMERIC_SHIFT);
+            codewords.extend(
+                (0..5)
+                    .rev()
+                    .map(|shift| (value >> (6 * shift)) as u8 & 0x3f),
+            );
+            idx += 9;
+            continue;
+        }
+
+        let ch = chars[idx];
+        let (current, other, other_set): (CodeSetValue, CodeSetValue, _) = match set {
+            CodeSet::A => (set_a, set_b, CodeSet::B),
+            CodeSet::B => (set_b, set_a, CodeSet::A),
+        };
+        if let Some(value) = current(ch) {
+            codewords.push(value);
+        } else if let Some(value) = other(ch) {
+            // latch when the next character needs the other set as well
+            let next_in_other = chars
+                .get(idx + 1)
+                .is_some_and(|&next| current(next).is_none() && other(next).is_some());
+            if next_in_other {
+                codewords.push(LATCH);
+                set = other_set;
+            } else {
+                codewords.push(SHIFT);
+            }
+            codewords.push(value);
+        } else {
+            let (shift, value) =
+                set_c_d_e(ch).ok_or(format!("invalid MaxiCode character {ch:?}"))?;
+            codewords.extend([shift, value]);
+        }
+        idx += 1;
+    }
+    Ok(codewords)
+}
+
+fn set_a(ch: char) -> Option<u8> {
+    match ch {
+        '\r' => Some(0),
+        'A'..='Z' => Some(ch as u8 - b'A' + 1),
+        '\x1c'..='\x1e' => Some(ch as u8 - 0x1c + 28),
+        ' ' => Some(32),
+        '"'..=':' => Some(ch as u8 - b'"' + 34),
+        _ => None,
+    }
+}
+
+fn set_b(ch: char) -> Option<u8> {
+    match ch {
+        '`' => Some(0),
+        'a'..='z' => Some(ch as u8 - b'a' + 1),
+        '\x1c'..='\x1e' => Some(ch as u8 - 0x1c + 28),
+        '{' => Some(32),
+        _ => "}~\x7f;<=>?[\\]^_ ,./:@!|"
+            .find(ch)
+            .map(|idx| idx as u8 + 34),
+    }
+}
+
+/// Shift codeword and value of the Latin-1 and control characters
+fn set_c_d_e(ch: char) -> Option<(u8, u8)> {
+    let high = |set: &str| set.chars().position(|c| c == ch).map(|idx| idx as u8 + 32);
+    match ch as u32 {
+        0xc0..=0xda => Some((60, ch as u8 - 0xc0)),
+        0xe0..=0xfa => Some((61, ch as u8 - 0xe0)),
+        0x00..=0x1a => Some((62, ch as u8)),
+        0x1b => Some((62, 30)),
+        _ => high(SET_C_HIGH)
+            .map(|value| (60, value))
+            .or_else(|| high(SET_D_HIGH).map(|value| (61, value)))
+            .or_else(|| high(SET_E_HIGH).map(|value| (62, value + 3))),
+    }
+}
+
+fn error_correction(data: &[u8], ec_len: usize) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut encoder = ReedSolomonEncoder::new(get_predefined_genericgf(
+        PredefinedGenericGF::MaxicodeField64,
+    ))?;
+    let mut codewords = data
+        .iter()
+        .map(|&codeword| codeword as i32)
+        .collect::<Vec<_>>();
+    codewords.resize(data.len() + ec_len, 0);
+    encoder.encode(&mut codewords, ec_len)?;
+    Ok(codewords[data.len()..]
+        .iter()
+        .map(|&codeword| codeword as u8)
+        .collect())
+}
+
+/// Dark modules of the 30 by 33 grid, odd rows are shifted by half a module
+fn module_matrix(codewords: &[u8; 144]) -> BitMatrix {
+    let mut matrix = BitMatrix::new(COLUMNS as u32, ROWS as u32).unwrap();
+    for &(x, y, codeword, bit) in MODULE_BITS.iter() {
+        if codewords[codeword] & bit != 0 {
+            matrix.set(x as u32, y as u32);
+        }
+    }
+    for (x, y) in ORIENTATION_MODULES {
+        matrix.set(x as u32, y as u32);
+    }
+    matrix
+}
+
+/// Draw the hexagonal modules and the finder rings at the nominal symbol
+/// size for the print resolution
+fn draw_symbol(matrix: &BitMatrix, dots_per_mm: f32) -> DecodedBitmap {
+    let width = (SYMBOL_WIDTH * dots_per_mm).round() as usize;
+    let height = (SYMBOL_HEIGHT * dots_per_mm).round() as usize;
+    let module_width = width as f32 / COLUMNS as f32;
+    let row_height = height as f32 / ROWS as f32;
+    let mut pixels = vec![0; width * height];
+
+    // a small gap between neighbouring hexagons
+    let inner_radius = module_width * 0.45;
+    let outer_radius = inner_radius * 2. / 3f32.sqrt();
+    for y in 0..ROWS {
+        for x in 0..COLUMNS {
+            if !matrix.get(x as u32, y as u32) {
+                continue;
+            }
+            let center_x = (x as f32 + 0.5 + 0.5 * (y % 2) as f32) * module_width;
+            let center_y = (y as f32 + 0.5) * row_height;
+            let top = (center_y - outer_radius).floor().max(0.) as usize;
+            let bottom = ((center_y + outer_radius).ceil() as usize).min(height);
+            let left = (center_x - inner_radius).floor().max(0.) as usize;
+            let right = ((center_x + inner_radius).ceil() as usize).min(width);
+            for py in top..bottom {
+                for px in left..right {
+                    let dx = (px as f32 + 0.5 - center_x).abs();
+                    let dy = (py as f32 + 0.5 - center_y).abs();
+                    if dx <= inner_radius && dx * 0.5 + dy * 3f32.sqrt() / 2. <= inner_radius {
+                        pixels[py * width + px] = 1;
+                    }
+                }
+            }
+        }
+    }
+
+    let center_x = 14.5 * module_width;
+    let center_y = 16.5 * row_height;
+    for (py, row) in pixels.chunks_mut(width).enumerate() {
+        for (px, pixel) in row.iter_mut().enumerate() {
+            let distance = ((px as f32 + 0.5 - center_x).powi(2)
+                + (py as f32 + 0.5 - center_y).powi(2))
+            .sqrt()
+                / module_width;
+            let ring = FINDER_RADII.iter().filter(|&&r| distance < r).count();
+            if ring % 2 == 1 {
+                *pixel = 1;
+            }
+        }
+    }
+
+    DecodedBitmap {
+        width,
+        height,
+        pixels,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rxing::maxicode::decoder::decode;
+
+    use crate::barcode::maxicode::{encode_codewords, generate_maxicode, module_matrix};
+
+    fn decode_codewords(codewords: &[u8; 144]) -> String {
+        decode(&module_matrix(codewords))
+            .unwrap()
+            .getText()
+            .to_string()
+    }
+
+    #[test]
+    fn should_encode_mode_2_carrier_message() {
+        let contents = "001840152382802[)>\x1e01\x1d961Z00004951\x1dUPSN\x1d06X610\x1e\x04";
+        let codewords = encode_codewords(2, 1, 1, contents).unwrap();
+        assert_eq!(
+            decode_codewords(&codewords),
+            "[)>\x1e01\x1d96152382802\x1d840\x1d001\x1d1Z00004951\x1dUPSN\x1d06X610\x1e\x04"
+        );
+    }
+
+    #[test]
+    fn should_encode_mode_3_carrier_message() {
+        let codewords = encode_codewords(3, 1, 1, "066826B1A2B3Hello, World").unwrap();
+        assert_eq!(
+            decode_codewords(&codewords),
+            "B1A2B3\x1d826\x1d066\x1dHello, World"
+        );
+    }
+
+    #[test]
+    fn should_encode_free_text() {
+        let text = "Grüße aus Köln: 123456789 ~{mode 4}";
+        let codewords = encode_codewords(4, 1, 1, text).unwrap();
+        assert_eq!(decode_codewords(&codewords), text);
+    }
+
+    #[test]
+    fn should_reject_invalid_data() {
+        assert!(encode_codewords(2, 1, 1, "00184015238").is_err());
+        assert!(encode_codewords(2, 1, 1, "001840ABCDEFGHI").is_err());
+        assert!(encode_codewords(4, 1, 1, &"A".repeat(100)).is_err());
+    }
+
+    #[test]
+    fn should_draw_nominal_size() {
+        let barcode = generate_maxicode(4, 1, 1, "MAXICODE", 8.).unwrap();
+        assert_eq!((barcode.bitmap.width, barcode.bitmap.height), (225, 215));
+        // the center of the finder is light, the innermost ring dark
+        let center = 107 * 225 + 108;
+        assert_eq!(barcode.bitmap.pixels[center], 0);
+        assert_eq!(barcode.bitmap.pixels[center + 7], 1);
+
+        let barcode = generate_maxicode(4, 1, 1, "MAXICODE", 12.).unwrap();
+        assert_eq!((barcode.bitmap.width, barcode.bitmap.height), (338, 323));
+    }
+}
@@ -1,22 +1,55 @@
+mod aztec;
+mod codabar;
+mod code11;
 mod code128;
+mod code39;
+mod code93;
+mod data_matrix;
+mod databar;
 mod ean13;
+mod ean8;
+mod maxicode;
+mod msi;
+mod pdf417;
+mod plessey;
+mod qr;
+mod two_of_five;
+mod upc;
 
-use std::error::Error;
+use std::{error::Error, iter};
 
 use rxing::common::BitMatrix;
-use zpl_parser::{BarcodeType, Justification};
+use zpl_parser::{BarcodeType, Justification, Orientation};
 
 use crate::{BarcodeConfig, DecodedBitmap};
 
+use aztec::generate_aztec;
+use codabar::generate_codabar;
+use code11::generate_code11;
+use code39::generate_code39;
+use code93::generate_code93;
 use code128::generate_code_128;
-use ean13::generate_ean13;
+use data_matrix::generate_data_matrix;
+use databar::generate_databar;
+use ean8::generate_ean8;
+use ean13::{generate_ean_extension, generate_ean13};
+use maxicode::generate_maxicode;
+use msi::generate_msi;
+use pdf417::{generate_micro_pdf417, generate_pdf417};
+use plessey::generate_plessey;
+use qr::generate_qr;
+use two_of_five::{TwoOfFive, generate_two_of_five};
+use upc::{generate_upc_a, generate_upc_e};
 
+/// Human readable line of a barcode, `text_x` and `text_y` anchor the top
+/// left (or top center) of the unrotated text
 #[derive(Debug, Clone)]
 pub struct TextElement {
     pub text_x: isize,
     pub text_y: isize,
     pub text: String,
     pub justification: Justification,
+    pub orientation: Orientation,
 }
 
 #[derive(Debug, Clone)]
@@ -35,12 +68,58 @@ impl BarcodeContent {
         }
     }
 
-    pub(crate) fn set_text_y(&mut self, relative_to: usize) {
+    pub(crate) fn set_text_y(&mut self, y: usize) {
         for elem in self.text_elements.iter_mut() {
-            elem.text_y += (relative_to + self.bitmap.height) as isize
+            elem.text_y += y as isize
         }
     }
 
+    /// How far the human readable line reaches left of and above the label
+    pub(crate) fn text_overhang(&self) -> (usize, usize) {
+        self.text_elements
+            .iter()
+            .filter(|elem| elem.orientation == Orientation::Normal)
+            .fold((0, 0), |(x, y), elem| {
+                (
+                    x.max((-elem.text_x).max(0) as usize),
+                    y.max((-elem.text_y).max(0) as usize),
+                )
+            })
+    }
+
+    /// Rotate bars and human readable line. The bitmap is extended by the
+    /// line below the bars first, so it covers the whole field afterwards.
+    fn rotate(&mut self, orientation: Orientation) {
+        if orientation == Orientation::Normal {
+            return;
+        }
+
+        let text_bottom = self
+            .text_elements
+            .iter()
+            .map(|elem| elem.text_y + (self.font_width * 1.2).ceil() as isize)
+            .max()
+            .unwrap_or(0);
+        let text_band = (text_bottom - self.bitmap.height as isize).max(0) as usize;
+        self.bitmap.height += text_band;
+        self.bitmap
+            .pixels
+            .resize(self.bitmap.width * self.bitmap.height, 0);
+
+        let (width, height) = (self.bitmap.width as isize, self.bitmap.height as isize);
+        for elem in self.text_elements.iter_mut() {
+            let (x, y) = (elem.text_x, elem.text_y);
+            (elem.text_x, elem.text_y) = match orientation {
+                Orientation::Normal => (x, y),
+                Orientation::Rotate => (height - y, x),
+                Orientation::Invert => (width - x, height - y),
+                Orientation::BackRotate => (y, width - x),
+            };
+            elem.orientation = orientation;
+        }
+        self.bitmap = self.bitmap.rotate(orientation);
+    }
+
     pub fn add_text_element(
         &mut self,
         text_x: isize,
@@ -53,6 +132,7 @@ impl BarcodeContent {
             text_y,
             text,
             justification,
+            orientation: Orientation::Normal,
         });
     }
 
@@ -65,6 +145,7 @@ pub(super) fn barcode_from_content(
     barcode_config: Option<&BarcodeConfig>,
     barcode_type: BarcodeType,
     contents: &str,
+    dots_per_mm: f32,
 ) -> Result<BarcodeContent, Box<dyn Error>> {
     let height = {
         let height = barcode_type.height();
@@ -76,24 +157,303 @@ pub(super) fn barcode_from_content(
     };
 
     let width = barcode_config.map(|state| state.width);
+    let width_ratio = barcode_config.map(|state| state.width_ratio);
 
     let mut barcode_content = match barcode_type {
-        BarcodeType::Code39 => todo!(),
-        BarcodeType::Code128 { .. } => generate_code_128(width, contents, height)?,
-        BarcodeType::Pdf417 => todo!(),
-        BarcodeType::Ean8 => todo!(),
+        BarcodeType::Code39 {
+            check_digit,
+            show_text,
+            text_above,
+            ..
+        } => generate_code39(
+            width,
+            width_ratio,
+            contents,
+            height,
+            check_digit,
+            show_text && text_above,
+        )?,
+        BarcodeType::Code128 {
+            show_text,
+            text_above,
+            check_digit,
+            mode,
+            ..
+        } => generate_code_128(
+            width,
+            contents,
+            height,
+            mode,
+            check_digit,
+            show_text && text_above,
+        )?,
+        BarcodeType::Pdf417 {
+            security_level,
+            columns,
+            rows,
+            truncate,
+            ..
+        } => generate_pdf417(
+            width,
+            height,
+            contents,
+            security_level,
+            columns,
+            rows,
+            truncate,
+        )?,
+        BarcodeType::MicroPdf417 { .. } => generate_micro_pdf417()?,
+        BarcodeType::Ean8 { .. } => generate_ean8(width, contents, height)?,
+        BarcodeType::UpcA { check_digit, .. } => {
+            generate_upc_a(width, contents, height, check_digit)?
+        }
+        BarcodeType::UpcE { check_digit, .. } => {
+            generate_upc_e(width, contents, height, check_digit)?
+        }
+        BarcodeType::Interleaved2Of5 {
+            show_text,
+            text_above,
+            check_digit,
+            ..
+        } => generate_two_of_five(
+            TwoOfFive::Interleaved,
+            width,
+            width_ratio,
+            contents,
+            height,
+            check_digit,
+            show_text && text_above,
+        )?,
+        BarcodeType::Standard2Of5 {
+            show_text,
+            text_above,
+            ..
+        } => generate_two_of_five(
+            TwoOfFive::Standard,
+            width,
+            width_ratio,
+            contents,
+            height,
+            false,
+            show_text && text_above,
+        )?,
+        BarcodeType::Industrial2Of5 {
+            show_text,
+            text_above,
+            ..
+        } => generate_two_of_five(
+            TwoOfFive::Industrial,
+            width,
+            width_ratio,
+            contents,
+            height,
+            false,
+            show_text && text_above,
+        )?,
+        BarcodeType::Code93 {
+            show_text,
+            text_above,
+            check_digit,
+            ..
+        } => generate_code93(
+            width,
+            contents,
+            height,
+            check_digit,
+            show_text && text_above,
+        )?,
+        BarcodeType::Codabar {
+            check_digit,
+            show_text,
+            text_above,
+            start,
+            stop,
+            ..
+        } => generate_codabar(
+            width,
+            width_ratio,
+            contents,
+            height,
+            check_digit,
+            (start, stop),
+            show_text && text_above,
+        )?,
+        BarcodeType::Code11 {
+            single_check_digit,
+            show_text,
+            text_above,
+            ..
+        } => generate_code11(
+            width,
+            width_ratio,
+            contents,
+            height,
+            single_check_digit,
+            show_text && text_above,
+        )?,
+        BarcodeType::Msi {
+            check_digit,
+            show_text,
+            text_above,
+            text_check_digit,
+            ..
+        } => generate_msi(
+            width,
+            width_ratio,
+            contents,
+            height,
+            check_digit,
+            text_check_digit,
+            show_text && text_above,
+        )?,
+        BarcodeType::Plessey {
+            check_digit,
+            show_text,
+            text_above,
+            ..
+        } => generate_plessey(
+            width,
+            width_ratio,
+            contents,
+            height,
+            check_digit,
+            show_text && text_above,
+        )?,
         BarcodeType::Ean13 { .. } => generate_ean13(width, contents, height)?,
-        BarcodeType::Qr => todo!(),
-        BarcodeType::DataMatrix => todo!(),
+        BarcodeType::UpcEanExtension {
+            show_text,
+            text_above,
+            ..
+        } => generate_ean_extension(width, contents, height, show_text && text_above)?,
+        BarcodeType::DataBar {
+            symbology,
+            magnification,
+            separator_height,
+            segment_width,
+            ..
+        } => generate_databar(
+            symbology,
+            magnification,
+            separator_height,
+            height.unwrap_or(25),
+            segment_width,
+            contents,
+        )?,
+        BarcodeType::Qr {
+            model,
+            magnification,
+            error_correction,
+            mask,
+            ..
+        } => generate_qr(model, magnification, contents, error_correction, mask)?,
+        BarcodeType::Aztec {
+            magnification,
+            size,
+            ..
+        } => generate_aztec(magnification, contents, size)?,
+        BarcodeType::MaxiCode {
+            mode,
+            symbol_number,
+            symbol_count,
+        } => generate_maxicode(mode, symbol_number, symbol_count, contents, dots_per_mm)?,
+        BarcodeType::DataMatrix {
+            element_height,
+            columns,
+            rows,
+            escape_char,
+            rectangular,
+            ..
+        } => generate_data_matrix(
+            element_height,
+            height,
+            contents,
+            columns,
+            rows,
+            escape_char,
+            rectangular,
+        )?,
     };
 
+    let bitmap = &barcode_content.bitmap;
+    if bitmap.width == 0 || bitmap.height == 0 {
+        return Err(format!("empty {}x{} barcode", bitmap.width, bitmap.height).into());
+    }
+
     if !barcode_type.show_content() {
         barcode_content.text_elements.clear();
     }
 
+    // the generators place the text relative to the bottom of the bars
+    let bars_height = barcode_content.bitmap.height as isize;
+    for elem in barcode_content.text_elements.iter_mut() {
+        elem.text_y += bars_height;
+    }
+    barcode_content.rotate(barcode_type.orientation());
+
     Ok(barcode_content)
 }
 
+/// Narrow and wide element widths in dots from the ^BY module width and
+/// wide to narrow ratio
+fn element_widths(narrow_width: Option<u8>, width_ratio: Option<f32>) -> (usize, usize) {
+    let narrow = narrow_width.unwrap_or(2) as usize;
+    let wide = (narrow as f32 * width_ratio.unwrap_or(3.).clamp(2., 3.)).round() as usize;
+    (narrow, wide)
+}
+
+/// Bars and spaces alternating, starting with a bar, `true` is a wide element
+fn row_from_elements(
+    elements: impl IntoIterator<Item = bool>,
+    narrow: usize,
+    wide: usize,
+) -> Vec<u8> {
+    let mut row = Vec::new();
+    for (idx, is_wide) in elements.into_iter().enumerate() {
+        let color = (idx % 2 == 0) as u8;
+        let width = if is_wide { wide } else { narrow };
+        row.extend(iter::repeat_n(color, width));
+    }
+    row
+}
+
+/// Repeat a single row of modules `height` times and center the human
+/// readable line below the bars, or above them in a blank band
+fn linear_barcode(
+    row: &[u8],
+    height: Option<usize>,
+    font_width: f32,
+    text: String,
+    text_above: bool,
+) -> BarcodeContent {
+    let text_band = match text_above {
+        true => (font_width * 1.2).ceil() as usize,
+        false => 0,
+    };
+    let height = height.unwrap_or(10);
+    let mut pixels = vec![0; row.len() * text_band];
+    for _ in 0..height {
+        pixels.extend_from_slice(row);
+    }
+    let bitmap = DecodedBitmap {
+        width: row.len(),
+        height: text_band + height,
+        pixels,
+    };
+
+    let text_x = bitmap.width as isize / 2;
+    let text_y = match text_above {
+        true => -(bitmap.height as isize),
+        false => (font_width * 0.2) as isize,
+    };
+    let mut barcode_content = BarcodeContent {
+        font_width,
+        text_elements: vec![],
+        bitmap,
+    };
+    barcode_content.add_text_element(text_x, text_y, text, Justification::Auto);
+    barcode_content
+}
+
 fn bitmap_from_bitmatrix(bitmatrix: BitMatrix) -> Result<DecodedBitmap, Box<dyn Error>> {
     let width = bitmatrix.width() as usize;
     let height = bitmatrix.height() as usize;
@@ -129,3 +489,31 @@ fn bitmap_from_bitmatrix(bitmatrix: BitMatrix) -> Result<DecodedBitmap, Box<dyn
 //         BarcodeType::DataMatrix => todo!(),
 //     }
 // }
+
+/// Read a generated barcode back with rxing, with a quiet zone added around it
+#[cfg(test)]
+fn decode(bitmap: &DecodedBitmap, format: rxing::BarcodeFormat) -> String {
+    let quiet_zone = 40;
+    let width = bitmap.width + 2 * quiet_zone;
+    let height = bitmap.height + 2 * quiet_zone;
+    let mut luma = vec![255u8; width * height];
+    for y in 0..bitmap.height {
+        for x in 0..bitmap.width {
+            if bitmap.pixels[y * bitmap.width + x] == 1 {
+                luma[(y + quiet_zone) * width + x + quiet_zone] = 0;
+            }
+        }
+    }
+
+    let mut hints = rxing::DecodeHints::default().with(rxing::DecodeHintValue::TryHarder(true));
+    rxing::helpers::detect_in_luma_with_hints(
+        luma,
+        width as u32,
+        height as u32,
+        Some(format),
+        &mut hints,
+    )
+    .expect("decodable barcode")
+    .getText()
+    .to_string()
+}
@@ -0,0 +1,146 @@
+use std::error::Error;
+
+use zpl_parser::MsiCheckDigit;
+
+use crate::BarcodeContent;
+
+use super::{element_widths, linear_barcode, row_from_elements};
+
+pub(super) fn generate_msi(
+    narrow_width: Option<u8>,
+    width_ratio: Option<f32>,
+    content: &str,
+    height: Option<usize>,
+    check_digit: MsiCheckDigit,
+    text_check_digit: bool,
+    text_above: bool,
+) -> Result<BarcodeContent, Box<dyn Error>> {
+    if content.is_empty() || !content.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("MSI only encodes digits, got {content:?}").into());
+    }
+
+    let mut data = content.to_string();
+    match check_digit {
+        MsiCheckDigit::None => {}
+        MsiCheckDigit::Mod10 => data.push_str(&mod10(&data)),
+        MsiCheckDigit::Mod10Mod10 => {
+            data.push_str(&mod10(&data));
+            data.push_str(&mod10(&data));
+        }
+        MsiCheckDigit::Mod11Mod10 => {
+            data.push_str(&mod11(&data));
+            data.push_str(&mod10(&data));
+        }
+    }
+
+    // every bit is a bar and a space, 1 is a wide bar and 0 a wide space
+    let mut elements = vec![true, false];
+    for digit in data.bytes().map(|b| b - b'0') {
+        for bit in (0..4).rev() {
+            let set = (digit >> bit) & 1 == 1;
+            elements.extend([set, !set]);
+        }
+    }
+    elements.extend([false, true, false]);
+
+    let (narrow, wide) = element_widths(narrow_width, width_ratio);
+    let row = row_from_elements(elements, narrow, wide);
+    let text = match text_check_digit {
+        true => data,
+        false => content.to_string(),
+    };
+
+    Ok(linear_barcode(
+        &row,
+        height,
+        narrow as f32 * 9.,
+        text,
+        text_above,
+    ))
+}
+
+/// Luhn check digit, every second digit from the right is doubled
+fn mod10(data: &str) -> String {
+    let sum = data
+        .bytes()
+        .rev()
+        .enumerate()
+        .map(|(idx, b)| {
+            let digit = (b - b'0') as usize;
+            match idx % 2 {
+                0 => digit * 2 / 10 + digit * 2 % 10,
+                _ => digit,
+            }
+        })
+        .sum::<usize>();
+    ((10 - sum % 10) % 10).to_string()
+}
+
+/// IBM check digit, weighted 2 to 7 from the right, 10 takes two digits
+fn mod11(data: &str) -> String {
+    let sum = data
+        .bytes()
+        .rev()
+        .enumerate()
+        .map(|(idx, b)| (b - b'0') as usize * (idx % 6 + 2))
+        .sum::<usize>();
+    ((11 - sum % 11) % 11).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use zpl_parser::MsiCheckDigit;
+
+    use crate::barcode::msi::generate_msi;
+
+    #[test]
+    fn should_add_check_digits() {
+        let check = |check_digit| {
+            let barcode =
+                generate_msi(Some(2), None, "1234567", None, check_digit, true, false).unwrap();
+            barcode.text_elements()[0].text.clone()
+        };
+        assert_eq!(check(MsiCheckDigit::None), "1234567");
+        assert_eq!(check(MsiCheckDigit::Mod10), "12345674");
+        assert_eq!(check(MsiCheckDigit::Mod10Mod10), "123456741");
+        // 7*2 + 6*3 + 5*4 + 4*5 + 3*6 + 2*7 + 1*2 = 106 % 11 = 7 → 4
+        assert_eq!(check(MsiCheckDigit::Mod11Mod10), "123456741");
+    }
+
+    #[test]
+    fn should_hide_check_digits_in_text() {
+        let barcode = generate_msi(
+            Some(2),
+            None,
+            "1234567",
+            Some(40),
+            MsiCheckDigit::Mod10,
+            false,
+            false,
+        )
+        .unwrap();
+        assert_eq!(barcode.text_elements()[0].text, "1234567");
+        assert_eq!(barcode.bitmap.height, 40);
+    }
+
+    #[test]
+    fn should_encode_bits() {
+        let barcode = generate_msi(
+            Some(1),
+            Some(2.),
+            "5",
+            None,
+            MsiCheckDigit::None,
+            false,
+            false,
+        )
+        .unwrap();
+        let row = &barcode.bitmap.pixels[..barcode.bitmap.width];
+        let runs = row
+            .chunk_by(|a, b| a == b)
+            .map(|run| run.len())
+            .collect::<Vec<_>>();
+        // start, 0101 and stop
+        assert_eq!(runs, [2, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1, 2, 1]);
+    }
+}
@@ -0,0 +1,121 @@
+use std::error::Error;
+
+use rxing::pdf417::encoder::PDF417;
+
+use crate::{BarcodeContent, DecodedBitmap};
+
+const MAX_CODEWORDS: usize = 928;
+
+pub(super) fn generate_pdf417(
+    module_width: Option<u8>,
+    row_height: Option<usize>,
+    contents: &str,
+    security_level: u8,
+    columns: Option<u8>,
+    rows: Option<u8>,
+    truncate: bool,
+) -> Result<BarcodeContent, Box<dyn Error>> {
+    let mut encoder = PDF417::with_compact_hint(truncate);
+    // a symbol holds at most 928 codewords, fewer rows fit more columns
+    let (min_columns, max_columns) = match (columns, rows) {
+        (Some(columns), _) => (columns, columns),
+        (None, Some(rows)) => (1, (MAX_CODEWORDS / rows as usize).min(30) as u8),
+        (None, None) => (1, 30),
+    };
+    let rows = rows.map(|r| r.min((MAX_CODEWORDS / max_columns as usize) as u8));
+    let (min_rows, max_rows) = rows.map(|r| (r, r)).unwrap_or((3, 90));
+    encoder.setDimensions(
+        max_columns as u32,
+        min_columns as u32,
+        max_rows as u32,
+        min_rows as u32,
+    );
+    encoder.generateBarcodeLogic(contents, security_level as u32)?;
+
+    // the encoder stores the rows bottom up
+    let matrix = encoder
+        .getBarcodeMatrix()
+        .as_ref()
+        .ok_or("PDF417 encoding failed")?
+        .getMatrix();
+    let width = matrix.first().map(Vec::len).unwrap_or_default();
+    let height = matrix.len();
+    let pixels = matrix.into_iter().rev().flatten().collect();
+    let bitmap = DecodedBitmap {
+        width,
+        height,
+        pixels,
+    };
+
+    let module_width = module_width.unwrap_or(2) as usize;
+    let row_height = row_height.unwrap_or(10).max(1);
+    Ok(BarcodeContent {
+        font_width: 0.,
+        text_elements: vec![],
+        bitmap: bitmap.scale(module_width, row_height),
+    })
+}
+
+/// rxing has no MicroPDF417 encoder. A compact PDF417 symbol does not scan
+/// as MicroPDF417, so ^BF fields are left out of the label with a warning.
+pub(super) fn generate_micro_pdf417() -> Result<BarcodeContent, Box<dyn Error>> {
+    Err("MicroPDF417 is not supported".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use rxing::BarcodeFormat;
+
+    use crate::barcode::{
+        decode,
+        pdf417::{generate_micro_pdf417, generate_pdf417},
+    };
+
+    #[test]
+    fn should_generate_pdf417() {
+        let barcode = generate_pdf417(
+            Some(2),
+            Some(6),
+            "SHIPPING MANIFEST 0042",
+            3,
+            Some(4),
+            None,
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            decode(&barcode.bitmap, BarcodeFormat::PDF_417),
+            "SHIPPING MANIFEST 0042"
+        );
+        // start, left indicator, 4 data columns, right indicator and stop pattern
+        assert_eq!(barcode.bitmap.width, 2 * ((4 + 4) * 17 + 1));
+        assert_eq!(barcode.bitmap.height % 6, 0);
+    }
+
+    #[test]
+    fn should_truncate_pdf417() {
+        let full = generate_pdf417(Some(1), Some(3), "12345", 0, Some(2), Some(6), false).unwrap();
+        let truncated =
+            generate_pdf417(Some(1), Some(3), "12345", 0, Some(2), Some(6), true).unwrap();
+        assert_eq!(full.bitmap.height, 6 * 3);
+        assert!(truncated.bitmap.width < full.bitmap.width);
+    }
+
+    #[test]
+    fn should_limit_pdf417_to_928_codewords() {
+        let barcode = generate_pdf417(Some(1), Some(1), "x", 3, Some(30), Some(90), false).unwrap();
+        assert_eq!(decode(&barcode.bitmap, BarcodeFormat::PDF_417), "x");
+        // 928 / 30 columns
+        assert_eq!(barcode.bitmap.height, 30);
+
+        // without a column count the rows are kept, with at most 928 / 90 columns
+        let barcode = generate_pdf417(Some(1), Some(1), "x", 3, None, Some(90), false).unwrap();
+        assert_eq!(decode(&barcode.bitmap, BarcodeFormat::PDF_417), "x");
+        assert_eq!(barcode.bitmap.height, 90);
+    }
+
+    #[test]
+    fn should_reject_micro_pdf417() {
+        assert!(generate_micro_pdf417().is_err());
+    }
+}
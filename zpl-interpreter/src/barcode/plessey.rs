@@ -0,0 +1,112 @@
+use std::error::Error;
+
+use crate::BarcodeContent;
+
+use super::{element_widths, linear_barcode, row_from_elements};
+
+/// CRC polynomial x^8 + x^7 + x^6 + x^5 + x^3 + 1
+const CRC_POLYNOMIAL: [bool; 9] = [true, true, true, true, false, true, false, false, true];
+
+const START_BITS: [bool; 4] = [true, true, false, true];
+
+/// Termination bar and stop character
+const STOP_ELEMENTS: [bool; 9] = [true, true, false, true, false, false, true, false, true];
+
+pub(super) fn generate_plessey(
+    narrow_width: Option<u8>,
+    width_ratio: Option<f32>,
+    content: &str,
+    height: Option<usize>,
+    check_digit: bool,
+    text_above: bool,
+) -> Result<BarcodeContent, Box<dyn Error>> {
+    let mut text = content.to_uppercase();
+    let values = text
+        .chars()
+        .map(|ch| {
+            ch.to_digit(16)
+                .ok_or(format!("invalid Plessey character {ch:?}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // hex digits with the least significant bit first, followed by 8 CRC bits
+    let mut bits = values
+        .iter()
+        .flat_map(|value| (0..4).map(move |bit| (value >> bit) & 1 == 1))
+        .collect::<Vec<_>>();
+    let data_bits = bits.len();
+    let mut crc = bits.clone();
+    crc.extend([false; 8]);
+    for idx in 0..data_bits {
+        if crc[idx] {
+            for (bit, &set) in CRC_POLYNOMIAL.iter().enumerate() {
+                crc[idx + bit] ^= set;
+            }
+        }
+    }
+    let crc = &crc[data_bits..];
+    bits.extend_from_slice(crc);
+
+    if check_digit {
+        for nibble in crc.chunks(4) {
+            let value = nibble
+                .iter()
+                .enumerate()
+                .map(|(bit, &set)| (set as u32) << bit)
+                .sum::<u32>();
+            text.extend(char::from_digit(value, 16).map(|ch| ch.to_ascii_uppercase()));
+        }
+    }
+
+    // every bit is a bar and a space, 1 is a wide bar and 0 a wide space
+    let mut elements = Vec::new();
+    for set in START_BITS.into_iter().chain(bits) {
+        elements.extend([set, !set]);
+    }
+    elements.extend(STOP_ELEMENTS);
+
+    let (narrow, wide) = element_widths(narrow_width, width_ratio);
+    let row = row_from_elements(elements, narrow, wide);
+
+    Ok(linear_barcode(
+        &row,
+        height,
+        narrow as f32 * 9.,
+        text,
+        text_above,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::barcode::plessey::generate_plessey;
+
+    #[test]
+    fn should_add_crc_check_digits() {
+        let barcode = generate_plessey(Some(2), None, "12ab", Some(40), true, false).unwrap();
+        assert_eq!(barcode.text_elements()[0].text, "12AB8C");
+        assert_eq!(barcode.bitmap.height, 40);
+
+        let barcode = generate_plessey(Some(2), None, "12AB", None, false, false).unwrap();
+        assert_eq!(barcode.text_elements()[0].text, "12AB");
+    }
+
+    #[test]
+    fn should_encode_bits_lsb_first() {
+        let barcode = generate_plessey(Some(1), Some(3.), "1", None, false, false).unwrap();
+        let row = &barcode.bitmap.pixels[..barcode.bitmap.width];
+        let runs = row
+            .chunk_by(|a, b| a == b)
+            .map(|run| run.len())
+            .collect::<Vec<_>>();
+        // start 1101, then 1 = 1000
+        assert_eq!(runs[..16], [3, 1, 3, 1, 1, 3, 3, 1, 3, 1, 1, 3, 1, 3, 1, 3]);
+        // 4 data and 8 CRC bits between start and stop
+        assert_eq!(runs.len(), 2 * (4 + 4 + 8) + 9);
+    }
+
+    #[test]
+    fn should_reject_invalid_characters() {
+        assert!(generate_plessey(None, None, "12G", None, false, false).is_err());
+    }
+}
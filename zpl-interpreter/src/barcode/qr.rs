@@ -0,0 +1,145 @@
+use std::error::Error;
+
+use rxing::{BarcodeFormat, EncodeHintValue, EncodeHints, Writer, qrcode::QRCodeWriter};
+use zpl_parser::QrErrorCorrection;
+
+use crate::{BarcodeContent, barcode::bitmap_from_bitmatrix};
+
+pub(super) fn generate_qr(
+    model: u8,
+    magnification: Option<u8>,
+    contents: &str,
+    error_correction: QrErrorCorrection,
+    mask: u8,
+) -> Result<BarcodeContent, Box<dyn Error>> {
+    // rxing only encodes Model 2 symbols, Model 1 has a different layout
+    if model != 2 {
+        return Err(format!("QR Model {model} is not supported").into());
+    }
+
+    let default_level = match error_correction {
+        QrErrorCorrection::High => 'H',
+        QrErrorCorrection::Quality => 'Q',
+        QrErrorCorrection::Medium => 'M',
+        QrErrorCorrection::Low => 'L',
+    };
+    let (level, data) = split_field_data(contents)?;
+    let level = level.unwrap_or(default_level);
+
+    let hints = EncodeHints::default()
+        .with(EncodeHintValue::ErrorCorrection(level.to_string()))
+        .with(EncodeHintValue::QrMaskPattern(mask.to_string()))
+        .with(EncodeHintValue::CharacterSet("UTF-8".into()))
+        .with(EncodeHintValue::Margin("0".into()));
+    // one dot per module, enlarged by the magnification afterwards
+    let bit_matrix =
+        QRCodeWriter.encode_with_hints(&data, &BarcodeFormat::QR_CODE, 0, 0, &hints)?;
+    let module_size = magnification.unwrap_or(2) as usize;
+    let bitmap = bitmap_from_bitmatrix(bit_matrix)?.scale(module_size, module_size);
+
+    Ok(BarcodeContent {
+        font_width: 0.,
+        text_elements: vec![],
+        bitmap,
+    })
+}
+
+/// Split the ^FD switches from the data.
+///
+/// The field data starts with the error correction level (H, Q, M or L) and
+/// the input mode, A for automatic or M for manual, followed by a comma. In
+/// manual mode every segment starts with its character mode: N numeric,
+/// A alphanumeric, K Kanji or B binary with a four digit byte count. Mixed
+/// mode data is prefixed by `D`, the symbol number, the symbol count and the
+/// parity byte.
+fn split_field_data(contents: &str) -> Result<(Option<char>, String), Box<dyn Error>> {
+    let contents = match contents.strip_prefix('D') {
+        Some(rest) if rest.len() > 7 && rest.as_bytes()[6] == b',' => &rest[7..],
+        _ => contents,
+    };
+
+    let mut chars = contents.chars();
+    let (level, mode) = match (chars.next(), chars.next(), chars.next()) {
+        (Some(level @ ('H' | 'Q' | 'M' | 'L')), Some(mode @ ('A' | 'M')), Some(',')) => {
+            (level, mode)
+        }
+        _ => return Ok((None, contents.to_string())),
+    };
+    let mut rest = chars.as_str();
+    if mode == 'A' {
+        return Ok((Some(level), rest.to_string()));
+    }
+
+    let mut data = String::new();
+    while let Some(character_mode) = rest.chars().next() {
+        rest = &rest[character_mode.len_utf8()..];
+        let segment = match character_mode {
+            'B' => {
+                let count = rest
+                    .get(..4)
+                    .and_then(|count| count.parse::<usize>().ok())
+                    .ok_or("QR binary segment without byte count")?;
+                rest = &rest[4..];
+                // the count is in bytes, a split character is taken whole
+                let end = (count..=rest.len())
+                    .find(|&end| rest.is_char_boundary(end))
+                    .unwrap_or(rest.len());
+                &rest[..end]
+            }
+            'N' | 'A' | 'K' => rest.split(',').next().unwrap_or_default(),
+            _ => return Err(format!("invalid QR character mode {character_mode:?}").into()),
+        };
+        data.push_str(segment);
+        rest = &rest[segment.len()..];
+        rest = rest.strip_prefix(',').unwrap_or(rest);
+    }
+
+    Ok((Some(level), data))
+}
+
+#[cfg(test)]
+mod tests {
+    use rxing::BarcodeFormat;
+    use zpl_parser::QrErrorCorrection;
+
+    use crate::barcode::{
+        decode,
+        qr::{generate_qr, split_field_data},
+    };
+
+    #[test]
+    fn should_split_field_data() {
+        let split = |data| split_field_data(data).unwrap();
+        assert_eq!(
+            split("QA,https://example.com"),
+            (Some('Q'), "https://example.com".into())
+        );
+        assert_eq!(split("HM,N0123456789"), (Some('H'), "0123456789".into()));
+        assert_eq!(split("LM,B0006a,b,cd,N42"), (Some('L'), "a,b,cd42".into()));
+        assert_eq!(
+            split("D03048F,LM,N0123456789,A12AABB,B0006qrcode"),
+            (Some('L'), "012345678912AABBqrcode".into())
+        );
+        assert_eq!(split("no switches"), (None, "no switches".into()));
+
+        // binary byte counts are in bytes, not characters
+        assert_eq!(split("LM,B0003\u{e9}a,N1"), (Some('L'), "\u{e9}a1".into()));
+        assert!(split_field_data("LM,\u{e9}").is_err());
+    }
+
+    #[test]
+    fn should_generate_qr_at_magnification() {
+        let barcode =
+            generate_qr(2, Some(4), "MA,Hello QR", QrErrorCorrection::Quality, 7).unwrap();
+        assert_eq!(decode(&barcode.bitmap, BarcodeFormat::QR_CODE), "Hello QR");
+        // version 1 has 21 modules
+        assert_eq!(barcode.bitmap.width, 21 * 4);
+        assert_eq!(barcode.bitmap.height, 21 * 4);
+        assert!(barcode.text_elements().is_empty());
+    }
+
+    #[test]
+    fn should_reject_model_1() {
+        assert!(generate_qr(1, Some(4), "MA,Hello QR", QrErrorCorrection::Quality, 7).is_err());
+    }
+}
@@ -0,0 +1,203 @@
+use std::error::Error;
+
+use crate::BarcodeContent;
+
+use super::{ean13::ean_check_digit, element_widths, linear_barcode, row_from_elements};
+
+/// Narrow (`false`) and wide (`true`) elements of the digits 0 to 9
+const DIGIT_PATTERNS: [[bool; 5]; 10] = [
+    [false, false, true, true, false],
+    [true, false, false, false, true],
+    [false, true, false, false, true],
+    [true, true, false, false, false],
+    [false, false, true, false, true],
+    [true, false, true, false, false],
+    [false, true, true, false, false],
+    [false, false, false, true, true],
+    [true, false, false, true, false],
+    [false, true, false, true, false],
+];
+
+const N: bool = false;
+const W: bool = true;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum TwoOfFive {
+    /// `^B2`, digit pairs interleaved in bars and spaces
+    Interleaved,
+    /// `^BJ`, every digit in 3 bars and 2 spaces
+    Standard,
+    /// `^BI`, every digit in 5 bars separated by narrow spaces
+    Industrial,
+}
+
+pub(super) fn generate_two_of_five(
+    kind: TwoOfFive,
+    narrow_width: Option<u8>,
+    width_ratio: Option<f32>,
+    content: &str,
+    height: Option<usize>,
+    check_digit: bool,
+    text_above: bool,
+) -> Result<BarcodeContent, Box<dyn Error>> {
+    if content.is_empty() || !content.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("2 of 5 only encodes digits, got {content:?}").into());
+    }
+
+    let mut text = content.to_string();
+    if check_digit {
+        let check = ean_check_digit(&text)?;
+        text.push(char::from(b'0' + check));
+    }
+    if kind == TwoOfFive::Interleaved && text.len() % 2 == 1 {
+        text.insert(0, '0');
+    }
+    let digits = text
+        .bytes()
+        .map(|b| DIGIT_PATTERNS[(b - b'0') as usize])
+        .collect::<Vec<_>>();
+
+    // bars and spaces alternating, starting with a bar
+    let elements = match kind {
+        TwoOfFive::Interleaved => {
+            let mut elements = vec![N, N, N, N];
+            for pair in digits.chunks(2) {
+                for (bar, space) in pair[0].iter().zip(pair[1]) {
+                    elements.extend([*bar, space]);
+                }
+            }
+            elements.extend([W, N, N]);
+            elements
+        }
+        TwoOfFive::Standard => {
+            let mut elements = vec![W, N, N, N, N, N];
+            for digit in &digits {
+                elements.extend(digit);
+                elements.push(N);
+            }
+            elements.extend([W, N, N, N, N]);
+            elements
+        }
+        TwoOfFive::Industrial => {
+            let mut elements = vec![W, N, W, N, N, N];
+            for digit in &digits {
+                for bar in digit {
+                    elements.extend([*bar, N]);
+                }
+            }
+            elements.extend([W, N, N, N, W]);
+            elements
+        }
+    };
+
+    let (narrow, wide) = element_widths(narrow_width, width_ratio);
+    let row = row_from_elements(elements, narrow, wide);
+
+    Ok(linear_barcode(
+        &row,
+        height,
+        narrow as f32 * 9.,
+        text,
+        text_above,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use rxing::BarcodeFormat;
+
+    use crate::barcode::{
+        decode,
+        two_of_five::{TwoOfFive, generate_two_of_five},
+    };
+
+    /// Widths of the bars and spaces in the first row
+    fn runs(row: &[u8]) -> Vec<usize> {
+        row.chunk_by(|a, b| a == b).map(|run| run.len()).collect()
+    }
+
+    #[test]
+    fn should_generate_interleaved_with_check_digit() {
+        let barcode = generate_two_of_five(
+            TwoOfFive::Interleaved,
+            Some(2),
+            Some(3.),
+            "1234567",
+            Some(50),
+            true,
+            false,
+        )
+        .unwrap();
+        // 1*3 + 2 + 3*3 + 4 + 5*3 + 6 + 7*3 = 60 → check digit 0
+        assert_eq!(barcode.text_elements()[0].text, "12345670");
+        assert_eq!(decode(&barcode.bitmap, BarcodeFormat::ITF), "12345670");
+        assert_eq!(barcode.bitmap.height, 50);
+    }
+
+    #[test]
+    fn should_pad_interleaved_to_even_length() {
+        let barcode = generate_two_of_five(
+            TwoOfFive::Interleaved,
+            Some(2),
+            None,
+            "12345",
+            None,
+            false,
+            false,
+        )
+        .unwrap();
+        assert_eq!(barcode.text_elements()[0].text, "012345");
+        assert_eq!(decode(&barcode.bitmap, BarcodeFormat::ITF), "012345");
+    }
+
+    #[test]
+    fn should_use_width_ratio_for_wide_elements() {
+        let barcode = generate_two_of_five(
+            TwoOfFive::Industrial,
+            Some(2),
+            Some(2.5),
+            "1",
+            None,
+            false,
+            false,
+        )
+        .unwrap();
+        let row = &barcode.bitmap.pixels[..barcode.bitmap.width];
+        // start, digit 1 = W N N N W bars with narrow spaces, stop
+        assert_eq!(
+            runs(row),
+            [
+                5, 2, 5, 2, 2, 2, 5, 2, 2, 2, 2, 2, 2, 2, 5, 2, 5, 2, 2, 2, 5
+            ]
+        );
+    }
+
+    #[test]
+    fn should_generate_standard() {
+        let barcode = generate_two_of_five(
+            TwoOfFive::Standard,
+            Some(1),
+            Some(3.),
+            "0",
+            None,
+            false,
+            false,
+        )
+        .unwrap();
+        let row = &barcode.bitmap.pixels[..barcode.bitmap.width];
+        // start, digit 0 = N N W W N and a narrow space, stop
+        assert_eq!(
+            runs(row),
+            [3, 1, 1, 1, 1, 1, 1, 1, 3, 3, 1, 1, 3, 1, 1, 1, 1]
+        );
+        assert_eq!(row[0], 1);
+    }
+
+    #[test]
+    fn should_reject_non_digits() {
+        assert!(
+            generate_two_of_five(TwoOfFive::Industrial, None, None, "12A", None, false, false)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use std::error::Error;
+
+use rxing::{
+    BarcodeFormat, EncodeHintValue, EncodeHints, Writer,
+    oned::{UPCAWriter, UPCEWriter},
+};
+use zpl_parser::Justification;
+
+use crate::{
+    BarcodeContent,
+    barcode::{
+        bitmap_from_bitmatrix,
+        ean13::{check_ean_content, padd_text},
+    },
+};
+
+/// Guards and 12 digits
+const UPC_A_MODULES: usize = 95;
+/// Guards and 6 digits
+const UPC_E_MODULES: usize = 51;
+
+pub(super) fn generate_upc_a(
+    module_width: Option<u8>,
+    content: &str,
+    height: Option<usize>,
+    print_check_digit: bool,
+) -> Result<BarcodeContent, Box<dyn Error>> {
+    let content = check_ean_content(content, 11)?;
+    let module_width = module_width.unwrap_or(2);
+
+    let height = height.unwrap_or(10);
+    let bitmatrix = UPCAWriter::default().encode_with_hints(
+        &content,
+        &BarcodeFormat::UPC_A,
+        (UPC_A_MODULES * module_width as usize) as i32,
+        height as i32,
+        &EncodeHints::default().with(EncodeHintValue::Margin("0".into())),
+    )?;
+    let bitmap = bitmap_from_bitmatrix(bitmatrix)?;
+    let font_width = { module_width as f32 * 3.95 };
+
+    let mut barcode_content = BarcodeContent {
+        font_width,
+        text_elements: Vec::new(),
+        bitmap,
+    };
+
+    let y_shift = (-0.4 * font_width) as isize;
+
+    // number system before barcode
+    let x_shift = -(module_width as isize * 6);
+    let text1 = content[..1].to_string();
+    barcode_content.add_text_element(x_shift, y_shift, text1, Justification::Left);
+
+    // manufacturer in left barcode area
+    let x_shift = module_width as isize * 13;
+    let text2 = padd_text(&content[1..6]);
+    barcode_content.add_text_element(x_shift, y_shift, text2, Justification::Left);
+
+    // product in right barcode area
+    let x_shift = module_width as isize * 51;
+    let text3 = padd_text(&content[6..11]);
+    barcode_content.add_text_element(x_shift, y_shift, text3, Justification::Left);
+
+    // check digit after barcode
+    if print_check_digit {
+        let x_shift = module_width as isize * 97;
+        let text4 = content[11..].to_string();
+        barcode_content.add_text_element(x_shift, y_shift, text4, Justification::Left);
+    }
+
+    Ok(barcode_content)
+}
+
+/// The field data holds manufacturer and product code of the UPC-A number
+/// with number system 0, it is zero suppressed to six digits
+pub(super) fn generate_upc_e(
+    module_width: Option<u8>,
+    content: &str,
+    height: Option<usize>,
+    print_check_digit: bool,
+) -> Result<BarcodeContent, Box<dyn Error>> {
+    let upc_a = match content.len() {
+        ..=10 => check_ean_content(&format!("0{content}"), 11)?,
+        _ => check_ean_content(content, 11)?,
+    };
+    let compressed =
+        zero_suppress(&upc_a[..11]).ok_or(format!("{upc_a} can not be encoded as UPC-E"))?;
+    let content = format!("{}{compressed}{}", &upc_a[..1], &upc_a[11..]);
+    let module_width = module_width.unwrap_or(2);
+
+    let height = height.unwrap_or(10);
+    let bitmatrix = UPCEWriter.encode_with_hints(
+        &content,
+        &BarcodeFormat::UPC_E,
+        (UPC_E_MODULES * module_width as usize) as i32,
+        height as i32,
+        &EncodeHints::default().with(EncodeHintValue::Margin("0".into())),
+    )?;
+    let bitmap = bitmap_from_bitmatrix(bitmatrix)?;
+    let font_width = { module_width as f32 * 3.95 };
+
+    let mut barcode_content = BarcodeContent {
+        font_width,
+        text_elements: Vec::new(),
+        bitmap,
+    };
+
+    let y_shift = (-0.4 * font_width) as isize;
+
+    // number system before barcode
+    let x_shift = -(module_width as isize * 6);
+    let text1 = content[..1].to_string();
+    barcode_content.add_text_element(x_shift, y_shift, text1, Justification::Left);
+
+    // zero suppressed number in barcode area
+    let x_shift = module_width as isize * 6;
+    let text2 = padd_text(&content[1..7]);
+    barcode_content.add_text_element(x_shift, y_shift, text2, Justification::Left);
+
+    // check digit after barcode
+    if print_check_digit {
+        let x_shift = module_width as isize * 53;
+        let text3 = content[7..].to_string();
+        barcode_content.add_text_element(x_shift, y_shift, text3, Justification::Left);
+    }
+
+    Ok(barcode_content)
+}
+
+/// Six digit UPC-E form of number system, manufacturer and product code
+fn zero_suppress(upc_a: &str) -> Option<String> {
+    let (manufacturer, product) = (&upc_a[1..6], &upc_a[6..11]);
+    let digits = |text: &str| text.to_string();
+
+    if matches!(&manufacturer[2..], "000" | "100" | "200") && product.starts_with("00") {
+        Some(digits(&manufacturer[..2]) + &product[2..] + &manufacturer[2..3])
+    } else if manufacturer.ends_with("00") && product.starts_with("000") {
+        Some(digits(&manufacturer[..3]) + &product[3..] + "3")
+    } else if manufacturer.ends_with('0') && product.starts_with("0000") {
+        Some(digits(&manufacturer[..4]) + &product[4..] + "4")
+    } else if product.starts_with("0000") && product[4..] >= *"5" {
+        Some(digits(manufacturer) + &product[4..])
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rxing::BarcodeFormat;
+
+    use crate::barcode::{
+        decode,
+        upc::{generate_upc_a, generate_upc_e, zero_suppress},
+    };
+
+    #[test]
+    fn should_generate_upc_a() {
+        let barcode = generate_upc_a(Some(2), "03600029145", Some(60), true).unwrap();
+        assert_eq!(
+            decode(&barcode.bitmap, BarcodeFormat::UPC_A),
+            "036000291452"
+        );
+        assert_eq!(barcode.bitmap.width, 95 * 2);
+
+        let texts = barcode
+            .text_elements()
+            .iter()
+            .map(|elem| elem.text.replace(' ', ""))
+            .collect::<Vec<_>>();
+        assert_eq!(texts, ["0", "36000", "29145", "2"]);
+
+        let barcode = generate_upc_a(Some(2), "03600029145", Some(60), false).unwrap();
+        assert_eq!(barcode.text_elements().len(), 3);
+    }
+
+    #[test]
+    fn should_zero_suppress_upc_e() {
+        assert_eq!(zero_suppress("01200000345").as_deref(), Some("123450"));
+        assert_eq!(zero_suppress("01230000045").as_deref(), Some("123453"));
+        assert_eq!(zero_suppress("01234000005").as_deref(), Some("123454"));
+        assert_eq!(zero_suppress("01234500007").as_deref(), Some("123457"));
+        assert_eq!(zero_suppress("01234567890"), None);
+    }
+
+    #[test]
+    fn should_generate_upc_e() {
+        let barcode = generate_upc_e(Some(2), "1230000045", Some(60), true).unwrap();
+        assert_eq!(barcode.bitmap.width, 51 * 2);
+        let texts = barcode
+            .text_elements()
+            .iter()
+            .map(|elem| elem.text.replace(' ', ""))
+            .collect::<Vec<_>>();
+        assert_eq!(texts, ["0", "123453", "1"]);
+        assert_eq!(decode(&barcode.bitmap, BarcodeFormat::UPC_E), "01234531");
+
+        assert!(generate_upc_e(Some(2), "1234567890", None, true).is_err());
+    }
+}
@@ -105,7 +105,7 @@ fn decompose(secs: u64) -> (u64, u64, u64, u64, u64, u64) {
 }
 
 fn is_leap(year: u64) -> bool {
-    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+    (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
 }
 
 #[cfg(test)]
@@ -135,7 +135,6 @@ mod tests {
             hour: Some(1),
             minute: Some(1),
             second: Some(1),
-            ..Default::default()
         };
         let res = format_timestamp("+Y+.+d.+m +H:+M:+S", &['+'], &real_time_clock_setup);
         assert_eq!(&res, "0001+.01.01 01:01:01")
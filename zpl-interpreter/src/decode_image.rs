@@ -1,5 +1,5 @@
 use base64::{Engine, engine::general_purpose};
-use zpl_parser::CompressionMethod;
+use zpl_parser::{CompressionMethod, Orientation};
 
 #[derive(Debug, Clone, Default)]
 pub struct DecodedBitmap {
@@ -8,6 +8,56 @@ pub struct DecodedBitmap {
     pub pixels: Vec<u8>, // 0 = white, 1 = black
 }
 
+impl DecodedBitmap {
+    /// Rotate clockwise like a field with `orientation`
+    pub(crate) fn rotate(&self, orientation: Orientation) -> Self {
+        let (width, height) = (self.width, self.height);
+        let pixel = |x: usize, y: usize| self.pixels[y * width + x];
+        let (new_width, new_height) = match orientation {
+            Orientation::Normal | Orientation::Invert => (width, height),
+            Orientation::Rotate | Orientation::BackRotate => (height, width),
+        };
+
+        let mut pixels = Vec::with_capacity(self.pixels.len());
+        for y in 0..new_height {
+            for x in 0..new_width {
+                pixels.push(match orientation {
+                    Orientation::Normal => pixel(x, y),
+                    Orientation::Rotate => pixel(y, height - 1 - x),
+                    Orientation::Invert => pixel(width - 1 - x, height - 1 - y),
+                    Orientation::BackRotate => pixel(width - 1 - y, x),
+                });
+            }
+        }
+
+        Self {
+            width: new_width,
+            height: new_height,
+            pixels,
+        }
+    }
+
+    /// Enlarge every pixel to a block of `x_factor` by `y_factor` pixels
+    pub(crate) fn scale(&self, x_factor: usize, y_factor: usize) -> Self {
+        let mut pixels = Vec::with_capacity(self.pixels.len() * x_factor * y_factor);
+        for row in self.pixels.chunks(self.width.max(1)) {
+            let scaled_row = row
+                .iter()
+                .flat_map(|&pixel| std::iter::repeat_n(pixel, x_factor))
+                .collect::<Vec<_>>();
+            for _ in 0..y_factor {
+                pixels.extend_from_slice(&scaled_row);
+            }
+        }
+
+        Self {
+            width: self.width * x_factor,
+            height: self.height * y_factor,
+            pixels,
+        }
+    }
+}
+
 pub fn decode_zpl_graphic(
     // compression: CompressionType,
     compression_method: CompressionMethod,
@@ -35,22 +85,6 @@ pub fn decode_zpl_graphic(
     })
 }
 
-pub fn decode_ascii_hex(s: &str) -> Result<Vec<u8>, String> {
-    let mut out = Vec::new();
-    let cleaned = s.trim().replace(char::is_whitespace, "");
-
-    if cleaned.len() % 2 != 0 {
-        return Err("invalid ASCII hex length".into());
-    }
-
-    for chunk in cleaned.as_bytes().chunks(2) {
-        let hex = std::str::from_utf8(chunk).unwrap();
-        let byte = u8::from_str_radix(hex, 16).map_err(|_| format!("invalid hex byte: {}", hex))?;
-        out.push(byte);
-    }
-    Ok(out)
-}
-
 fn decode_base64(s: &str) -> Result<Vec<u8>, String> {
     let cleaned = s.trim().replace(char::is_whitespace, "");
     general_purpose::STANDARD
@@ -76,7 +110,7 @@ fn expand_monochrome_bitmap(
     height: usize,
     bytes_per_row: usize,
 ) -> Result<Vec<u8>, String> {
-    let expected = (bytes_per_row * height) as usize;
+    let expected = bytes_per_row * height;
     if packed.len() < expected {
         return Err(format!(
             "bitmap too small: expected {} bytes, got {}",
@@ -85,17 +119,17 @@ fn expand_monochrome_bitmap(
         ));
     }
 
-    let mut pixels = Vec::with_capacity((width * height) as usize);
+    let mut pixels = Vec::with_capacity(width * height);
 
     for row in 0..height {
-        let row_start = (row * bytes_per_row) as usize;
+        let row_start = row * bytes_per_row;
 
-        for byte in &packed[row_start..row_start + bytes_per_row as usize] {
+        for byte in &packed[row_start..row_start + bytes_per_row] {
             for bit in 0..8 {
                 let shift = 7 - bit;
                 let value = (byte >> shift) & 1;
 
-                if pixels.len() < (width * height) as usize {
+                if pixels.len() < (width * height) {
                     pixels.push(value); // push 0 or 1
                 }
             }
@@ -103,3 +137,31 @@ fn expand_monochrome_bitmap(
     }
     Ok(pixels)
 }
+
+#[cfg(test)]
+mod tests {
+    use zpl_parser::Orientation;
+
+    use crate::DecodedBitmap;
+
+    #[test]
+    fn should_rotate_bitmap() {
+        // 1 0 0
+        // 1 1 0
+        let bitmap = DecodedBitmap {
+            width: 3,
+            height: 2,
+            pixels: vec![1, 0, 0, 1, 1, 0],
+        };
+
+        let rotated = bitmap.rotate(Orientation::Rotate);
+        assert_eq!((rotated.width, rotated.height), (2, 3));
+        assert_eq!(rotated.pixels, vec![1, 1, 1, 0, 0, 0]);
+
+        let rotated = bitmap.rotate(Orientation::Invert);
+        assert_eq!(rotated.pixels, vec![0, 1, 1, 0, 0, 1]);
+
+        let rotated = bitmap.rotate(Orientation::BackRotate);
+        assert_eq!(rotated.pixels, vec![0, 0, 0, 1, 1, 1]);
+    }
+}
@@ -0,0 +1,317 @@
+use std::{collections::HashMap, fmt};
+
+use zpl_parser::ZplFormatCommand;
+
+/// In-memory storage for formats downloaded with ^DF and recalled with ^XF,
+/// like the memory of a printer.
+#[derive(Debug, Clone, Default)]
+pub struct FormatStore {
+    formats: HashMap<String, Vec<ZplFormatCommand>>,
+}
+
+/// ^XF recalled a format that was never stored with ^DF
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownFormat {
+    pub name: String,
+}
+
+impl fmt::Display for UnknownFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown format: {}", self.name)
+    }
+}
+
+impl std::error::Error for UnknownFormat {}
+
+/// `LABEL` and `R:LABEL.ZPL` name the same format
+fn normalize_name(name: &str) -> String {
+    let name = name.trim().to_uppercase();
+    let name = match name.contains(':') {
+        true => name,
+        false => format!("R:{name}"),
+    };
+    match name.contains('.') {
+        true => name,
+        false => format!("{name}.ZPL"),
+    }
+}
+
+/// Replace the ^FN placeholders of `template` with `fields`.
+///
+/// A value replaces the default ^FD of its field, or is added at the end of
+/// the field if there is none. Fields without a value keep their default.
+/// The `commands` of a field, like ^FH, take the place of its ^FN.
+fn merge_fields(
+    template: &[ZplFormatCommand],
+    fields: &HashMap<u16, String>,
+    commands: &HashMap<u16, Vec<ZplFormatCommand>>,
+) -> Vec<ZplFormatCommand> {
+    let mut merged = Vec::with_capacity(template.len());
+    let mut value = None;
+    // position of the default ^FD of the current field in `merged`
+    let mut default = None;
+
+    for command in template {
+        match (command, value) {
+            (ZplFormatCommand::FieldNumber(number), _) => {
+                merged.extend(commands.get(number).into_iter().flatten().cloned());
+                value = fields.get(number);
+                // the default may come before the ^FN
+                if let (Some(idx), Some(data)) = (default, value) {
+                    merged[idx] = ZplFormatCommand::FieldData(data.clone());
+                    value = None;
+                }
+            }
+            (ZplFormatCommand::FieldData(_), Some(data)) => {
+                merged.push(ZplFormatCommand::FieldData(data.clone()));
+                value = None;
+            }
+            (ZplFormatCommand::FieldData(_), None) => {
+                default = Some(merged.len());
+                merged.push(command.clone());
+            }
+            (ZplFormatCommand::FieldSeparator, data) => {
+                if let Some(data) = data {
+                    merged.push(ZplFormatCommand::FieldData(data.clone()));
+                }
+                merged.push(ZplFormatCommand::FieldSeparator);
+                value = None;
+                default = None;
+            }
+            _ => merged.push(command.clone()),
+        }
+    }
+
+    merged
+}
+
+impl FormatStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, name: &str, commands: Vec<ZplFormatCommand>) {
+        self.formats.insert(normalize_name(name), commands);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&[ZplFormatCommand]> {
+        self.formats.get(&normalize_name(name)).map(Vec::as_slice)
+    }
+
+    /// Merge field values into the stored format `name`, ready to be interpreted.
+    pub fn merge(
+        &self,
+        name: &str,
+        fields: &HashMap<u16, String>,
+    ) -> Option<Vec<ZplFormatCommand>> {
+        self.get(name)
+            .map(|template| merge_fields(template, fields, &HashMap::new()))
+    }
+
+    /// Handle ^DF and ^XF of a single label.
+    ///
+    /// A label with ^DF is stored and `None` returned, the printer does not
+    /// print it. A label with ^XF is merged with the stored format, its
+    /// ^FN fields provide the values. Other labels are returned unchanged.
+    /// Fails if the recalled format was never stored.
+    pub fn resolve(
+        &mut self,
+        commands: &[ZplFormatCommand],
+    ) -> Result<Option<Vec<ZplFormatCommand>>, UnknownFormat> {
+        let download = commands
+            .iter()
+            .enumerate()
+            .find_map(|(idx, command)| match command {
+                ZplFormatCommand::StoreFormat { name } => Some((idx, name)),
+                _ => None,
+            });
+        if let Some((idx, name)) = download {
+            let template = match commands.get(idx + 1) {
+                Some(ZplFormatCommand::FieldSeparator) => &commands[idx + 2..],
+                _ => &commands[idx + 1..],
+            };
+            self.insert(name, template.to_vec());
+            return Ok(None);
+        }
+
+        let Some((idx, name)) =
+            commands
+                .iter()
+                .enumerate()
+                .find_map(|(idx, command)| match command {
+                    ZplFormatCommand::RecallFormat { name } => Some((idx, name)),
+                    _ => None,
+                })
+        else {
+            return Ok(Some(commands.to_vec()));
+        };
+        let template = self.get(name).ok_or_else(|| UnknownFormat {
+            name: name.to_string(),
+        })?;
+
+        // split the ^FN fields from the commands printed in addition to the format
+        let mut fields = HashMap::new();
+        let mut field_commands: HashMap<_, Vec<_>> = HashMap::new();
+        let mut before = Vec::new();
+        let mut after = Vec::new();
+        let mut field = None;
+        let mut skip_separator = false;
+        for (pos, command) in commands.iter().enumerate() {
+            match (command, field) {
+                (ZplFormatCommand::RecallFormat { .. }, _) => skip_separator = true,
+                (ZplFormatCommand::FieldNumber(number), _) => field = Some(*number),
+                (ZplFormatCommand::FieldData(data), Some(number)) => {
+                    fields.insert(number, data.clone());
+                }
+                (ZplFormatCommand::FieldSeparator, _) if field.is_some() || skip_separator => {
+                    field = None;
+                    skip_separator = false;
+                }
+                (_, Some(number)) => field_commands
+                    .entry(number)
+                    .or_default()
+                    .push(command.clone()),
+                _ => match pos < idx {
+                    true => before.push(command.clone()),
+                    false => after.push(command.clone()),
+                },
+            }
+        }
+
+        before.extend(merge_fields(template, &fields, &field_commands));
+        before.extend(after);
+        Ok(Some(before))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use zpl_parser::{ZplFormatCommand, parse_zpl_labels};
+
+    use crate::format_store::{FormatStore, UnknownFormat};
+
+    const TEMPLATE: &str = "^XA^DFR:ADDRESS.ZPL^FS\
+        ^FO10,10^A0N,30,30^FN1^FS\
+        ^FO10,50^FN2^FDDefault^FS\
+        ^XZ";
+
+    #[test]
+    fn should_store_downloaded_format() {
+        let mut store = FormatStore::new();
+        let labels = parse_zpl_labels(TEMPLATE).unwrap();
+        assert_eq!(store.resolve(&labels[0]), Ok(None));
+
+        // device and extension are optional
+        let template = store.get("address").unwrap();
+        assert_eq!(template.len(), 8);
+        assert_eq!(
+            template[0],
+            ZplFormatCommand::FieldOrigin {
+                x: 10,
+                y: 10,
+                justification: Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn should_merge_recalled_format() {
+        let input = format!("{TEMPLATE}^XA^XFADDRESS^FS^FN1^FDName^FS^PW400^XZ");
+        let mut store = FormatStore::new();
+        let labels: Vec<_> = parse_zpl_labels(&input)
+            .unwrap()
+            .iter()
+            .filter_map(|label| store.resolve(label).unwrap())
+            .collect();
+
+        let expected =
+            parse_zpl_labels("^XA^FO10,10^A0N,30,30^FDName^FS^FO10,50^FDDefault^FS^PW400^XZ")
+                .unwrap();
+        assert_eq!(labels, expected);
+    }
+
+    #[test]
+    fn should_keep_field_commands() {
+        let input = format!("{TEMPLATE}^XA^XFADDRESS^FS^FN1^FH_^FDName_41^FS^XZ");
+        let mut store = FormatStore::new();
+        let labels: Vec<_> = parse_zpl_labels(&input)
+            .unwrap()
+            .iter()
+            .filter_map(|label| store.resolve(label).unwrap())
+            .collect();
+
+        let expected =
+            parse_zpl_labels("^XA^FO10,10^A0N,30,30^FH_^FDName_41^FS^FO10,50^FDDefault^FS^XZ")
+                .unwrap();
+        assert_eq!(labels, expected);
+    }
+
+    #[test]
+    fn should_replace_default_before_field_number() {
+        // ^FD data runs up to ^FS, so only a built format has this order
+        let mut store = FormatStore::new();
+        store.insert(
+            "NAME",
+            vec![
+                ZplFormatCommand::FieldOrigin {
+                    x: 10,
+                    y: 10,
+                    justification: Default::default(),
+                },
+                ZplFormatCommand::FieldData("Default".to_string()),
+                ZplFormatCommand::FieldNumber(1),
+                ZplFormatCommand::FieldSeparator,
+            ],
+        );
+
+        let fields = HashMap::from([(1, "Name".to_string())]);
+        let merged = store.merge("NAME", &fields).unwrap();
+        let expected = parse_zpl_labels("^XA^FO10,10^FDName^FS^XZ").unwrap();
+        assert_eq!(merged, expected[0]);
+    }
+
+    #[test]
+    fn should_merge_barcode_fields() {
+        let input = "^XA^DFR:CODE.ZPL^FS^FO10,10^BCN,50^FN1^FS^XZ\
+            ^XA^XFR:CODE.ZPL^FS^FN1^FDABC^FS^XZ";
+        let mut store = FormatStore::new();
+        let labels: Vec<_> = parse_zpl_labels(input)
+            .unwrap()
+            .iter()
+            .filter_map(|label| store.resolve(label).unwrap())
+            .collect();
+
+        let expected = parse_zpl_labels("^XA^FO10,10^BCN,50^FDABC^FS^XZ").unwrap();
+        assert_eq!(labels, expected);
+    }
+
+    #[test]
+    fn should_reject_unknown_format() {
+        let mut store = FormatStore::new();
+        let label = &parse_zpl_labels("^XA^XFMISSING^FS^FN1^FDName^FS^XZ").unwrap()[0];
+        assert_eq!(
+            store.resolve(label),
+            Err(UnknownFormat {
+                name: "MISSING".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn should_merge_field_values() {
+        let mut store = FormatStore::new();
+        store
+            .resolve(&parse_zpl_labels(TEMPLATE).unwrap()[0])
+            .unwrap();
+
+        let fields = HashMap::from([(1, "Name".to_string()), (2, "Street".to_string())]);
+        let merged = store.merge("R:ADDRESS.ZPL", &fields).unwrap();
+        let expected =
+            parse_zpl_labels("^XA^FO10,10^A0N,30,30^FDName^FS^FO10,50^FDStreet^FS^XZ").unwrap();
+        assert_eq!(merged, expected[0]);
+
+        assert_eq!(store.merge("OTHER", &fields), None);
+    }
+}
@@ -1,19 +1,24 @@
 mod barcode;
 mod datetime;
 mod decode_image;
+mod format_store;
 
 use std::cmp;
 
 use zpl_parser::{
-    BarcodeType, ClockFormat, Color, Justification, TextBlockJustification, ZplFormatCommand,
+    BarcodeType, Color, DiagonalOrientation, Justification, Orientation, TextBlockJustification,
+    ZplFormatCommand,
 };
 
-pub use crate::decode_image::DecodedBitmap;
 use crate::{
     barcode::{BarcodeContent, barcode_from_content},
     datetime::format_timestamp,
     decode_image::decode_zpl_graphic,
 };
+pub use crate::{
+    decode_image::DecodedBitmap,
+    format_store::{FormatStore, UnknownFormat},
+};
 
 #[derive(Default)]
 enum Origin {
@@ -34,6 +39,9 @@ pub enum ZplElement {
         justification: Justification,
         inverted: bool,
         field_block: Option<FieldBlock>,
+        orientation: Orientation,
+        /// ^FT, rotated text is anchored at its baseline instead of its top left corner
+        typeset: bool,
     },
     Rectangle {
         x: usize,
@@ -45,6 +53,33 @@ pub enum ZplElement {
         rounding: u8,
         inverted: bool,
     },
+    Circle {
+        x: usize,
+        y: usize,
+        diameter: usize,
+        thickness: usize,
+        color: Color,
+        inverted: bool,
+    },
+    Ellipse {
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        thickness: usize,
+        color: Color,
+        inverted: bool,
+    },
+    DiagonalLine {
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        thickness: usize,
+        color: Color,
+        orientation: DiagonalOrientation,
+        inverted: bool,
+    },
     Image {
         x: usize,
         y: usize,
@@ -60,6 +95,12 @@ pub enum ZplElement {
 impl ZplElement {
     fn max_width(&self) -> usize {
         match self {
+            ZplElement::Text {
+                x,
+                font_height,
+                orientation: Orientation::Rotate | Orientation::BackRotate,
+                ..
+            } => x + *font_height as usize,
             ZplElement::Text {
                 x,
                 font_width,
@@ -67,7 +108,10 @@ impl ZplElement {
                 ..
             // } => x + (content.chars().count() as f32 * font_width) as usize,
             } => x + (content.chars().count() as f32 * font_width / 1.5) as usize,
-            ZplElement::Rectangle { x, width, .. } => x + width,
+            ZplElement::Rectangle { x, width, .. }
+            | ZplElement::Ellipse { x, width, .. }
+            | ZplElement::DiagonalLine { x, width, .. } => x + width,
+            ZplElement::Circle { x, diameter, .. } => x + diameter,
             ZplElement::Image { x, bmp, .. } => x + bmp.width,
             ZplElement::Barcode { x, content, .. } => x + content.bitmap.width,
         }
@@ -77,11 +121,16 @@ impl ZplElement {
         match self {
             ZplElement::Text {
                 y,
-                font_height,
+                font_width,
                 content,
+                orientation: Orientation::Rotate | Orientation::BackRotate,
                 ..
-            } => y + *font_height as usize,
-            ZplElement::Rectangle { y, height, .. } => y + height,
+            } => y + (content.chars().count() as f32 * font_width / 1.5) as usize,
+            ZplElement::Text { y, font_height, .. } => y + *font_height as usize,
+            ZplElement::Rectangle { y, height, .. }
+            | ZplElement::Ellipse { y, height, .. }
+            | ZplElement::DiagonalLine { y, height, .. } => y + height,
+            ZplElement::Circle { y, diameter, .. } => y + diameter,
             ZplElement::Image { y, bmp, .. } => y + bmp.height,
             ZplElement::Barcode { y, content, .. } => y + content.bitmap.height,
         }
@@ -127,7 +176,52 @@ pub struct SetRealTimeClock {
     hour: Option<u8>,
     minute: Option<u8>,
     second: Option<u8>,
-    format: ClockFormat,
+}
+
+/// ^LH, offset of all field origins
+#[derive(Default, Clone, Copy)]
+struct LabelHome {
+    x: usize,
+    y: usize,
+}
+
+/// ^LS and ^LT, shift of the whole label content
+#[derive(Default, Clone, Copy)]
+struct LabelOffset {
+    shift: isize,
+    top: isize,
+}
+
+/// ^PO and ^LR, applied to the whole label when printing
+#[derive(Default, Clone, Copy)]
+struct PrintMode {
+    inverted: bool,
+    reverse: bool,
+}
+
+/// Dots per millimeter of the printer, halved by ^JMB
+#[derive(Clone, Copy)]
+struct Resolution {
+    dots_per_mm: u8,
+    half: bool,
+}
+
+impl Resolution {
+    fn dots_per_mm(self) -> f32 {
+        match self.half {
+            true => self.dots_per_mm as f32 / 2.,
+            false => self.dots_per_mm as f32,
+        }
+    }
+}
+
+impl Default for Resolution {
+    fn default() -> Self {
+        Self {
+            dots_per_mm: DEFAULT_DOTS_PER_MM,
+            half: false,
+        }
+    }
 }
 
 #[derive(Default)]
@@ -147,11 +241,21 @@ struct InterpreterState {
     fieldblock_state: Option<FieldBlock>,
     current_justification: Justification,
     inverted: bool,
+    /// ^A orientation of the current field
+    orientation: Option<Orientation>,
+    /// ^FW default orientation
+    field_orientation: Orientation,
+    /// ^FW default justification
+    field_justification: Justification,
     barcode_type: Option<BarcodeType>,
     barcode_config: Option<BarcodeConfig>,
     escape_chars: Vec<char>,
     real_time_clock_setup: SetRealTimeClock,
+    label_home: LabelHome,
+    label_offset: LabelOffset,
+    print_mode: PrintMode,
     label_size: LabelSize,
+    resolution: Resolution,
 }
 
 impl InterpreterState {
@@ -173,47 +277,97 @@ impl InterpreterState {
 pub struct ZplLabel {
     pub width: usize,
     pub height: usize,
+    /// offset of all elements in dots, content outside the label is clipped
+    pub offset_x: isize,
+    pub offset_y: isize,
+    /// ^POI, the label is rotated by 180°
+    pub inverted: bool,
+    /// ^LRY, all fields are reverse printed
+    pub reverse: bool,
     pub elements: Vec<ZplElement>,
+    /// fields left out of the label, like barcodes that could not be generated
+    pub warnings: Vec<String>,
 }
 
+/// Dots per millimeter of a 203 dpi printer
+pub const DEFAULT_DOTS_PER_MM: u8 = 8;
+
 pub fn interpret(cmds: &[ZplFormatCommand]) -> ZplLabel {
-    let mut state = InterpreterState::default();
+    interpret_with_resolution(cmds, DEFAULT_DOTS_PER_MM)
+}
+
+/// Like [`interpret`], for a printer with `dots_per_mm` dots per millimeter
+/// (8 for 203 dpi, 12 for 300 dpi, 24 for 600 dpi). Symbols with a fixed
+/// physical size like MaxiCode are scaled to it.
+pub fn interpret_with_resolution(cmds: &[ZplFormatCommand], dots_per_mm: u8) -> ZplLabel {
+    let mut state = InterpreterState {
+        resolution: Resolution {
+            dots_per_mm,
+            half: false,
+        },
+        ..Default::default()
+    };
     let mut elements = Vec::new();
+    let mut warnings = Vec::new();
 
     for cmd in cmds {
         match cmd {
+            ZplFormatCommand::FieldOrientation {
+                orientation,
+                justification,
+            } => {
+                state.field_orientation = *orientation;
+                state.field_justification = *justification;
+            }
             ZplFormatCommand::FieldOrigin {
                 x,
                 y,
                 justification,
             } => {
-                state.current_x = *x;
-                state.current_y = *y;
-                state.current_justification = *justification;
+                state.current_x = x + state.label_home.x;
+                state.current_y = y + state.label_home.y;
+                state.current_justification = justification.unwrap_or(state.field_justification);
             }
             ZplFormatCommand::FieldTypeset {
                 x,
                 y,
                 justification,
             } => {
-                state.current_x = *x;
-                state.current_y = *y;
+                state.current_x = x + state.label_home.x;
+                state.current_y = y + state.label_home.y;
                 state.current_origin = Origin::Bottom;
-                state.current_justification = *justification;
+                state.current_justification = justification.unwrap_or(state.field_justification);
             }
             ZplFormatCommand::FieldData(text) => {
                 let mut content = text.clone();
-                let elem = if let Some(barcode_type) = state.barcode_type
-                    && let Ok(mut barcode_content) =
-                        barcode_from_content(state.barcode_config.as_ref(), barcode_type, &content)
-                {
-                    barcode_content.set_text_x(state.current_x());
+                let elem = if let Some(barcode_type) = state.barcode_type {
+                    let mut barcode_content = match barcode_from_content(
+                        state.barcode_config.as_ref(),
+                        barcode_type,
+                        &content,
+                        state.resolution.dots_per_mm(),
+                    ) {
+                        Ok(barcode_content) => barcode_content,
+                        // a barcode is never printed as plain text
+                        Err(err) => {
+                            warnings.push(format!("{barcode_type} left out: {err}"));
+                            continue;
+                        }
+                    };
                     let element_height = barcode_content.bitmap.height;
-                    barcode_content.set_text_y(state.current_y(element_height));
+                    let (x, y) = (state.current_x(), state.current_y(element_height));
+                    barcode_content.set_text_x(x);
+                    barcode_content.set_text_y(y);
+
+                    // a line left of or above the label moves the whole
+                    // barcode in, so its background never covers the bars
+                    let (shift_x, shift_y) = barcode_content.text_overhang();
+                    barcode_content.set_text_x(shift_x);
+                    barcode_content.set_text_y(shift_y);
 
                     ZplElement::Barcode {
-                        x: state.current_x() as usize,
-                        y: state.current_y(element_height) as usize,
+                        x: x + shift_x,
+                        y: y + shift_y,
                         content: barcode_content,
                     }
                 } else {
@@ -233,6 +387,8 @@ pub fn interpret(cmds: &[ZplFormatCommand]) -> ZplLabel {
                         justification: state.current_justification,
                         inverted: state.inverted,
                         field_block: state.fieldblock_state.clone(),
+                        orientation: state.orientation.unwrap_or(state.field_orientation),
+                        typeset: matches!(state.current_origin, Origin::Bottom),
                     }
                 };
                 state.label_size.current_height =
@@ -243,13 +399,26 @@ pub fn interpret(cmds: &[ZplFormatCommand]) -> ZplLabel {
             }
             ZplFormatCommand::LabelLength(h) => state.label_size.total_height = Some(*h),
             ZplFormatCommand::PrintWidth(w) => state.label_size.total_width = Some(*w),
-            ZplFormatCommand::LabelShift(_) => {}
+            ZplFormatCommand::LabelShift(shift) => state.label_offset.shift = *shift,
+            ZplFormatCommand::LabelTop(top) => state.label_offset.top = *top,
+            ZplFormatCommand::PrintOrientation { inverted } => {
+                state.print_mode.inverted = *inverted
+            }
+            ZplFormatCommand::LabelReverse(reverse) => state.print_mode.reverse = *reverse,
+            ZplFormatCommand::HalfResolution(half) => state.resolution.half = *half,
+            ZplFormatCommand::LabelHome { x, y } => {
+                state.label_home = LabelHome {
+                    x: x.unwrap_or(state.label_home.x),
+                    y: y.unwrap_or(state.label_home.y),
+                };
+            }
             ZplFormatCommand::Font {
                 name,
                 orientation,
                 height,
                 width,
             } => {
+                state.orientation = *orientation;
                 state.font.current_font_name = *name;
                 state.font.current_font_height = *height as f32;
                 state.font.current_font_width = *width as f32;
@@ -264,27 +433,25 @@ pub fn interpret(cmds: &[ZplFormatCommand]) -> ZplLabel {
                 state.font.current_font_width = *width as f32;
             }
             ZplFormatCommand::GraphicField {
-                compression_type,
-                data_bytes,
+                compression_type: _,
+                data_bytes: _,
                 total_bytes,
                 row_bytes,
                 data,
             } => {
                 let width = row_bytes * 8;
                 let height = total_bytes / row_bytes;
-                let bmp = match decode_zpl_graphic(
+                let bmp = decode_zpl_graphic(
                     data.compression_method,
                     &data.data,
                     width,
                     height,
                     *row_bytes,
-                ) {
-                    Ok(bmp) => bmp,
-                    _ => DecodedBitmap::default(),
-                };
+                )
+                .unwrap_or_default();
                 let elem = ZplElement::Image {
-                    x: state.current_x() as usize,
-                    y: state.current_y(height) as usize,
+                    x: state.current_x(),
+                    y: state.current_y(height),
                     bmp,
                 };
                 state.label_size.current_height =
@@ -316,6 +483,69 @@ pub fn interpret(cmds: &[ZplFormatCommand]) -> ZplLabel {
                     cmp::max(state.label_size.current_width, elem.max_width());
                 elements.push(elem);
             }
+            ZplFormatCommand::GraphicalCircle {
+                diameter,
+                thickness,
+                color,
+            } => {
+                let elem = ZplElement::Circle {
+                    x: state.current_x(),
+                    y: state.current_y(*diameter),
+                    diameter: *diameter,
+                    thickness: *thickness,
+                    color: *color,
+                    inverted: state.inverted,
+                };
+                state.label_size.current_height =
+                    cmp::max(state.label_size.current_height, elem.max_height());
+                state.label_size.current_width =
+                    cmp::max(state.label_size.current_width, elem.max_width());
+                elements.push(elem);
+            }
+            ZplFormatCommand::GraphicalEllipse {
+                width,
+                height,
+                thickness,
+                color,
+            } => {
+                let elem = ZplElement::Ellipse {
+                    x: state.current_x(),
+                    y: state.current_y(*height),
+                    width: *width,
+                    height: *height,
+                    thickness: *thickness,
+                    color: *color,
+                    inverted: state.inverted,
+                };
+                state.label_size.current_height =
+                    cmp::max(state.label_size.current_height, elem.max_height());
+                state.label_size.current_width =
+                    cmp::max(state.label_size.current_width, elem.max_width());
+                elements.push(elem);
+            }
+            ZplFormatCommand::GraphicalDiagonalLine {
+                width,
+                height,
+                thickness,
+                color,
+                orientation,
+            } => {
+                let elem = ZplElement::DiagonalLine {
+                    x: state.current_x(),
+                    y: state.current_y(*height),
+                    width: *width,
+                    height: *height,
+                    thickness: *thickness,
+                    color: *color,
+                    orientation: *orientation,
+                    inverted: state.inverted,
+                };
+                state.label_size.current_height =
+                    cmp::max(state.label_size.current_height, elem.max_height());
+                state.label_size.current_width =
+                    cmp::max(state.label_size.current_width, elem.max_width());
+                elements.push(elem);
+            }
             ZplFormatCommand::Inverted => state.inverted = true,
             ZplFormatCommand::BarcodeConfig {
                 width,
@@ -329,8 +559,8 @@ pub fn interpret(cmds: &[ZplFormatCommand]) -> ZplLabel {
                 })
             }
             ZplFormatCommand::Barcode(barcode_type) => state.barcode_type = Some(*barcode_type),
-            ZplFormatCommand::FieldHexIndicator { char } => {}
-            ZplFormatCommand::CharacterSet { num, mapping } => {}
+            ZplFormatCommand::FieldHexIndicator { .. } => {}
+            ZplFormatCommand::CharacterSet { .. } => {}
             ZplFormatCommand::FieldBlock {
                 width,
                 lines,
@@ -346,7 +576,14 @@ pub fn interpret(cmds: &[ZplFormatCommand]) -> ZplLabel {
                     hanging_indent: *hanging_indent,
                 })
             }
-            ZplFormatCommand::RealTimeClockMode { mode, language } => {}
+            ZplFormatCommand::RealTimeClockMode { .. } => {}
+            // resolved by the `FormatStore` before interpreting
+            ZplFormatCommand::StoreFormat { .. }
+            | ZplFormatCommand::RecallFormat { .. }
+            | ZplFormatCommand::FieldNumber(_) => {}
+            // only affects parsing
+            ZplFormatCommand::ChangePrefix { .. } => {}
+            ZplFormatCommand::Unknown { .. } => {}
             ZplFormatCommand::RealTimeClockEscapeChar {
                 first,
                 second,
@@ -367,7 +604,7 @@ pub fn interpret(cmds: &[ZplFormatCommand]) -> ZplLabel {
                 hour,
                 minute,
                 second,
-                format,
+                ..
             } => {
                 state.real_time_clock_setup = SetRealTimeClock {
                     month: *month,
@@ -376,7 +613,6 @@ pub fn interpret(cmds: &[ZplFormatCommand]) -> ZplLabel {
                     hour: *hour,
                     minute: *minute,
                     second: *second,
-                    format: *format,
                 }
             }
             ZplFormatCommand::FieldSeparator => {
@@ -387,7 +623,13 @@ pub fn interpret(cmds: &[ZplFormatCommand]) -> ZplLabel {
                         current_font_width: state.font.current_font_width,
                         current_font_name: state.font.current_font_name,
                     },
+                    field_orientation: state.field_orientation,
+                    field_justification: state.field_justification,
+                    label_home: state.label_home,
+                    label_offset: state.label_offset,
+                    print_mode: state.print_mode,
                     label_size: state.label_size,
+                    resolution: state.resolution,
                     ..Default::default()
                 }
             }
@@ -404,16 +646,251 @@ pub fn interpret(cmds: &[ZplFormatCommand]) -> ZplLabel {
         .total_height
         .unwrap_or(state.label_size.current_height);
 
+    // a positive ^LS moves the content to the left, a positive ^LT down
     ZplLabel {
         width,
         height,
+        offset_x: -state.label_offset.shift,
+        offset_y: state.label_offset.top,
+        inverted: state.print_mode.inverted,
+        reverse: state.print_mode.reverse,
         elements,
+        warnings,
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use zpl_parser::{Justification, Orientation, parse_zpl};
+
+    use crate::{ZplElement, interpret, interpret_with_resolution};
+
+    fn positions(input: &str) -> Vec<(usize, usize)> {
+        let commands = parse_zpl(input).unwrap();
+        interpret(&commands)
+            .elements
+            .iter()
+            .map(|elem| match elem {
+                ZplElement::Text { x, y, .. }
+                | ZplElement::Rectangle { x, y, .. }
+                | ZplElement::Circle { x, y, .. }
+                | ZplElement::Ellipse { x, y, .. }
+                | ZplElement::DiagonalLine { x, y, .. }
+                | ZplElement::Image { x, y, .. }
+                | ZplElement::Barcode { x, y, .. } => (*x, *y),
+            })
+            .collect()
+    }
 
     #[test]
     fn interpreter_test() {}
+
+    #[test]
+    fn label_home_offsets_text() {
+        let input = "^XA^LH30,40^FO10,20^A0N,20,20^FDText^FS\
+            ^FT10,100^FDText^FS^XZ";
+        assert_eq!(positions(input), vec![(40, 60), (40, 120)]);
+    }
+
+    #[test]
+    fn label_home_keeps_omitted_parameters() {
+        let input = "^XA^LH30,40^LH,50^FO10,20^GB10,10,1^FS\
+            ^LH60^FO10,20^GB10,10,1^FS^XZ";
+        assert_eq!(positions(input), vec![(40, 70), (70, 70)]);
+    }
+
+    #[test]
+    fn label_home_offsets_boxes() {
+        let input = "^XA^FO10,20^GB100,50,2^FS^LH30,40^FO10,20^GB100,50,2^FS\
+            ^FT10,100^GB100,50,2^FS^XZ";
+        assert_eq!(positions(input), vec![(10, 20), (40, 60), (40, 90)]);
+    }
+
+    #[test]
+    fn label_home_offsets_images() {
+        let data = "eJytk7ENg0AMRQ8BAinFNenZBMpshdkgK1CnyAp4g2SEG4ESIYLjozr7LkqKmO7JenxsUxhVhWk1sthrVOE+fO+yGLtcwtWbOgT14TqHqDxcwmZH68BAiBr0uShMZhd2lSS6ZnbZXaCVczWbSEUVttMSohNdEeAZoowe2NEovocIQbyQ/YREN1GT76KXeIhduhxECH9DKdce51KL7LwLBQLvcuobHcAsJ3HBthPzynlefSWnuvHsc5HCrryhTG0ovUe97eRNRJfz4b5UJW8VNPrv3f/yp6VccVdm7jqXGd7xtuh/:E957";
+        let input = format!("^XA^LH30,40^FO10,20^GFA,309,988,19,:Z64:{data}^FS^XZ");
+        assert_eq!(positions(&input), vec![(40, 60)]);
+    }
+
+    #[test]
+    fn label_home_offsets_barcodes() {
+        let input = "^XA^LH30,40^BY2^FO10,20^BCN,50,N^FD12345678^FS\
+            ^FT10,200^BEN,50,N^FD12345678^FS^XZ";
+        let commands = parse_zpl(input).unwrap();
+        let label = interpret(&commands);
+        let ZplElement::Barcode { content, .. } = &label.elements[1] else {
+            panic!("expected barcode");
+        };
+        let height = content.bitmap.height;
+        assert_eq!(positions(input), vec![(40, 60), (40, 240 - height)]);
+    }
+
+    #[test]
+    fn label_shift_and_top_offset_label() {
+        let input = "^XA^LS-20^FO10,20^GB10,10,10^FS^LT-5^XZ";
+        let label = interpret(&parse_zpl(input).unwrap());
+        assert_eq!((label.offset_x, label.offset_y), (20, -5));
+        // element positions stay as written, the offset applies to all of them
+        assert_eq!(positions(input), vec![(10, 20)]);
+    }
+
+    #[test]
+    fn field_justification_defaults_to_field_orientation() {
+        let input = "^XA^FWN,1^FO10,10^FDDefault^FS^FO10,10,0^FDExplicit^FS\
+            ^FT10,50^FDTypeset^FS^XZ";
+        let label = interpret(&parse_zpl(input).unwrap());
+        let justifications: Vec<_> = label
+            .elements
+            .iter()
+            .map(|elem| match elem {
+                ZplElement::Text { justification, .. } => *justification,
+                _ => panic!("expected text"),
+            })
+            .collect();
+        assert_eq!(
+            justifications,
+            vec![
+                Justification::Right,
+                Justification::Left,
+                Justification::Right
+            ]
+        );
+    }
+
+    #[test]
+    fn text_orientation_defaults_to_field_orientation() {
+        let input = "^XA^FWR^FO10,10^A0,20,20^FDDefault^FS\
+            ^FO10,10^A0B,20,20^FDExplicit^FS^FO10,10^FDNo font^FS^XZ";
+        let label = interpret(&parse_zpl(input).unwrap());
+        let orientations: Vec<_> = label
+            .elements
+            .iter()
+            .map(|elem| match elem {
+                ZplElement::Text { orientation, .. } => *orientation,
+                _ => panic!("expected text"),
+            })
+            .collect();
+        assert_eq!(
+            orientations,
+            vec![
+                Orientation::Rotate,
+                Orientation::BackRotate,
+                Orientation::Rotate
+            ]
+        );
+    }
+
+    #[test]
+    fn rotated_barcode_covers_rotated_field() {
+        let input = "^XA^BY2^FO10,10^BCN,80,Y,N,N^FD1234^FS\
+            ^FO10,10^BCR,80,Y,N,N^FD1234^FS^XZ";
+        let label = interpret(&parse_zpl(input).unwrap());
+        let [
+            ZplElement::Barcode {
+                content: normal, ..
+            },
+            ZplElement::Barcode {
+                content: rotated, ..
+            },
+        ] = label.elements.as_slice()
+        else {
+            panic!("expected barcodes");
+        };
+
+        // the field is extended by the human readable line left of the bars
+        assert_eq!(rotated.bitmap.height, normal.bitmap.width);
+        assert!(rotated.bitmap.width > normal.bitmap.height);
+        let band = rotated.bitmap.width - normal.bitmap.height;
+        let text = &rotated.text_elements()[0];
+        assert_eq!(text.orientation, Orientation::Rotate);
+        assert!(text.text_x > 10 && text.text_x <= 10 + band as isize);
+        assert_eq!(label.height, 10 + normal.bitmap.width);
+    }
+
+    #[test]
+    fn barcodes_that_cannot_be_generated_are_left_out() {
+        // an empty code is not printed as text either
+        let input = "^XA^FO10,10^BCN,0,N^FD123^FS^FO10,50^A0N,20,20^FDtext^FS^XZ";
+        let label = interpret(&parse_zpl(input).unwrap());
+        assert_eq!(label.elements.len(), 1);
+        assert!(matches!(label.elements[0], ZplElement::Text { .. }));
+        assert_eq!(label.warnings.len(), 1);
+        assert!(label.warnings[0].starts_with("^BC"), "{:?}", label.warnings);
+    }
+
+    #[test]
+    fn micro_pdf417_is_left_out() {
+        let label = interpret(&parse_zpl("^XA^FO10,10^BFN,8,12^FDMICRO^FS^XZ").unwrap());
+        assert!(label.elements.is_empty());
+        assert_eq!(
+            label.warnings,
+            ["^BFN,8,12 left out: MicroPDF417 is not supported"]
+        );
+    }
+
+    #[test]
+    fn barcode_moves_in_with_its_line() {
+        let label = interpret(&parse_zpl("^XA^FO0,0^BEN,50^FD12345678^FS^XZ").unwrap());
+        let ZplElement::Barcode { x, content, .. } = &label.elements[0] else {
+            panic!("expected a barcode");
+        };
+        // the leading digit starts at the label edge, left of the bars
+        assert_eq!(content.text_elements()[0].text_x, 0);
+        assert!(*x > 0);
+        assert_eq!(label.width, x + content.bitmap.width);
+    }
+
+    #[test]
+    fn code_128_defaults_keep_data_unchanged() {
+        for (input, data) in [
+            ("^XA^FO10,10^BCN,50^FDABC^FS^XZ", "ABC"),
+            ("^XA^FO10,10^BC^FD12345^FS^XZ", "12345"),
+        ] {
+            let label = interpret(&parse_zpl(input).unwrap());
+            let ZplElement::Barcode { content, .. } = &label.elements[0] else {
+                panic!("expected a barcode");
+            };
+            // no check digit, and the interpretation line below the bars
+            let text = &content.text_elements()[0];
+            assert_eq!(text.text, data);
+            assert!(text.text_y > 10, "{input}");
+        }
+    }
+
+    #[test]
+    fn graphic_shapes_at_field_typeset() {
+        let input = "^XA^FT10,100^GC50,5^FS^FT10,100^GE60,40,5^FS\
+            ^FO10,100^GD30,20,5^FS^XZ";
+        assert_eq!(positions(input), vec![(10, 50), (10, 60), (10, 100)]);
+    }
+
+    #[test]
+    fn print_orientation_and_reverse_apply_to_label() {
+        let label = interpret(&parse_zpl("^XA^POI^LRY^FO10,20^GB10,10,10^FS^XZ").unwrap());
+        assert!(label.inverted);
+        assert!(label.reverse);
+
+        let label = interpret(&parse_zpl("^XA^LRY^LRN^FO10,20^GB10,10,10^FS^PON^XZ").unwrap());
+        assert!(!label.inverted);
+        assert!(!label.reverse);
+    }
+
+    #[test]
+    fn maxicode_follows_printer_resolution() {
+        let maxicode_size = |input: &str, dots_per_mm| {
+            let label = interpret_with_resolution(&parse_zpl(input).unwrap(), dots_per_mm);
+            let Some(ZplElement::Barcode { content, .. }) = label.elements.last() else {
+                panic!("expected barcode");
+            };
+            (content.bitmap.width, content.bitmap.height)
+        };
+        let input = "^XA^FO10,10^BD4^FDMAXICODE^FS^XZ";
+        assert_eq!(maxicode_size(input, 8), (225, 215));
+        assert_eq!(maxicode_size(input, 12), (338, 323));
+
+        // ^JMB halves the resolution for all following fields, the last one is checked
+        let input = "^XA^JMB^FO10,10^BD4^FDMAXICODE^FS^FO10,300^BD4^FDMAXICODE^FS^XZ";
+        assert_eq!(maxicode_size(input, 12), (169, 161));
+    }
 }
@@ -6,9 +6,57 @@ pub enum Code128Mode {
     Ean,
 }
 
+/// QR error correction level, from ultra-high reliability to high density
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QrErrorCorrection {
+    High,
+    Quality,
+    Medium,
+    Low,
+}
+
+/// Check digits of an MSI barcode
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MsiCheckDigit {
+    None,
+    Mod10,
+    Mod10Mod10,
+    Mod11Mod10,
+}
+
+/// Symbology of a ^BR GS1 DataBar barcode.
+///
+/// The composite symbologies are rendered without their 2D component: field
+/// data without `|` is drawn as the linear symbol, a CC-A/B/C component
+/// after `|` leaves the field out with a warning.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DataBarType {
+    Omnidirectional,
+    Truncated,
+    Stacked,
+    StackedOmnidirectional,
+    Limited,
+    /// Stacked in rows of `segment_width` segments when wider
+    Expanded,
+    /// Composite, only the linear component can be drawn
+    UpcA,
+    UpcE,
+    Ean13,
+    Ean8,
+    Gs1128CcAb,
+    Gs1128CcC,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BarcodeType {
-    Code39,
+    Code39 {
+        orientation: super::Orientation,
+        /// Mod-43 check digit
+        check_digit: bool,
+        height: Option<usize>,
+        show_text: bool,
+        text_above: bool,
+    },
     Code128 {
         orientation: super::Orientation,
         height: Option<usize>,
@@ -17,40 +65,261 @@ pub enum BarcodeType {
         check_digit: bool,
         mode: Code128Mode,
     },
-    Pdf417,
-    Ean8,
+    Pdf417 {
+        orientation: super::Orientation,
+        /// Height of every row in dots
+        row_height: Option<usize>,
+        /// Error correction level 0 to 8
+        security_level: u8,
+        columns: Option<u8>,
+        rows: Option<u8>,
+        /// Compact PDF417 without the right row indicator
+        truncate: bool,
+    },
+    MicroPdf417 {
+        orientation: super::Orientation,
+        /// Height of every row in dots
+        row_height: Option<usize>,
+        /// Symbol size and error correction, 0 to 33
+        mode: u8,
+    },
+    Ean8 {
+        orientation: super::Orientation,
+        height: Option<usize>,
+        show_text: bool,
+        text_above: bool,
+    },
+    UpcA {
+        orientation: super::Orientation,
+        height: Option<usize>,
+        show_text: bool,
+        text_above: bool,
+        /// Print the check digit in the interpretation line
+        check_digit: bool,
+    },
+    UpcE {
+        orientation: super::Orientation,
+        height: Option<usize>,
+        show_text: bool,
+        text_above: bool,
+        /// Print the check digit in the interpretation line
+        check_digit: bool,
+    },
     Ean13 {
         orientation: super::Orientation,
         height: Option<usize>,
         show_text: bool,
         text_above: bool,
     },
-    Qr,
-    DataMatrix,
+    /// 2 or 5 digit add-on placed next to an EAN-13 or UPC-A
+    UpcEanExtension {
+        orientation: super::Orientation,
+        height: Option<usize>,
+        show_text: bool,
+        text_above: bool,
+    },
+    Interleaved2Of5 {
+        orientation: super::Orientation,
+        height: Option<usize>,
+        show_text: bool,
+        text_above: bool,
+        /// Mod-10 check digit
+        check_digit: bool,
+    },
+    Standard2Of5 {
+        orientation: super::Orientation,
+        height: Option<usize>,
+        show_text: bool,
+        text_above: bool,
+    },
+    Industrial2Of5 {
+        orientation: super::Orientation,
+        height: Option<usize>,
+        show_text: bool,
+        text_above: bool,
+    },
+    Code93 {
+        orientation: super::Orientation,
+        height: Option<usize>,
+        show_text: bool,
+        text_above: bool,
+        /// Print the two mandatory check characters in the interpretation line
+        check_digit: bool,
+    },
+    Codabar {
+        orientation: super::Orientation,
+        /// Mod-16 check character
+        check_digit: bool,
+        height: Option<usize>,
+        show_text: bool,
+        text_above: bool,
+        /// Start and stop character, A to D
+        start: char,
+        stop: char,
+    },
+    Code11 {
+        orientation: super::Orientation,
+        /// Only the C check digit instead of both C and K
+        single_check_digit: bool,
+        height: Option<usize>,
+        show_text: bool,
+        text_above: bool,
+    },
+    Msi {
+        orientation: super::Orientation,
+        check_digit: MsiCheckDigit,
+        height: Option<usize>,
+        show_text: bool,
+        text_above: bool,
+        /// Print the check digits in the interpretation line
+        text_check_digit: bool,
+    },
+    Plessey {
+        orientation: super::Orientation,
+        /// Print the CRC check digits in the interpretation line
+        check_digit: bool,
+        height: Option<usize>,
+        show_text: bool,
+        text_above: bool,
+    },
+    DataBar {
+        orientation: super::Orientation,
+        symbology: DataBarType,
+        /// Module width in dots, 1 to 10
+        magnification: u8,
+        /// Height of the separator rows in modules, 1 or 2
+        separator_height: u8,
+        /// Bar height of the UPC/EAN and GS1-128 linear components, the
+        /// DataBar symbologies have a fixed height
+        height: usize,
+        /// Segments per row of a stacked DataBar Expanded, even from 2 to 22
+        segment_width: u8,
+    },
+    Qr {
+        orientation: super::Orientation,
+        /// Model 1 (original) or 2 (enhanced)
+        model: u8,
+        /// Module size in dots, 1 to 10
+        magnification: Option<u8>,
+        /// Used when the field data does not select a level itself
+        error_correction: QrErrorCorrection,
+        mask: u8,
+    },
+    Aztec {
+        orientation: super::Orientation,
+        /// Module size in dots, 1 to 10
+        magnification: Option<u8>,
+        /// The field data contains ECI sequences
+        extended_channel: bool,
+        /// 0 default error correction, 1 to 99 error correction percentage,
+        /// 101 to 104 compact and 201 to 232 full range symbols with 1 to 32
+        /// layers, 300 Aztec Rune
+        size: u16,
+        menu_symbol: bool,
+        /// Number of symbols for structured append, the optional ID is not kept
+        symbol_count: u8,
+    },
+    MaxiCode {
+        /// 2 and 3 carry a structured carrier message, 4 to 6 free text
+        mode: u8,
+        /// Position in a structured append of `symbol_count` symbols
+        symbol_number: u8,
+        symbol_count: u8,
+    },
+    DataMatrix {
+        orientation: super::Orientation,
+        /// Module size in dots, the ^BY height is the approximate symbol
+        /// height when missing
+        element_height: Option<usize>,
+        /// ECC 0 to 140 or 200, symbols are always generated with ECC 200
+        quality: u16,
+        columns: Option<usize>,
+        rows: Option<usize>,
+        /// Only used by ECC 0 to 140
+        format_id: u8,
+        /// Escape character for control sequences like FNC1 in the field data
+        escape_char: char,
+        rectangular: bool,
+    },
 }
 
 impl BarcodeType {
     pub fn height(&self) -> Option<usize> {
         match self {
-            BarcodeType::Code39 => todo!(),
+            BarcodeType::Code39 { height, .. } => *height,
             BarcodeType::Code128 { height, .. } => *height,
-            BarcodeType::Pdf417 => todo!(),
-            BarcodeType::Ean8 => todo!(),
-            BarcodeType::Ean13 { height, .. } => *height,
-            BarcodeType::Qr => todo!(),
-            BarcodeType::DataMatrix => todo!(),
+            BarcodeType::Pdf417 { row_height, .. }
+            | BarcodeType::MicroPdf417 { row_height, .. } => *row_height,
+            BarcodeType::Ean8 { height, .. }
+            | BarcodeType::UpcA { height, .. }
+            | BarcodeType::UpcE { height, .. }
+            | BarcodeType::Ean13 { height, .. }
+            | BarcodeType::UpcEanExtension { height, .. }
+            | BarcodeType::Interleaved2Of5 { height, .. }
+            | BarcodeType::Standard2Of5 { height, .. }
+            | BarcodeType::Industrial2Of5 { height, .. }
+            | BarcodeType::Code93 { height, .. }
+            | BarcodeType::Codabar { height, .. }
+            | BarcodeType::Code11 { height, .. }
+            | BarcodeType::Msi { height, .. }
+            | BarcodeType::Plessey { height, .. } => *height,
+            BarcodeType::DataBar { height, .. } => Some(*height),
+            BarcodeType::Qr { .. } => None,
+            BarcodeType::Aztec { .. } | BarcodeType::MaxiCode { .. } => None,
+            BarcodeType::DataMatrix { .. } => None,
+        }
+    }
+
+    pub fn orientation(&self) -> super::Orientation {
+        match self {
+            BarcodeType::Code39 { orientation, .. }
+            | BarcodeType::Code128 { orientation, .. }
+            | BarcodeType::Pdf417 { orientation, .. }
+            | BarcodeType::MicroPdf417 { orientation, .. }
+            | BarcodeType::Ean8 { orientation, .. }
+            | BarcodeType::UpcA { orientation, .. }
+            | BarcodeType::UpcE { orientation, .. }
+            | BarcodeType::Ean13 { orientation, .. }
+            | BarcodeType::UpcEanExtension { orientation, .. }
+            | BarcodeType::Interleaved2Of5 { orientation, .. }
+            | BarcodeType::Standard2Of5 { orientation, .. }
+            | BarcodeType::Industrial2Of5 { orientation, .. }
+            | BarcodeType::Code93 { orientation, .. }
+            | BarcodeType::Codabar { orientation, .. }
+            | BarcodeType::Code11 { orientation, .. }
+            | BarcodeType::Msi { orientation, .. }
+            | BarcodeType::Plessey { orientation, .. }
+            | BarcodeType::DataBar { orientation, .. }
+            | BarcodeType::Qr { orientation, .. }
+            | BarcodeType::Aztec { orientation, .. }
+            | BarcodeType::DataMatrix { orientation, .. } => *orientation,
+            // MaxiCode symbols are never rotated
+            BarcodeType::MaxiCode { .. } => super::Orientation::Normal,
         }
     }
 
     pub fn show_content(&self) -> bool {
         match self {
-            BarcodeType::Code39 => todo!(),
+            BarcodeType::Code39 { show_text, .. } => *show_text,
             BarcodeType::Code128 { show_text, .. } => *show_text,
-            BarcodeType::Pdf417 => todo!(),
-            BarcodeType::Ean8 => todo!(),
-            BarcodeType::Ean13 { show_text, .. } => *show_text,
-            BarcodeType::Qr => todo!(),
-            BarcodeType::DataMatrix => todo!(),
+            BarcodeType::Pdf417 { .. } | BarcodeType::MicroPdf417 { .. } => false,
+            BarcodeType::Ean8 { show_text, .. }
+            | BarcodeType::UpcA { show_text, .. }
+            | BarcodeType::UpcE { show_text, .. }
+            | BarcodeType::Ean13 { show_text, .. }
+            | BarcodeType::UpcEanExtension { show_text, .. }
+            | BarcodeType::Interleaved2Of5 { show_text, .. }
+            | BarcodeType::Standard2Of5 { show_text, .. }
+            | BarcodeType::Industrial2Of5 { show_text, .. }
+            | BarcodeType::Code93 { show_text, .. }
+            | BarcodeType::Codabar { show_text, .. }
+            | BarcodeType::Code11 { show_text, .. }
+            | BarcodeType::Msi { show_text, .. }
+            | BarcodeType::Plessey { show_text, .. } => *show_text,
+            BarcodeType::DataBar { .. } => true,
+            BarcodeType::Qr { .. } => false,
+            BarcodeType::Aztec { .. } | BarcodeType::MaxiCode { .. } => false,
+            BarcodeType::DataMatrix { .. } => false,
         }
     }
 }
@@ -24,9 +24,10 @@ pub struct GraficData {
     pub data: String,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub enum Orientation {
-    Normal,     // 0°
+    #[default]
+    Normal, // 0°
     Rotate,     // 90°
     Invert,     // 180°
     BackRotate, // 270°
@@ -57,9 +58,9 @@ pub enum Justification {
 impl From<Option<u8>> for Justification {
     fn from(value: Option<u8>) -> Self {
         match value {
-            Some(u) if u == 0 => Justification::Left,
-            Some(u) if u == 1 => Justification::Right,
-            Some(u) if u == 2 => Justification::Auto,
+            Some(0) => Justification::Left,
+            Some(1) => Justification::Right,
+            Some(2) => Justification::Auto,
             Some(_) => Justification::Left,
             None => Justification::Left,
         }
@@ -85,14 +86,33 @@ pub enum Color {
 impl From<Option<&str>> for Color {
     fn from(value: Option<&str>) -> Self {
         match value {
-            Some(c) if c == "B" => Self::Black,
-            Some(c) if c == "W" => Self::White,
+            Some("B") => Self::Black,
+            Some("W") => Self::White,
             Some(_) => Self::Black,
             None => Self::Black,
         }
     }
 }
 
+/// ^GD, direction of the diagonal line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DiagonalOrientation {
+    /// `R` or `/`, from the bottom left to the top right corner
+    #[default]
+    Right,
+    /// `L` or `\`, from the top left to the bottom right corner
+    Left,
+}
+
+impl From<Option<char>> for DiagonalOrientation {
+    fn from(value: Option<char>) -> Self {
+        match value {
+            Some('L') | Some('\\') => Self::Left,
+            _ => Self::Right,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum ClockMode {
     #[default]
@@ -181,7 +201,21 @@ pub enum ClockFormat {
 pub enum ZplFormatCommand {
     LabelLength(usize),
     PrintWidth(usize),
-    LabelShift(usize),
+    LabelShift(isize),
+    LabelTop(isize),
+    /// ^PO, `true` for ^POI printing the label upside down
+    PrintOrientation {
+        inverted: bool,
+    },
+    /// ^LR, reverse print all fields
+    LabelReverse(bool),
+    /// ^JM, `true` for ^JMB printing at half the dots per millimeter
+    HalfResolution(bool),
+    /// ^LH, omitted parameters keep the current label home
+    LabelHome {
+        x: Option<usize>,
+        y: Option<usize>,
+    },
     BarcodeConfig {
         width: u8,
         width_ratio: f32,
@@ -195,19 +229,27 @@ pub enum ZplFormatCommand {
     },
     Font {
         name: char,
-        orientation: Orientation,
+        /// `None` uses the default set by ^FW
+        orientation: Option<Orientation>,
         height: usize,
         width: usize,
     },
+    /// ^FW, default orientation and justification of the following fields
+    FieldOrientation {
+        orientation: Orientation,
+        justification: Justification,
+    },
+    /// ^FO, without justification the one of ^FW applies
     FieldOrigin {
         x: usize,
         y: usize,
-        justification: Justification,
+        justification: Option<Justification>,
     },
+    /// ^FT, without justification the one of ^FW applies
     FieldTypeset {
         x: usize,
         y: usize,
-        justification: Justification,
+        justification: Option<Justification>,
     },
     FieldData(String),
     GraphicField {
@@ -224,6 +266,24 @@ pub enum ZplFormatCommand {
         color: Color,
         rounding: u8,
     },
+    GraphicalCircle {
+        diameter: usize,
+        thickness: usize,
+        color: Color,
+    },
+    GraphicalEllipse {
+        width: usize,
+        height: usize,
+        thickness: usize,
+        color: Color,
+    },
+    GraphicalDiagonalLine {
+        width: usize,
+        height: usize,
+        thickness: usize,
+        color: Color,
+        orientation: DiagonalOrientation,
+    },
     Inverted,
     FieldHexIndicator {
         char: char,
@@ -258,16 +318,96 @@ pub enum ZplFormatCommand {
         second: Option<u8>,
         format: ClockFormat,
     },
+    StoreFormat {
+        name: String,
+    },
+    RecallFormat {
+        name: String,
+    },
+    FieldNumber(u16),
+    /// ^CC/~CC or ^CT/~CT, the command prefixes in effect from here on
+    ChangePrefix {
+        caret: char,
+        tilde: char,
+    },
+    /// Command that is not supported or could not be parsed (lenient mode only)
+    Unknown {
+        code: String,
+        params: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadFormat {
+    Ascii,      // A
+    Binary,     // B
+    Compressed, // C
+    Png,        // P
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BackfeedSequence {
+    After,  // A
+    Before, // B
+    #[default]
+    Normal, // N
+    Off,    // O
+    Percent(u8),
+}
+
+impl From<&str> for BackfeedSequence {
+    fn from(value: &str) -> Self {
+        match value {
+            "A" => BackfeedSequence::After,
+            "B" => BackfeedSequence::Before,
+            "N" => BackfeedSequence::Normal,
+            "O" => BackfeedSequence::Off,
+            _ => match value.parse::<u8>() {
+                Ok(n) => BackfeedSequence::Percent(n),
+                Err(_) => BackfeedSequence::Normal,
+            },
+        }
+    }
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub enum ZplHostCommand {
     CancelAllCommands,    // ~JA
     CancelCurrentCommand, // ~JC
     PrintHostStatus,      // ~HS
-    DownloadGraphics,     // ~DG
+    HostIdentification,   // ~HI
+    // ~DG
+    DownloadGraphics {
+        name: String,
+        total_bytes: usize,
+        row_bytes: usize,
+        data: GraficData,
+    },
+    // ~DY
+    DownloadObject {
+        name: String,
+        format: DownloadFormat,
+        extension: String,
+        total_bytes: usize,
+        row_bytes: Option<usize>,
+        data: GraficData,
+    },
+    // ~DU
+    DownloadUnboundedFont {
+        name: String,
+        size: usize,
+        data: String,
+    },
+    SetDarkness(u8),                    // ~SD
+    TearOffAdjust(isize),               // ~TA
+    BackfeedSequence(BackfeedSequence), // ~JS
 }
 
+/// Top level item of a ZPL stream
+#[derive(Debug, Clone, PartialEq)]
 pub enum ZplCommand {
+    StartFormat, // ^XA
+    EndFormat,   // ^XZ
     Format(ZplFormatCommand),
     Host(ZplHostCommand),
 }
@@ -1,4 +1,5 @@
 mod barcode;
+#[allow(clippy::module_inception)]
 mod commands;
 
 pub use barcode::*;
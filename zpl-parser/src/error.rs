@@ -1,5 +1,7 @@
 use std::fmt::{self, Display};
 
+use crate::Location;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ParseErrorKind {
     InvalidSyntax,
@@ -11,6 +13,14 @@ pub enum ParseErrorKind {
 pub struct ParseError {
     pub kind: ParseErrorKind,
     pub message: String,
+    pub location: Option<Location>,
+}
+
+impl ParseError {
+    pub(crate) fn with_location(mut self, location: Option<Location>) -> Self {
+        self.location = location;
+        self
+    }
 }
 
 impl fmt::Display for ParseError {
@@ -21,12 +31,49 @@ impl fmt::Display for ParseError {
             ParseErrorKind::MissingCommand => {
                 write!(f, "Missing command: {}", self.message)
             }
+        }?;
+        if let Some(location) = self.location {
+            write!(f, " at {location}")?;
         }
+        Ok(())
     }
 }
 
 impl std::error::Error for ParseError {}
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseWarningKind {
+    UnknownCommand,
+    MalformedCommand,
+    UnexpectedInput,
+    MissingCommand,
+}
+
+/// Problem the lenient parser recovered from
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseWarning {
+    pub kind: ParseWarningKind,
+    pub message: String,
+    pub location: Option<Location>,
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ParseWarningKind::UnknownCommand => write!(f, "Unknown command: {}", self.message),
+            ParseWarningKind::MalformedCommand => {
+                write!(f, "Malformed command: {}...", self.message)
+            }
+            ParseWarningKind::UnexpectedInput => write!(f, "Unexpected input: {}...", self.message),
+            ParseWarningKind::MissingCommand => write!(f, "Missing command: {}", self.message),
+        }?;
+        if let Some(location) = self.location {
+            write!(f, " at {location}")?;
+        }
+        Ok(())
+    }
+}
+
 impl<T: Display> From<nom::Err<nom::error::Error<T>>> for ParseError {
     fn from(value: nom::Err<nom::error::Error<T>>) -> Self {
         match value {
@@ -38,6 +85,7 @@ impl<T: Display> From<nom::Err<nom::error::Error<T>>> for ParseError {
                 ParseError {
                     kind: ParseErrorKind::IncompleteInput,
                     message,
+                    location: None,
                 }
             }
             nom::Err::Error(err) | nom::Err::Failure(err) => {
@@ -46,6 +94,7 @@ impl<T: Display> From<nom::Err<nom::error::Error<T>>> for ParseError {
                 ParseError {
                     kind: ParseErrorKind::InvalidSyntax,
                     message,
+                    location: None,
                 }
             }
         }
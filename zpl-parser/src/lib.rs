@@ -1,7 +1,14 @@
 mod commands;
 mod error;
 mod parse;
+mod serialize;
+mod span;
 
 pub use commands::*;
 pub use error::*;
-pub use parse::parse_zpl;
+pub use parse::{
+    LenientParse, parse_zpl, parse_zpl_labels, parse_zpl_labels_spanned, parse_zpl_lenient,
+    parse_zpl_spanned, parse_zpl_stream,
+};
+pub use serialize::to_zpl;
+pub use span::{Location, Span, Spanned};
@@ -2,969 +2,3438 @@ use nom::{
     IResult, Parser,
     branch::alt,
     bytes::{
-        complete::{tag, take_till, take_until},
+        complete::{tag, take_till, take_until, take_while_m_n},
         take,
     },
     character::complete::{
         alpha1, alphanumeric1, anychar, char, digit1, i8 as parse_i8, isize as parse_isize,
-        line_ending, multispace0, satisfy, u8 as parse_u8, usize as parse_usize,
+        line_ending, multispace0, none_of, one_of, satisfy, u8 as parse_u8, u16 as parse_u16,
+        usize as parse_usize,
     },
-    combinator::{complete, cut, map, not, opt, peek},
+    combinator::{complete, consumed, cut, map, not, opt, peek},
     error::{Error, ErrorKind},
-    multi::{many_till, many1},
+    multi::{many_till, many0},
     number::complete::float as parse_float,
-    sequence::{preceded, tuple},
+    sequence::preceded,
 };
 
 use crate::{
-    BarcodeType, ClockFormat, ClockLanguage, Code128Mode, Color, ParseError, ParseErrorKind,
+    BarcodeType, ClockFormat, Code128Mode, Color, DataBarType, MsiCheckDigit, ParseError,
+    ParseErrorKind, ParseWarning, ParseWarningKind, QrErrorCorrection, Spanned,
     TextBlockJustification,
-    commands::{CompressionMethod, CompressionType, GraficData, Orientation, ZplFormatCommand},
+    commands::{
+        CompressionMethod, CompressionType, DownloadFormat, GraficData, Orientation, ZplCommand,
+        ZplFormatCommand, ZplHostCommand,
+    },
+    span::LineIndex,
 };
 
-pub fn parse_pw(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, _) = tag("^PW")(input)?;
-    let (input, width) = parse_usize(input)?;
-    Ok((input, ZplFormatCommand::PrintWidth(width)))
+/// Command prefixes, changed by ^CC/~CC and ^CT/~CT
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Prefixes {
+    caret: char,
+    tilde: char,
 }
 
-pub fn parse_ll(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, _) = tag("^LL")(input)?;
-    let (input, length) = parse_usize(input)?;
-    Ok((input, ZplFormatCommand::LabelLength(length)))
+impl Default for Prefixes {
+    fn default() -> Self {
+        Self {
+            caret: '^',
+            tilde: '~',
+        }
+    }
 }
 
-pub fn parse_ls(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, _) = tag("^LS")(input)?;
-    let (input, length) = parse_usize(input)?;
-    Ok((input, ZplFormatCommand::LabelShift(length)))
+impl Prefixes {
+    fn is_prefix(self, c: char) -> bool {
+        c == self.caret || c == self.tilde
+    }
+
+    fn command(self) -> ZplFormatCommand {
+        ZplFormatCommand::ChangePrefix {
+            caret: self.caret,
+            tilde: self.tilde,
+        }
+    }
 }
 
-fn parse_cf(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, _) = tag("^CF")(input)?;
-    let (input, (name, _, height, _, width)) = tuple((
-        take(1u8),
-        char(','),
-        opt(parse_usize),
-        opt(char(',')),
-        opt(parse_usize),
-    ))(input)?;
+fn prefixed<'a>(prefix: char, code: &'static str, input: &'a str) -> IResult<&'a str, &'a str> {
+    match input
+        .strip_prefix(prefix)
+        .and_then(|rest| rest.strip_prefix(code))
+    {
+        Some(rest) => Ok((rest, &input[..input.len() - rest.len()])),
+        None => Err(nom::Err::Error(Error::new(input, ErrorKind::Tag))),
+    }
+}
 
-    let (height, width) = match (height, width) {
-        (None, None) => return IResult::Err(nom::Err::Error(Error::new(input, ErrorKind::NoneOf))),
-        (None, Some(w)) => (w, w),
-        (Some(h), None) => (h, h),
-        (Some(h), Some(w)) => (h, w),
-    };
+/// Format command `code`, prefixed by the active caret
+fn caret<'a>(
+    prefixes: Prefixes,
+    code: &'static str,
+) -> impl Fn(&'a str) -> IResult<&'a str, &'a str> {
+    move |input| prefixed(prefixes.caret, code, input)
+}
 
-    let name = name.chars().next().unwrap_or('A');
-    Ok((
-        input,
-        ZplFormatCommand::ChangeFont {
-            name,
-            height,
-            width,
-        },
-    ))
+/// Host command `code`, prefixed by the active tilde
+fn tilde<'a>(
+    prefixes: Prefixes,
+    code: &'static str,
+) -> impl Fn(&'a str) -> IResult<&'a str, &'a str> {
+    move |input| prefixed(prefixes.tilde, code, input)
 }
 
-pub fn parse_a(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, _) = tag("^A")(input)?;
+/// Input up to the format command `code`
+fn take_until_caret<'a>(
+    prefixes: Prefixes,
+    code: &'static str,
+) -> impl Fn(&'a str) -> IResult<&'a str, &'a str> {
+    move |input| {
+        let pattern = format!("{}{code}", prefixes.caret);
+        take_until(pattern.as_str())(input)
+    }
+}
 
-    let (input, (font, orientation, _, height, _, width)) = tuple((
-        take(1u8),
-        take(1u8),
-        char(','),
-        parse_usize,
-        char(','),
-        parse_usize,
-    ))(input)?;
+/// Parameters of a command, the input up to the next command prefix
+fn take_parameters<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, &'a str> {
+    move |input| take_till(|c| prefixes.is_prefix(c))(input)
+}
 
-    let font = font.chars().next().unwrap_or('A');
-    let (_, orientation) = Orientation::try_from_str(orientation)?;
-    Ok((
-        input,
-        ZplFormatCommand::Font {
-            name: font,
-            orientation,
-            height,
-            width,
-        },
-    ))
+/// parse ^CC/~CC and ^CT/~CT, returns the prefixes in effect afterwards
+fn parse_prefix_change<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, Prefixes> {
+    move |input| {
+        let (input, (code, prefix)) = (
+            alt((
+                caret(prefixes, "CC"),
+                tilde(prefixes, "CC"),
+                caret(prefixes, "CT"),
+                tilde(prefixes, "CT"),
+            )),
+            satisfy(|c| !c.is_whitespace()),
+        )
+            .parse(input)?;
+
+        let mut prefixes = prefixes;
+        match code.ends_with("CC") {
+            true => prefixes.caret = prefix,
+            false => prefixes.tilde = prefix,
+        }
+        Ok((input, prefixes))
+    }
+}
+
+pub fn parse_pw<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "PW")(input)?;
+        let (input, width) = parse_usize(input)?;
+        Ok((input, ZplFormatCommand::PrintWidth(width)))
+    }
+}
+
+pub fn parse_ll<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "LL")(input)?;
+        let (input, length) = parse_usize(input)?;
+        Ok((input, ZplFormatCommand::LabelLength(length)))
+    }
+}
+
+pub fn parse_ls<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "LS")(input)?;
+        let (input, shift) = parse_isize(input)?;
+        Ok((input, ZplFormatCommand::LabelShift(shift)))
+    }
+}
+
+fn parse_lt<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "LT")(input)?;
+        let (input, top) = parse_isize(input)?;
+        Ok((input, ZplFormatCommand::LabelTop(top)))
+    }
+}
+
+fn parse_po<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "PO")(input)?;
+        let (input, orientation) = opt(one_of("NI")).parse(input)?;
+        Ok((
+            input,
+            ZplFormatCommand::PrintOrientation {
+                inverted: orientation == Some('I'),
+            },
+        ))
+    }
+}
+
+fn parse_lr<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "LR")(input)?;
+        let (input, reverse) = opt(one_of("YN")).parse(input)?;
+        Ok((input, ZplFormatCommand::LabelReverse(reverse == Some('Y'))))
+    }
+}
+
+fn parse_jm<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "JM")(input)?;
+        let (input, resolution) = opt(one_of("AB")).parse(input)?;
+        Ok((
+            input,
+            ZplFormatCommand::HalfResolution(resolution == Some('B')),
+        ))
+    }
+}
+
+fn parse_lh<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "LH")(input)?;
+        let (input, x) = opt(parse_usize).parse(input)?;
+        let (input, y) = opt(preceded(char(','), opt(parse_usize))).parse(input)?;
+        Ok((input, ZplFormatCommand::LabelHome { x, y: y.flatten() }))
+    }
+}
+
+fn parse_cf<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "CF")(input)?;
+        let (input, (name, _, height, _, width)) = (
+            take(1u8),
+            char(','),
+            opt(parse_usize),
+            opt(char(',')),
+            opt(parse_usize),
+        )
+            .parse(input)?;
+
+        let (height, width) = match (height, width) {
+            (None, None) => {
+                return IResult::Err(nom::Err::Error(Error::new(input, ErrorKind::NoneOf)));
+            }
+            (None, Some(w)) => (w, w),
+            (Some(h), None) => (h, h),
+            (Some(h), Some(w)) => (h, w),
+        };
+
+        let name = name.chars().next().unwrap_or('A');
+        Ok((
+            input,
+            ZplFormatCommand::ChangeFont {
+                name,
+                height,
+                width,
+            },
+        ))
+    }
+}
+
+pub fn parse_a<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "A")(input)?;
+
+        let (input, (font, orientation, _, height, _, width)) = (
+            take(1u8),
+            opt(take_while_m_n(1, 1, |c| "NRIB".contains(c))),
+            char(','),
+            parse_usize,
+            char(','),
+            parse_usize,
+        )
+            .parse(input)?;
+
+        let font = font.chars().next().unwrap_or('A');
+        let orientation = match orientation {
+            Some(orientation) => Some(Orientation::try_from_str(orientation)?.1),
+            None => None,
+        };
+        Ok((
+            input,
+            ZplFormatCommand::Font {
+                name: font,
+                orientation,
+                height,
+                width,
+            },
+        ))
+    }
+}
+
+fn parse_fw<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "FW")(input)?;
+        let (input, orientation) =
+            opt(take_while_m_n(1, 1, |c| "NRIB".contains(c))).parse(input)?;
+        let (input, justification) = opt(preceded(char(','), parse_u8)).parse(input)?;
+
+        let orientation = match orientation {
+            Some(orientation) => Orientation::try_from_str(orientation)?.1,
+            None => Orientation::Normal,
+        };
+        Ok((
+            input,
+            ZplFormatCommand::FieldOrientation {
+                orientation,
+                justification: justification.into(),
+            },
+        ))
+    }
 }
 
 fn parse_coordinates(input: &str) -> IResult<&str, (usize, usize, Option<u8>)> {
     alt((
         map(
-            tuple((parse_usize, char(','), parse_usize, char(','), parse_u8)),
+            (parse_usize, char(','), parse_usize, char(','), parse_u8),
             |(x, _, y, _, z)| (x, y, Some(z)),
         ),
-        map(tuple((parse_usize, char(','), parse_usize)), |(x, _, y)| {
+        map((parse_usize, char(','), parse_usize), |(x, _, y)| {
             (x, y, None)
         }),
     ))
     .parse(input)
 }
 
-pub fn parse_fo(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, (x, y, justification)) = preceded(tag("^FO"), parse_coordinates).parse(input)?;
-    Ok((
-        input,
-        ZplFormatCommand::FieldOrigin {
-            x,
-            y,
-            justification: justification.into(),
-        },
-    ))
+pub fn parse_fo<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, (x, y, justification)) =
+            preceded(caret(prefixes, "FO"), parse_coordinates).parse(input)?;
+        Ok((
+            input,
+            ZplFormatCommand::FieldOrigin {
+                x,
+                y,
+                justification: justification.map(|z| Some(z).into()),
+            },
+        ))
+    }
 }
 
-pub fn parse_ft(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, (x, y, justification)) = preceded(tag("^FT"), parse_coordinates).parse(input)?;
-    Ok((
-        input,
-        ZplFormatCommand::FieldTypeset {
-            x,
-            y,
-            justification: justification.into(),
-        },
-    ))
+pub fn parse_ft<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, (x, y, justification)) =
+            preceded(caret(prefixes, "FT"), parse_coordinates).parse(input)?;
+        Ok((
+            input,
+            ZplFormatCommand::FieldTypeset {
+                x,
+                y,
+                justification: justification.map(|z| Some(z).into()),
+            },
+        ))
+    }
 }
 
-pub fn parse_fd(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, _) = tag("^FD")(input)?;
-    let (input, text) = take_until("^FS")(input)?;
-    Ok((input, ZplFormatCommand::FieldData(text.to_string())))
+pub fn parse_fd<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "FD")(input)?;
+        let (input, text) = take_until_caret(prefixes, "FS")(input)?;
+        Ok((input, ZplFormatCommand::FieldData(text.to_string())))
+    }
 }
 
-pub fn parse_fs(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, _) = tag("^FS")(input)?;
-    Ok((input, ZplFormatCommand::FieldSeparator))
+pub fn parse_fs<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "FS")(input)?;
+        Ok((input, ZplFormatCommand::FieldSeparator))
+    }
 }
 
-pub fn parse_fg(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, _) = tag("^GF")(input)?;
-    let (input, (compression_type, _, data_bytes, _, total_bytes, _, row_bytes, _)) = tuple((
-        alpha1,
-        char(','),
-        parse_usize,
-        char(','),
-        parse_usize,
-        char(','),
-        parse_usize,
-        char(','),
-    ))(input)?;
+pub fn parse_fg<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "GF")(input)?;
+        let (input, (compression_type, _, data_bytes, _, total_bytes, _, row_bytes, _)) = (
+            alpha1,
+            char(','),
+            parse_usize,
+            char(','),
+            parse_usize,
+            char(','),
+            parse_usize,
+            char(','),
+        )
+            .parse(input)?;
 
-    let compression_type = match compression_type {
-        "A" => CompressionType::Ascii,
-        "B" => CompressionType::Binary,
-        "C" => CompressionType::Compressed,
-        _ => return IResult::Err(nom::Err::Error(Error::new(input, ErrorKind::NoneOf))),
-    };
+        let compression_type = match compression_type {
+            "A" => CompressionType::Ascii,
+            "B" => CompressionType::Binary,
+            "C" => CompressionType::Compressed,
+            _ => return IResult::Err(nom::Err::Error(Error::new(input, ErrorKind::NoneOf))),
+        };
 
-    let (input, (_, compression_method, _, img_data)) =
-        tuple((char(':'), alphanumeric1, char(':'), take(data_bytes)))(input)?;
-    let (_, img_data) = take_until(":")(img_data)?;
-    let compression_method = match compression_method {
-        "Z64" => CompressionMethod::Zlib,
-        _ => return IResult::Err(nom::Err::Error(Error::new(input, ErrorKind::NoneOf))),
-    };
-    let data = GraficData {
-        compression_method,
-        data: img_data.into(),
-    };
+        let (input, (_, compression_method, _, img_data)) =
+            (char(':'), alphanumeric1, char(':'), take_bytes(data_bytes)).parse(input)?;
+        let (_, img_data) = take_until(":")(img_data)?;
+        let compression_method = match compression_method {
+            "Z64" => CompressionMethod::Zlib,
+            _ => return IResult::Err(nom::Err::Error(Error::new(input, ErrorKind::NoneOf))),
+        };
+        let data = GraficData {
+            compression_method,
+            data: img_data.into(),
+        };
 
-    Ok((
-        input,
-        ZplFormatCommand::GraphicField {
-            compression_type,
-            data_bytes,
-            total_bytes,
-            row_bytes,
-            data,
-        },
-    ))
+        Ok((
+            input,
+            ZplFormatCommand::GraphicField {
+                compression_type,
+                data_bytes,
+                total_bytes,
+                row_bytes,
+                data,
+            },
+        ))
+    }
 }
 
-fn parse_gb(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, _) = tag("^GB")(input)?;
-    let (input, graphical_box) = take_until("^FS")(input)?;
-    let (_, (width, _, height, _, thickness, _, color, _, rounding)) = tuple((
-        opt(parse_usize),
-        char(','),
-        opt(parse_usize),
-        char(','),
-        opt(parse_usize),
-        opt(char(',')),
-        opt(alpha1),
-        opt(char(',')),
-        opt(parse_u8),
-    ))(graphical_box)?;
+fn parse_gb<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "GB")(input)?;
+        let (input, graphical_box) = take_until_caret(prefixes, "FS")(input)?;
+        let (_, (width, _, height, _, thickness, _, color, _, rounding)) = (
+            opt(parse_usize),
+            char(','),
+            opt(parse_usize),
+            char(','),
+            opt(parse_usize),
+            opt(char(',')),
+            opt(alpha1),
+            opt(char(',')),
+            opt(parse_u8),
+        )
+            .parse(graphical_box)?;
 
-    let thickness = thickness.unwrap_or(1);
-    let width = width.unwrap_or(thickness);
-    let height = height.unwrap_or(thickness);
-    let color: Color = color.into();
-    let rounding = rounding.unwrap_or(0);
+        let thickness = thickness.unwrap_or(1);
+        let width = width.unwrap_or(thickness);
+        let height = height.unwrap_or(thickness);
+        let color: Color = color.into();
+        let rounding = rounding.unwrap_or(0);
 
-    Ok((
-        input,
-        ZplFormatCommand::GraphicalBox {
-            width,
-            height,
-            thickness,
-            color,
-            rounding,
-        },
-    ))
+        Ok((
+            input,
+            ZplFormatCommand::GraphicalBox {
+                width,
+                height,
+                thickness,
+                color,
+                rounding,
+            },
+        ))
+    }
 }
 
-fn parse_fr(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, _) = tag("^FR")(input)?;
-    Ok((input, ZplFormatCommand::Inverted))
+fn parse_gc<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "GC")(input)?;
+        let (input, circle) = take_until_caret(prefixes, "FS")(input)?;
+        let (_, (diameter, _, thickness, _, color)) = (
+            opt(parse_usize),
+            opt(char(',')),
+            opt(parse_usize),
+            opt(char(',')),
+            opt(alpha1),
+        )
+            .parse(circle)?;
+
+        Ok((
+            input,
+            ZplFormatCommand::GraphicalCircle {
+                diameter: diameter.unwrap_or(3),
+                thickness: thickness.unwrap_or(1),
+                color: color.into(),
+            },
+        ))
+    }
 }
 
-fn parse_by(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, _) = tag("^BY")(input)?;
-    let (input, (width, _, width_ratio, _, height)) = tuple((
-        opt(parse_u8),
-        opt(char(',')),
-        opt(parse_float),
-        opt(char(',')),
-        opt(parse_usize),
-    ))(input)?;
+fn parse_ge<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "GE")(input)?;
+        let (input, ellipse) = take_until_caret(prefixes, "FS")(input)?;
+        let (_, (width, _, height, _, thickness, _, color)) = (
+            opt(parse_usize),
+            opt(char(',')),
+            opt(parse_usize),
+            opt(char(',')),
+            opt(parse_usize),
+            opt(char(',')),
+            opt(alpha1),
+        )
+            .parse(ellipse)?;
 
-    let width = width.unwrap_or(2);
-    let width_ratio = width_ratio.unwrap_or(3.);
-    let height = height.unwrap_or(10);
+        let thickness = thickness.unwrap_or(1);
+        Ok((
+            input,
+            ZplFormatCommand::GraphicalEllipse {
+                width: width.unwrap_or(thickness),
+                height: height.unwrap_or(thickness),
+                thickness,
+                color: color.into(),
+            },
+        ))
+    }
+}
 
-    Ok((
-        input,
-        ZplFormatCommand::BarcodeConfig {
-            width,
-            width_ratio,
-            height,
-        },
-    ))
+fn parse_gd<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "GD")(input)?;
+        let (input, line) = take_until_caret(prefixes, "FS")(input)?;
+        let (_, (width, _, height, _, thickness, _, color, _, orientation)) = (
+            opt(parse_usize),
+            opt(char(',')),
+            opt(parse_usize),
+            opt(char(',')),
+            opt(parse_usize),
+            opt(char(',')),
+            opt(alpha1),
+            opt(char(',')),
+            opt(one_of("RL/\\")),
+        )
+            .parse(line)?;
+
+        let thickness = thickness.unwrap_or(1);
+        Ok((
+            input,
+            ZplFormatCommand::GraphicalDiagonalLine {
+                width: width.unwrap_or(thickness),
+                height: height.unwrap_or(thickness),
+                thickness,
+                color: color.into(),
+                orientation: orientation.into(),
+            },
+        ))
+    }
+}
+
+fn parse_fr<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "FR")(input)?;
+        Ok((input, ZplFormatCommand::Inverted))
+    }
 }
 
-// fn parse_b3(input: &str) -> IResult<&str, ZplFormatCommand> {
-//     let (input, _) = tag("^B3")(input)?;
-//     // let
-//     // Ok((input, ZplFormatCommand::))
-// }
+fn parse_by<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "BY")(input)?;
+        let (input, (width, _, width_ratio, _, height)) = (
+            opt(parse_u8),
+            opt(char(',')),
+            opt(parse_float),
+            opt(char(',')),
+            opt(parse_usize),
+        )
+            .parse(input)?;
+
+        // module width in dots, 1 to 10
+        let width = width.unwrap_or(2).clamp(1, 10);
+        let width_ratio = width_ratio.unwrap_or(3.);
+        let height = height.unwrap_or(10);
 
-fn parse_b7(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, _) = tag("^B7")(input)?;
-    Ok((input, ZplFormatCommand::Inverted))
+        Ok((
+            input,
+            ZplFormatCommand::BarcodeConfig {
+                width,
+                width_ratio,
+                height,
+            },
+        ))
+    }
 }
 
-fn parse_b8(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, _) = tag("^B8")(input)?;
-    Ok((input, ZplFormatCommand::Inverted))
+fn parse_b3<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "B3")(input)?;
+        let (input, rest) = take_parameters(prefixes)(input)?;
+        let (_, (orientation, check_digit, height, show_text, text_above)) =
+            parse_code39_params(rest)?;
+
+        Ok((
+            input,
+            ZplFormatCommand::Barcode(BarcodeType::Code39 {
+                orientation,
+                check_digit,
+                height,
+                show_text,
+                text_above,
+            }),
+        ))
+    }
 }
 
-fn parse_bc(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, _) = tag("^BC")(input)?;
+/// Orientation, check digit, height, interpretation line and line above
+/// shared by the Code 39, Code 11 and Plessey barcodes
+fn parse_code39_params(
+    input: &str,
+) -> IResult<&str, (Orientation, bool, Option<usize>, bool, bool)> {
+    let (input, (orientation, _, check_digit, _, height, _, line, _, line_above)) = (
+        opt(take_while_m_n(1, 1, |c| "NRIB".contains(c))),
+        opt(char(',')),
+        opt(alpha1),
+        opt(char(',')),
+        opt(parse_usize),
+        opt(char(',')),
+        opt(alpha1),
+        opt(char(',')),
+        opt(alpha1),
+    )
+        .parse(input)?;
 
-    let (input, rest) = take_until("^FD")(input)?;
+    let orientation = orientation
+        .and_then(|o| Orientation::try_from_str(o).ok())
+        .map(|(_, orientation)| orientation)
+        .unwrap_or(Orientation::Normal);
 
-    let (input, (orientation, _, height, _, line, _, line_above, _, check_digit, _, mode)) =
-        match rest.is_empty() {
-            true => (
-                input,
-                (
-                    None, None, None, None, None, None, None, None, None, None, None,
-                ),
-            ),
-            false => {
-                let (_, params) = tuple((
-                    opt(take(1usize)),
-                    opt(char(',')),
-                    opt(parse_usize),
-                    opt(char(',')),
-                    opt(alpha1),
-                    opt(char(',')),
-                    opt(alpha1),
-                    opt(char(',')),
-                    opt(alpha1),
-                    opt(char(',')),
-                    opt(alpha1),
-                ))(rest)?;
-                (input, params)
-            }
-        };
+    let check_digit = check_digit.is_some_and(|digit| digit == "Y");
 
-    let orientation = match orientation {
-        Some(o) => {
-            let result = Orientation::try_from_str(o);
-            result
-                .map(|(_, orientation)| orientation)
-                .unwrap_or(Orientation::Normal)
-        }
-        None => Orientation::Normal,
-    };
+    let show_text = line.map(|line| line != "N").unwrap_or(true);
 
-    let show_text = line
-        .map(|line| match line {
-            "N" => false,
-            _ => true,
-        })
-        .unwrap_or(true);
-
-    let text_above = line_above
-        .map(|l_above| match l_above {
-            "N" => false,
-            _ => true,
-        })
-        .unwrap_or(true);
-
-    let check_digit = check_digit
-        .map(|digit| match digit {
-            "N" => false,
-            _ => true,
-        })
-        .unwrap_or(true);
-
-    let mode = mode
-        .map(|mode| match mode {
-            "N" => Code128Mode::Normal,
-            "U" => Code128Mode::Ucc,
-            "D" => Code128Mode::Ean,
-            "A" => Code128Mode::Auto,
-            _ => Code128Mode::Normal,
-        })
-        .unwrap_or(Code128Mode::Normal);
+    let text_above = line_above.is_some_and(|l_above| l_above == "Y");
 
     Ok((
         input,
-        ZplFormatCommand::Barcode(BarcodeType::Code128 {
-            orientation,
-            height,
-            show_text,
-            text_above,
-            check_digit,
-            mode,
-        }),
+        (orientation, check_digit, height, show_text, text_above),
     ))
 }
 
-fn parse_be(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, _) = tag("^BE")(input)?;
-    let (input, rest) = take_until("^FD")(input)?;
+fn parse_b7<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "B7")(input)?;
+        let (input, rest) = take_parameters(prefixes)(input)?;
 
-    let (input, (orientation, _, height, _, line, _, line_above)) = match rest.is_empty() {
-        true => (input, (None, None, None, None, None, None, None)),
-        false => {
-            let (_, params) = tuple((
-                opt(take(1usize)),
+        let (_, (orientation, _, row_height, _, security_level, _, columns, _, rows, _, truncate)) =
+            (
+                opt(take_while_m_n(1, 1, |c| "NRIB".contains(c))),
                 opt(char(',')),
                 opt(parse_usize),
                 opt(char(',')),
-                opt(alpha1),
+                opt(parse_u8),
+                opt(char(',')),
+                opt(parse_u8),
+                opt(char(',')),
+                opt(parse_u8),
                 opt(char(',')),
                 opt(alpha1),
-            ))(rest)?;
-            (input, params)
-        }
-    };
+            )
+                .parse(rest)?;
 
-    let orientation = match orientation {
-        Some(o) => {
-            let result = Orientation::try_from_str(o);
-            result
-                .map(|(_, orientation)| orientation)
-                .unwrap_or(Orientation::Normal)
-        }
-        None => Orientation::Normal,
-    };
+        let orientation = orientation
+            .and_then(|o| Orientation::try_from_str(o).ok())
+            .map(|(_, orientation)| orientation)
+            .unwrap_or(Orientation::Normal);
 
-    let show_text = line
-        .map(|line| match line {
-            "N" => false,
-            _ => true,
-        })
-        .unwrap_or(true);
+        let row_height = row_height.filter(|height| *height > 0);
 
-    let text_above = line_above
-        .map(|l_above| match l_above {
-            "N" => false,
-            _ => true,
-        })
-        .unwrap_or(true);
+        let security_level = security_level.unwrap_or(0).min(8);
 
-    Ok((
-        input,
-        ZplFormatCommand::Barcode(BarcodeType::Ean13 {
-            orientation,
-            height,
-            show_text,
-            text_above,
-        }),
-    ))
-}
+        let columns = columns.filter(|columns| (1..=30).contains(columns));
 
-fn parse_bq(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, _) = tag("^BQ")(input)?;
-    Ok((input, ZplFormatCommand::Inverted))
-}
+        let rows = rows.filter(|rows| (3..=90).contains(rows));
 
-fn parse_bx(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, _) = tag("^BX")(input)?;
-    Ok((input, ZplFormatCommand::Inverted))
-}
+        let truncate = truncate.is_some_and(|truncate| truncate == "Y");
 
-fn parse_fx(input: &str) -> IResult<&str, ()> {
-    let (input, _) = tag("^FX")(input)?;
-    let (input, _) = take_till(|c| c == '\n' || c == '\r')(input)?;
-    let (input, _) = opt(line_ending).parse(input)?;
-    Ok((input, ()))
+        Ok((
+            input,
+            ZplFormatCommand::Barcode(BarcodeType::Pdf417 {
+                orientation,
+                row_height,
+                security_level,
+                columns,
+                rows,
+                truncate,
+            }),
+        ))
+    }
 }
 
-fn parse_mm(input: &str) -> IResult<&str, ()> {
-    let (input, _) = tag("^MM")(input)?;
-    let (input, (_, _, _)) = tuple((alpha1, opt(char(',')), opt(alpha1)))(input)?;
-    Ok((input, ()))
-}
+fn parse_b8<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "B8")(input)?;
+        let (input, rest) = take_parameters(prefixes)(input)?;
+        let (_, (orientation, height, show_text, text_above, _)) = parse_upc_params(rest)?;
 
-fn parse_md(input: &str) -> IResult<&str, ()> {
-    let (input, _) = tag("^MD")(input)?;
-    let (input, _) = parse_i8(input)?;
-    Ok((input, ()))
+        Ok((
+            input,
+            ZplFormatCommand::Barcode(BarcodeType::Ean8 {
+                orientation,
+                height,
+                show_text,
+                text_above,
+            }),
+        ))
+    }
 }
 
-fn parse_fh(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, _) = tag("^FH")(input)?;
-    let (input, ch) = anychar(input)?;
-    Ok((input, ZplFormatCommand::FieldHexIndicator { char: ch }))
-}
+fn parse_b9<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "B9")(input)?;
+        let (input, rest) = take_parameters(prefixes)(input)?;
+        let (_, (orientation, height, show_text, text_above, check_digit)) =
+            parse_upc_params(rest)?;
+        let check_digit = check_digit.unwrap_or(true);
 
-fn parse_ci(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, _) = tag("^CI")(input)?;
-    let (input, num) = parse_u8(input)?;
-    let mapping_parser = complete(tuple((char(','), parse_u8, char(','), parse_u8)));
-    // let (input, mapping) = many0(parse_mapping_strict).parse(input)?;
-    let (input, (mapping, _)) = many_till(mapping_parser, peek(not(char(',')))).parse(input)?;
-    let mapping = mapping.into_iter().map(|(_, x, _, y)| (x, y)).collect();
-    Ok((input, ZplFormatCommand::CharacterSet { num, mapping }))
+        Ok((
+            input,
+            ZplFormatCommand::Barcode(BarcodeType::UpcE {
+                orientation,
+                height,
+                show_text,
+                text_above,
+                check_digit,
+            }),
+        ))
+    }
 }
 
-fn parse_fb(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, _) = tag("^FB")(input)?;
-    let (input, width) = opt(parse_usize).parse(input)?;
-    let (input, lines) = opt((char(','), parse_usize)).parse(input)?;
-    let (input, line_spacing) = opt((char(','), parse_isize)).parse(input)?;
-    let (input, justification) = opt((char(','), alpha1)).parse(input)?;
-    let (input, hanging_indent) = opt((char(','), parse_usize)).parse(input)?;
+fn parse_bu<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "BU")(input)?;
+        let (input, rest) = take_parameters(prefixes)(input)?;
+        let (_, (orientation, height, show_text, text_above, check_digit)) =
+            parse_upc_params(rest)?;
+        let check_digit = check_digit.unwrap_or(true);
 
-    let width = width.unwrap_or(0);
-    let lines = lines.map(|(_, l)| l).unwrap_or(0);
-    let line_spacing = line_spacing.map(|(_, l)| l).unwrap_or(0);
-    let hanging_indent = hanging_indent.map(|(_, h)| h).unwrap_or(0);
+        Ok((
+            input,
+            ZplFormatCommand::Barcode(BarcodeType::UpcA {
+                orientation,
+                height,
+                show_text,
+                text_above,
+                check_digit,
+            }),
+        ))
+    }
+}
 
-    let justification = match justification.map(|(_, j)| j) {
-        Some(j) if j == "L" => TextBlockJustification::Left,
-        Some(j) if j == "R" => TextBlockJustification::Right,
-        Some(j) if j == "C" => TextBlockJustification::Center,
-        Some(j) if j == "J" => TextBlockJustification::Justified,
-        Some(j) => TextBlockJustification::Left,
-        None => TextBlockJustification::Left,
-    };
+fn parse_b2<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "B2")(input)?;
+        let (input, rest) = take_parameters(prefixes)(input)?;
+        let (_, (orientation, height, show_text, text_above, check_digit)) =
+            parse_upc_params(rest)?;
+        let check_digit = check_digit.unwrap_or(false);
 
-    Ok((
-        input,
-        ZplFormatCommand::FieldBlock {
-            width,
-            lines: lines,
-            line_spacing,
-            justification,
-            hanging_indent,
-        },
-    ))
+        Ok((
+            input,
+            ZplFormatCommand::Barcode(BarcodeType::Interleaved2Of5 {
+                orientation,
+                height,
+                show_text,
+                text_above,
+                check_digit,
+            }),
+        ))
+    }
 }
 
-fn parse_pq(input: &str) -> IResult<&str, ()> {
-    let (input, _) = tag("^PQ")(input)?;
-    let (input, _) = opt(parse_usize).parse(input)?;
-    let (input, _) = opt((char(','), parse_usize)).parse(input)?;
-    let (input, _) = opt((char(','), parse_usize)).parse(input)?;
-    let (input, _) = opt((char(','), alpha1)).parse(input)?;
-    let (input, _) = opt((char(','), alpha1)).parse(input)?;
-    Ok((input, ()))
-}
+fn parse_bi<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "BI")(input)?;
+        let (input, rest) = take_parameters(prefixes)(input)?;
+        let (_, (orientation, height, show_text, text_above, _)) = parse_upc_params(rest)?;
 
-fn parse_sl(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, _) = tag("^SL")(input)?;
-    let (input, mode) = alpha1(input)?;
-    let mode = mode.into();
-    let (input, language) = opt(preceded(char(','), parse_u8)).parse(input)?;
-    let language = language.into();
-    Ok((
-        input,
-        ZplFormatCommand::RealTimeClockMode { mode, language },
-    ))
+        Ok((
+            input,
+            ZplFormatCommand::Barcode(BarcodeType::Industrial2Of5 {
+                orientation,
+                height,
+                show_text,
+                text_above,
+            }),
+        ))
+    }
 }
 
-fn parse_fc(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, _) = tag("^FC")(input)?;
-    let (input, first) = opt(satisfy(|c| c != ',')).parse(input)?;
-    let first = first.unwrap_or('%');
-    let (input, second) = opt(preceded(char(','), anychar)).parse(input)?;
-    let (input, third) = opt(preceded(char(','), anychar)).parse(input)?;
-    // let third = third.map(|(_, c)| c);
-    Ok((
-        input,
-        ZplFormatCommand::RealTimeClockEscapeChar {
-            first,
-            second,
-            third,
-        },
-    ))
+fn parse_bj<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "BJ")(input)?;
+        let (input, rest) = take_parameters(prefixes)(input)?;
+        let (_, (orientation, height, show_text, text_above, _)) = parse_upc_params(rest)?;
+
+        Ok((
+            input,
+            ZplFormatCommand::Barcode(BarcodeType::Standard2Of5 {
+                orientation,
+                height,
+                show_text,
+                text_above,
+            }),
+        ))
+    }
 }
 
-fn parse_st(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, _) = tag("^ST")(input)?;
-    let (input, month) = opt(parse_u8).parse(input)?;
+fn parse_ba<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "BA")(input)?;
+        let (input, rest) = take_parameters(prefixes)(input)?;
+        let (_, (orientation, height, show_text, text_above, check_digit)) =
+            parse_upc_params(rest)?;
+        let check_digit = check_digit.unwrap_or(false);
 
-    // consume the comma, but the value after it is optional
-    let (input, day) = opt(preceded(char(','), opt(digit1))).parse(input)?;
-    let day: Option<u8> = day.flatten().and_then(|n| n.parse().ok());
+        Ok((
+            input,
+            ZplFormatCommand::Barcode(BarcodeType::Code93 {
+                orientation,
+                height,
+                show_text,
+                text_above,
+                check_digit,
+            }),
+        ))
+    }
+}
 
-    let (input, year) = opt(preceded(char(','), opt(digit1))).parse(input)?;
-    let year: Option<usize> = year.flatten().and_then(|n| n.parse().ok());
+fn parse_bk<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "BK")(input)?;
+        let (input, rest) = take_parameters(prefixes)(input)?;
+
+        let (
+            _,
+            (orientation, _, check_digit, _, height, _, line, _, line_above, _, start, _, stop),
+        ) = (
+            opt(take_while_m_n(1, 1, |c| "NRIB".contains(c))),
+            opt(char(',')),
+            opt(alpha1),
+            opt(char(',')),
+            opt(parse_usize),
+            opt(char(',')),
+            opt(alpha1),
+            opt(char(',')),
+            opt(alpha1),
+            opt(char(',')),
+            opt(one_of("ABCD")),
+            opt(char(',')),
+            opt(one_of("ABCD")),
+        )
+            .parse(rest)?;
 
-    let (input, hour) = opt(preceded(char(','), opt(digit1))).parse(input)?;
-    let hour = hour.flatten().and_then(|n| n.parse().ok());
+        let orientation = orientation
+            .and_then(|o| Orientation::try_from_str(o).ok())
+            .map(|(_, orientation)| orientation)
+            .unwrap_or(Orientation::Normal);
 
-    let (input, minute) = opt(preceded(char(','), opt(digit1))).parse(input)?;
-    let minute = minute.flatten().and_then(|n| n.parse().ok());
+        let check_digit = check_digit.is_some_and(|digit| digit == "Y");
 
-    let (input, second) = opt(preceded(char(','), opt(digit1))).parse(input)?;
-    let second = second.flatten().and_then(|n| n.parse().ok());
+        let show_text = line.map(|line| line != "N").unwrap_or(true);
 
-    let (input, format) = opt(preceded(char(','), opt(alpha1))).parse(input)?;
-    let format = match format.flatten() {
-        Some(f) if f == "A" => ClockFormat::AM,
-        Some(f) if f == "P" => ClockFormat::PM,
-        Some(_) => ClockFormat::Military,
-        None => ClockFormat::Military,
-    };
-    Ok((
-        input,
-        ZplFormatCommand::SetRealTimeClock {
-            month,
-            day,
-            year,
-            hour,
-            minute,
-            second,
-            format,
-        },
-    ))
-}
+        let text_above = line_above.is_some_and(|l_above| l_above == "Y");
 
-/// parse ^XA as start of label definition
-fn parse_xa(input: &str) -> IResult<&str, ()> {
-    let (input, _) = tag("^XA")(input)?;
-    Ok((input, ()))
+        Ok((
+            input,
+            ZplFormatCommand::Barcode(BarcodeType::Codabar {
+                orientation,
+                check_digit,
+                height,
+                show_text,
+                text_above,
+                start: start.unwrap_or('A'),
+                stop: stop.unwrap_or('A'),
+            }),
+        ))
+    }
 }
 
-/// parse ^XZ as end of label definition
-fn parse_xz(input: &str) -> IResult<&str, ()> {
-    let (input, _) = tag("^XZ")(input)?;
-    Ok((input, ()))
-}
+fn parse_b1<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "B1")(input)?;
+        let (input, rest) = take_parameters(prefixes)(input)?;
+        let (_, (orientation, check_digit, height, show_text, text_above)) =
+            parse_code39_params(rest)?;
 
-pub fn parse_command(input: &str) -> IResult<&str, ZplFormatCommand> {
-    alt((
-        parse_fo, parse_fd, parse_a, parse_fg, parse_ft, parse_ll, parse_ls, parse_pw, parse_fs,
-        parse_cf, parse_gb, parse_fr, parse_by, parse_bc, parse_be, parse_ci, parse_fh, parse_fb,
-        parse_sl, parse_fc, parse_st, // add more commands here
-    ))
-    .parse(input)
+        Ok((
+            input,
+            ZplFormatCommand::Barcode(BarcodeType::Code11 {
+                orientation,
+                single_check_digit: check_digit,
+                height,
+                show_text,
+                text_above,
+            }),
+        ))
+    }
 }
 
-/// Parse a single ZPL item
-fn parse_zpl_item(input: &str) -> IResult<&str, ZplFormatCommand> {
-    let (input, _) = multispace0(input)?; // Skip whitespace only
-    let (input, _) = opt(parse_fx).parse(input)?;
-    let (input, _) = multispace0(input)?;
-    let (input, _) = opt(parse_md).parse(input)?;
-    let (input, _) = multispace0(input)?;
-    let (input, _) = opt(parse_mm).parse(input)?;
-    let (input, _) = multispace0(input)?;
-    let (input, _) = opt(parse_pq).parse(input)?;
-    let (input, _) = multispace0(input)?;
-
-    // STOP on ^XZ (terminator)
-    if peek(parse_xz).parse(input).is_ok() {
-        return Err(nom::Err::Error(nom::error::Error::new(
+fn parse_bm<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "BM")(input)?;
+        let (input, rest) = take_parameters(prefixes)(input)?;
+
+        let (
+            _,
+            (orientation, _, check_digit, _, height, _, line, _, line_above, _, text_check_digit),
+        ) = (
+            opt(take_while_m_n(1, 1, |c| "NRIB".contains(c))),
+            opt(char(',')),
+            opt(one_of("ABCD")),
+            opt(char(',')),
+            opt(parse_usize),
+            opt(char(',')),
+            opt(alpha1),
+            opt(char(',')),
+            opt(alpha1),
+            opt(char(',')),
+            opt(alpha1),
+        )
+            .parse(rest)?;
+
+        let orientation = orientation
+            .and_then(|o| Orientation::try_from_str(o).ok())
+            .map(|(_, orientation)| orientation)
+            .unwrap_or(Orientation::Normal);
+
+        let check_digit = match check_digit {
+            Some('A') => MsiCheckDigit::None,
+            Some('C') => MsiCheckDigit::Mod10Mod10,
+            Some('D') => MsiCheckDigit::Mod11Mod10,
+            _ => MsiCheckDigit::Mod10,
+        };
+
+        let show_text = line.map(|line| line != "N").unwrap_or(true);
+
+        let text_above = line_above.is_some_and(|l_above| l_above == "Y");
+
+        let text_check_digit = text_check_digit.is_some_and(|digit| digit == "Y");
+
+        Ok((
             input,
-            ErrorKind::Eof,
-        )));
+            ZplFormatCommand::Barcode(BarcodeType::Msi {
+                orientation,
+                check_digit,
+                height,
+                show_text,
+                text_above,
+                text_check_digit,
+            }),
+        ))
     }
-
-    cut(parse_command).parse(input)
 }
 
-/// Internal parser - returns IResult
-fn parse_zpl_intern(input: &str) -> IResult<&str, Vec<ZplFormatCommand>> {
-    let (input, commands) = many1(parse_zpl_item).parse(input)?;
-    Ok((input, commands))
-}
+fn parse_bp<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "BP")(input)?;
+        let (input, rest) = take_parameters(prefixes)(input)?;
+        let (_, (orientation, check_digit, height, show_text, text_above)) =
+            parse_code39_params(rest)?;
+
+        Ok((
+            input,
+            ZplFormatCommand::Barcode(BarcodeType::Plessey {
+                orientation,
+                check_digit,
+                height,
+                show_text,
+                text_above,
+            }),
+        ))
+    }
+}
+
+/// Orientation, height, interpretation line, line above and printed check
+/// digit shared by the UPC/EAN, 2 of 5 and Code 93 barcodes
+type UpcParams = (Orientation, Option<usize>, bool, bool, Option<bool>);
+
+fn parse_upc_params(input: &str) -> IResult<&str, UpcParams> {
+    let (input, (orientation, _, height, _, line, _, line_above, _, check_digit)) = (
+        opt(take_while_m_n(1, 1, |c| "NRIB".contains(c))),
+        opt(char(',')),
+        opt(parse_usize),
+        opt(char(',')),
+        opt(alpha1),
+        opt(char(',')),
+        opt(alpha1),
+        opt(char(',')),
+        opt(alpha1),
+    )
+        .parse(input)?;
+
+    let orientation = orientation
+        .and_then(|o| Orientation::try_from_str(o).ok())
+        .map(|(_, orientation)| orientation)
+        .unwrap_or(Orientation::Normal);
+
+    let show_text = line.map(|line| line != "N").unwrap_or(true);
+
+    let text_above = line_above.is_some_and(|l_above| l_above == "Y");
+
+    let check_digit = check_digit.map(|digit| digit != "N");
+
+    Ok((
+        input,
+        (orientation, height, show_text, text_above, check_digit),
+    ))
+}
+
+fn parse_bf<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "BF")(input)?;
+        let (input, rest) = take_parameters(prefixes)(input)?;
+
+        let (_, (orientation, _, row_height, _, mode)) = (
+            opt(take_while_m_n(1, 1, |c| "NRIB".contains(c))),
+            opt(char(',')),
+            opt(parse_usize),
+            opt(char(',')),
+            opt(parse_u8),
+        )
+            .parse(rest)?;
+
+        let orientation = orientation
+            .and_then(|o| Orientation::try_from_str(o).ok())
+            .map(|(_, orientation)| orientation)
+            .unwrap_or(Orientation::Normal);
+
+        let row_height = row_height.filter(|height| *height > 0);
+
+        let mode = mode.filter(|mode| *mode <= 33).unwrap_or(0);
+
+        Ok((
+            input,
+            ZplFormatCommand::Barcode(BarcodeType::MicroPdf417 {
+                orientation,
+                row_height,
+                mode,
+            }),
+        ))
+    }
+}
+
+fn parse_bc<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "BC")(input)?;
+
+        let (input, rest) = take_parameters(prefixes)(input)?;
+
+        let (input, (orientation, _, height, _, line, _, line_above, _, check_digit, _, mode)) =
+            match rest.is_empty() {
+                true => (
+                    input,
+                    (
+                        None, None, None, None, None, None, None, None, None, None, None,
+                    ),
+                ),
+                false => {
+                    let (_, params) = (
+                        opt(take(1usize)),
+                        opt(char(',')),
+                        opt(parse_usize),
+                        opt(char(',')),
+                        opt(alpha1),
+                        opt(char(',')),
+                        opt(alpha1),
+                        opt(char(',')),
+                        opt(alpha1),
+                        opt(char(',')),
+                        opt(alpha1),
+                    )
+                        .parse(rest)?;
+                    (input, params)
+                }
+            };
+
+        let orientation = match orientation {
+            Some(o) => {
+                let result = Orientation::try_from_str(o);
+                result
+                    .map(|(_, orientation)| orientation)
+                    .unwrap_or(Orientation::Normal)
+            }
+            None => Orientation::Normal,
+        };
+
+        let show_text = line.map(|line| line != "N").unwrap_or(true);
+
+        // the interpretation line above the code and the mod 103 check digit
+        // in the text are both off by default
+        let text_above = line_above.map(|l_above| l_above == "Y").unwrap_or(false);
+
+        let check_digit = check_digit.map(|digit| digit == "Y").unwrap_or(false);
+
+        let mode = mode
+            .map(|mode| match mode {
+                "N" => Code128Mode::Normal,
+                "U" => Code128Mode::Ucc,
+                "D" => Code128Mode::Ean,
+                "A" => Code128Mode::Auto,
+                _ => Code128Mode::Normal,
+            })
+            .unwrap_or(Code128Mode::Normal);
+
+        Ok((
+            input,
+            ZplFormatCommand::Barcode(BarcodeType::Code128 {
+                orientation,
+                height,
+                show_text,
+                text_above,
+                check_digit,
+                mode,
+            }),
+        ))
+    }
+}
+
+fn parse_bs<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "BS")(input)?;
+        let (input, rest) = take_parameters(prefixes)(input)?;
+
+        let (_, (orientation, _, height, _, line, _, line_above)) = (
+            opt(take_while_m_n(1, 1, |c| "NRIB".contains(c))),
+            opt(char(',')),
+            opt(parse_usize),
+            opt(char(',')),
+            opt(alpha1),
+            opt(char(',')),
+            opt(alpha1),
+        )
+            .parse(rest)?;
+
+        let orientation = orientation
+            .and_then(|o| Orientation::try_from_str(o).ok())
+            .map(|(_, orientation)| orientation)
+            .unwrap_or(Orientation::Normal);
+
+        let show_text = line.map(|line| line != "N").unwrap_or(true);
+
+        let text_above = line_above.map(|l_above| l_above != "N").unwrap_or(true);
+
+        Ok((
+            input,
+            ZplFormatCommand::Barcode(BarcodeType::UpcEanExtension {
+                orientation,
+                height,
+                show_text,
+                text_above,
+            }),
+        ))
+    }
+}
+
+fn parse_be<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "BE")(input)?;
+        let (input, rest) = take_parameters(prefixes)(input)?;
+
+        let (input, (orientation, _, height, _, line, _, line_above)) = match rest.is_empty() {
+            true => (input, (None, None, None, None, None, None, None)),
+            false => {
+                let (_, params) = (
+                    opt(take(1usize)),
+                    opt(char(',')),
+                    opt(parse_usize),
+                    opt(char(',')),
+                    opt(alpha1),
+                    opt(char(',')),
+                    opt(alpha1),
+                )
+                    .parse(rest)?;
+                (input, params)
+            }
+        };
+
+        let orientation = match orientation {
+            Some(o) => {
+                let result = Orientation::try_from_str(o);
+                result
+                    .map(|(_, orientation)| orientation)
+                    .unwrap_or(Orientation::Normal)
+            }
+            None => Orientation::Normal,
+        };
+
+        let show_text = line.map(|line| line != "N").unwrap_or(true);
+
+        let text_above = line_above.map(|l_above| l_above != "N").unwrap_or(true);
+
+        Ok((
+            input,
+            ZplFormatCommand::Barcode(BarcodeType::Ean13 {
+                orientation,
+                height,
+                show_text,
+                text_above,
+            }),
+        ))
+    }
+}
+
+fn parse_bq<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "BQ")(input)?;
+        let (input, rest) = take_parameters(prefixes)(input)?;
+
+        let (_, (orientation, _, model, _, magnification, _, error_correction, _, mask)) = (
+            opt(take_while_m_n(1, 1, |c| "NRIB".contains(c))),
+            opt(char(',')),
+            opt(parse_u8),
+            opt(char(',')),
+            opt(parse_u8),
+            opt(char(',')),
+            opt(alpha1),
+            opt(char(',')),
+            opt(parse_u8),
+        )
+            .parse(rest)?;
+
+        let orientation = orientation
+            .and_then(|o| Orientation::try_from_str(o).ok())
+            .map(|(_, orientation)| orientation)
+            .unwrap_or(Orientation::Normal);
+
+        let model = model.filter(|model| *model == 1).unwrap_or(2);
+
+        let magnification = magnification.map(|magnification| magnification.clamp(1, 10));
+
+        let error_correction = error_correction
+            .map(|level| match level {
+                "H" => QrErrorCorrection::High,
+                "Q" => QrErrorCorrection::Quality,
+                "L" => QrErrorCorrection::Low,
+                _ => QrErrorCorrection::Medium,
+            })
+            .unwrap_or(QrErrorCorrection::Quality);
+
+        let mask = mask.filter(|mask| *mask <= 7).unwrap_or(7);
+
+        Ok((
+            input,
+            ZplFormatCommand::Barcode(BarcodeType::Qr {
+                orientation,
+                model,
+                magnification,
+                error_correction,
+                mask,
+            }),
+        ))
+    }
+}
+
+fn parse_bo<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = alt((caret(prefixes, "BO"), caret(prefixes, "B0"))).parse(input)?;
+        let (input, rest) = take_parameters(prefixes)(input)?;
+
+        let (_, (orientation, _, magnification, _, extended_channel, _, size, _, menu, _, count)) =
+            (
+                opt(take_while_m_n(1, 1, |c| "NRIB".contains(c))),
+                opt(char(',')),
+                opt(parse_u8),
+                opt(char(',')),
+                opt(alpha1),
+                opt(char(',')),
+                opt(parse_u16),
+                opt(char(',')),
+                opt(alpha1),
+                opt(char(',')),
+                opt(parse_u8),
+            )
+                .parse(rest)?;
+
+        let orientation = orientation
+            .and_then(|o| Orientation::try_from_str(o).ok())
+            .map(|(_, orientation)| orientation)
+            .unwrap_or(Orientation::Normal);
+
+        let magnification = magnification.map(|magnification| magnification.clamp(1, 10));
+
+        let extended_channel = extended_channel.is_some_and(|eci| eci == "Y");
+
+        let size = size
+            .filter(|size| matches!(size, 1..=99 | 101..=104 | 201..=232 | 300))
+            .unwrap_or(0);
+
+        let menu_symbol = menu.is_some_and(|menu| menu == "Y");
+
+        let symbol_count = count.map(|count| count.clamp(1, 26)).unwrap_or(1);
+
+        Ok((
+            input,
+            ZplFormatCommand::Barcode(BarcodeType::Aztec {
+                orientation,
+                magnification,
+                extended_channel,
+                size,
+                menu_symbol,
+                symbol_count,
+            }),
+        ))
+    }
+}
+
+fn parse_bd<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "BD")(input)?;
+        let (input, rest) = take_parameters(prefixes)(input)?;
+
+        let (_, (mode, _, symbol_number, _, symbol_count)) = (
+            opt(parse_u8),
+            opt(char(',')),
+            opt(parse_u8),
+            opt(char(',')),
+            opt(parse_u8),
+        )
+            .parse(rest)?;
+
+        let mode = mode.filter(|mode| (2..=6).contains(mode)).unwrap_or(2);
+
+        let symbol_count = symbol_count.map(|count| count.clamp(1, 8)).unwrap_or(1);
+
+        let symbol_number = symbol_number
+            .map(|number| number.clamp(1, symbol_count))
+            .unwrap_or(1);
+
+        Ok((
+            input,
+            ZplFormatCommand::Barcode(BarcodeType::MaxiCode {
+                mode,
+                symbol_number,
+                symbol_count,
+            }),
+        ))
+    }
+}
+
+fn parse_br<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "BR")(input)?;
+        let (input, rest) = take_parameters(prefixes)(input)?;
+
+        let (
+            _,
+            (
+                orientation,
+                _,
+                symbology,
+                _,
+                magnification,
+                _,
+                separator_height,
+                _,
+                height,
+                _,
+                segments,
+            ),
+        ) = (
+            opt(take_while_m_n(1, 1, |c| "NRIB".contains(c))),
+            opt(char(',')),
+            opt(parse_u8),
+            opt(char(',')),
+            opt(parse_u8),
+            opt(char(',')),
+            opt(parse_u8),
+            opt(char(',')),
+            opt(parse_usize),
+            opt(char(',')),
+            opt(parse_u8),
+        )
+            .parse(rest)?;
+
+        let orientation = orientation
+            .and_then(|o| Orientation::try_from_str(o).ok())
+            .map(|(_, orientation)| orientation)
+            .unwrap_or(Orientation::Normal);
+
+        let symbology = match symbology {
+            Some(2) => DataBarType::Truncated,
+            Some(3) => DataBarType::Stacked,
+            Some(4) => DataBarType::StackedOmnidirectional,
+            Some(5) => DataBarType::Limited,
+            Some(6) => DataBarType::Expanded,
+            Some(7) => DataBarType::UpcA,
+            Some(8) => DataBarType::UpcE,
+            Some(9) => DataBarType::Ean13,
+            Some(10) => DataBarType::Ean8,
+            Some(11) => DataBarType::Gs1128CcAb,
+            Some(12) => DataBarType::Gs1128CcC,
+            _ => DataBarType::Omnidirectional,
+        };
+
+        let magnification = magnification.map(|mag| mag.clamp(1, 10)).unwrap_or(2);
+
+        let separator_height = separator_height.map(|sep| sep.clamp(1, 2)).unwrap_or(1);
+
+        let height = height.map(|height| height.clamp(1, 32000)).unwrap_or(25);
+
+        // segments come in pairs
+        let segment_width = segments
+            .map(|segments| segments.clamp(2, 22) & !1)
+            .unwrap_or(22);
+
+        Ok((
+            input,
+            ZplFormatCommand::Barcode(BarcodeType::DataBar {
+                orientation,
+                symbology,
+                magnification,
+                separator_height,
+                height,
+                segment_width,
+            }),
+        ))
+    }
+}
+
+fn parse_bx<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "BX")(input)?;
+        // the escape character may be the tilde, only a caret ends the parameters
+        let (input, rest) = take_till(|c| c == prefixes.caret)(input)?;
+
+        let (rest, (orientation, _, element_height, _, quality, _, columns, _, rows)) = (
+            opt(take_while_m_n(1, 1, |c| "NRIB".contains(c))),
+            opt(char(',')),
+            opt(parse_usize),
+            opt(char(',')),
+            opt(parse_u16),
+            opt(char(',')),
+            opt(parse_usize),
+            opt(char(',')),
+            opt(parse_usize),
+        )
+            .parse(rest)?;
+        let (_, (_, format_id, _, escape_char, _, aspect_ratio)) = (
+            opt(char(',')),
+            opt(parse_u8),
+            opt(char(',')),
+            opt(none_of(",")),
+            opt(char(',')),
+            opt(one_of("12")),
+        )
+            .parse(rest)?;
+
+        let orientation = orientation
+            .and_then(|o| Orientation::try_from_str(o).ok())
+            .map(|(_, orientation)| orientation)
+            .unwrap_or(Orientation::Normal);
+
+        let element_height = element_height.filter(|height| *height > 0);
+
+        let quality = quality
+            .filter(|quality| [0, 50, 80, 100, 140, 200].contains(quality))
+            .unwrap_or(0);
+
+        let columns = columns.filter(|columns| *columns > 0);
+        let rows = rows.filter(|rows| *rows > 0);
+
+        let format_id = format_id.filter(|id| (1..=6).contains(id)).unwrap_or(6);
+
+        let escape_char = escape_char.unwrap_or('~');
+
+        let rectangular = aspect_ratio == Some('2');
+
+        Ok((
+            input,
+            ZplFormatCommand::Barcode(BarcodeType::DataMatrix {
+                orientation,
+                element_height,
+                quality,
+                columns,
+                rows,
+                format_id,
+                escape_char,
+                rectangular,
+            }),
+        ))
+    }
+}
+
+fn parse_fx<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ()> {
+    move |input| {
+        let (input, _) = caret(prefixes, "FX")(input)?;
+        let (input, _) = take_till(|c| c == '\n' || c == '\r')(input)?;
+        let (input, _) = opt(line_ending).parse(input)?;
+        Ok((input, ()))
+    }
+}
+
+fn parse_mm<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ()> {
+    move |input| {
+        let (input, _) = caret(prefixes, "MM")(input)?;
+        let (input, (_, _, _)) = (alpha1, opt(char(',')), opt(alpha1)).parse(input)?;
+        Ok((input, ()))
+    }
+}
+
+fn parse_md<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ()> {
+    move |input| {
+        let (input, _) = caret(prefixes, "MD")(input)?;
+        let (input, _) = parse_i8(input)?;
+        Ok((input, ()))
+    }
+}
+
+fn parse_fh<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "FH")(input)?;
+        // the indicator defaults to an underscore
+        let (input, ch) = opt(satisfy(|c| !prefixes.is_prefix(c))).parse(input)?;
+        let ch = ch.unwrap_or('_');
+        Ok((input, ZplFormatCommand::FieldHexIndicator { char: ch }))
+    }
+}
+
+fn parse_ci<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "CI")(input)?;
+        let (input, num) = parse_u8(input)?;
+        let mapping_parser = complete((char(','), parse_u8, char(','), parse_u8));
+        // let (input, mapping) = many0(parse_mapping_strict).parse(input)?;
+        let (input, (mapping, _)) = many_till(mapping_parser, peek(not(char(',')))).parse(input)?;
+        let mapping = mapping.into_iter().map(|(_, x, _, y)| (x, y)).collect();
+        Ok((input, ZplFormatCommand::CharacterSet { num, mapping }))
+    }
+}
+
+fn parse_fb<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "FB")(input)?;
+        let (input, width) = opt(parse_usize).parse(input)?;
+        let (input, lines) = opt((char(','), parse_usize)).parse(input)?;
+        let (input, line_spacing) = opt((char(','), parse_isize)).parse(input)?;
+        let (input, justification) = opt((char(','), alpha1)).parse(input)?;
+        let (input, hanging_indent) = opt((char(','), parse_usize)).parse(input)?;
+
+        let width = width.unwrap_or(0);
+        let lines = lines.map(|(_, l)| l).unwrap_or(0);
+        let line_spacing = line_spacing.map(|(_, l)| l).unwrap_or(0);
+        let hanging_indent = hanging_indent.map(|(_, h)| h).unwrap_or(0);
+
+        let justification = match justification.map(|(_, j)| j) {
+            Some("L") => TextBlockJustification::Left,
+            Some("R") => TextBlockJustification::Right,
+            Some("C") => TextBlockJustification::Center,
+            Some("J") => TextBlockJustification::Justified,
+            Some(_) => TextBlockJustification::Left,
+            None => TextBlockJustification::Left,
+        };
+
+        Ok((
+            input,
+            ZplFormatCommand::FieldBlock {
+                width,
+                lines,
+                line_spacing,
+                justification,
+                hanging_indent,
+            },
+        ))
+    }
+}
+
+fn parse_pq<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ()> {
+    move |input| {
+        let (input, _) = caret(prefixes, "PQ")(input)?;
+        let (input, _) = opt(parse_usize).parse(input)?;
+        let (input, _) = opt((char(','), parse_usize)).parse(input)?;
+        let (input, _) = opt((char(','), parse_usize)).parse(input)?;
+        let (input, _) = opt((char(','), alpha1)).parse(input)?;
+        let (input, _) = opt((char(','), alpha1)).parse(input)?;
+        Ok((input, ()))
+    }
+}
+
+fn parse_sl<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "SL")(input)?;
+        let (input, mode) = alpha1(input)?;
+        let mode = mode.into();
+        let (input, language) = opt(preceded(char(','), parse_u8)).parse(input)?;
+        let language = language.into();
+        Ok((
+            input,
+            ZplFormatCommand::RealTimeClockMode { mode, language },
+        ))
+    }
+}
+
+fn parse_fc<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "FC")(input)?;
+        let (input, first) = opt(satisfy(|c| c != ',')).parse(input)?;
+        let first = first.unwrap_or('%');
+        let (input, second) = opt(preceded(char(','), anychar)).parse(input)?;
+        let (input, third) = opt(preceded(char(','), anychar)).parse(input)?;
+        // let third = third.map(|(_, c)| c);
+        Ok((
+            input,
+            ZplFormatCommand::RealTimeClockEscapeChar {
+                first,
+                second,
+                third,
+            },
+        ))
+    }
+}
+
+fn parse_st<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "ST")(input)?;
+        let (input, month) = opt(parse_u8).parse(input)?;
+
+        // consume the comma, but the value after it is optional
+        let (input, day) = opt(preceded(char(','), opt(digit1))).parse(input)?;
+        let day: Option<u8> = day.flatten().and_then(|n| n.parse().ok());
+
+        let (input, year) = opt(preceded(char(','), opt(digit1))).parse(input)?;
+        let year: Option<usize> = year.flatten().and_then(|n| n.parse().ok());
+
+        let (input, hour) = opt(preceded(char(','), opt(digit1))).parse(input)?;
+        let hour = hour.flatten().and_then(|n| n.parse().ok());
+
+        let (input, minute) = opt(preceded(char(','), opt(digit1))).parse(input)?;
+        let minute = minute.flatten().and_then(|n| n.parse().ok());
+
+        let (input, second) = opt(preceded(char(','), opt(digit1))).parse(input)?;
+        let second = second.flatten().and_then(|n| n.parse().ok());
+
+        let (input, format) = opt(preceded(char(','), opt(alpha1))).parse(input)?;
+        let format = match format.flatten() {
+            Some("A") => ClockFormat::AM,
+            Some("P") => ClockFormat::PM,
+            Some(_) => ClockFormat::Military,
+            None => ClockFormat::Military,
+        };
+        Ok((
+            input,
+            ZplFormatCommand::SetRealTimeClock {
+                month,
+                day,
+                year,
+                hour,
+                minute,
+                second,
+                format,
+            },
+        ))
+    }
+}
+
+/// name of a stored object like `R:LABEL.ZPL`
+fn parse_object_name<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, String> {
+    move |input| {
+        let (_, name) = take_till(|c| prefixes.is_prefix(c))(input)?;
+        let name = name.trim_end();
+        if name.is_empty() {
+            return Err(nom::Err::Error(Error::new(input, ErrorKind::TakeTill1)));
+        }
+        Ok((&input[name.len()..], name.to_string()))
+    }
+}
+
+fn parse_df<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "DF")(input)?;
+        let (input, name) = parse_object_name(prefixes)(input)?;
+        Ok((input, ZplFormatCommand::StoreFormat { name }))
+    }
+}
+
+fn parse_xf<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "XF")(input)?;
+        let (input, name) = parse_object_name(prefixes)(input)?;
+        Ok((input, ZplFormatCommand::RecallFormat { name }))
+    }
+}
+
+fn parse_fn<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        let (input, _) = caret(prefixes, "FN")(input)?;
+        let (input, number) = parse_u16(input)?;
+        // the optional prompt is only used by keyboard display units
+        let (input, _) = opt((char('"'), take_till(|c| c == '"'), char('"'))).parse(input)?;
+        Ok((input, ZplFormatCommand::FieldNumber(number)))
+    }
+}
+
+/// Take `count` bytes, `take` counts chars on `&str` input
+fn take_bytes<'a>(count: usize) -> impl Fn(&'a str) -> IResult<&'a str, &'a str> {
+    move |input: &'a str| match input.is_char_boundary(count) {
+        true => Ok((&input[count..], &input[..count])),
+        false => Err(nom::Err::Error(Error::new(input, ErrorKind::Eof))),
+    }
+}
+
+/// parse the data of a download command, either `:Z64:` compressed
+/// (with optional CRC) or plain ASCII hex up to the next command
+fn parse_download_data<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, GraficData> {
+    move |input| {
+        let compressed: IResult<&str, &str> = preceded(
+            tag(":Z64:"),
+            take_till(|c| c == ':' || prefixes.is_prefix(c)),
+        )
+        .parse(input);
+        if let Ok((input, data)) = compressed {
+            let (input, _) = opt((char(':'), alphanumeric1)).parse(input)?;
+            let data = GraficData {
+                compression_method: CompressionMethod::Zlib,
+                data: data.into(),
+            };
+            return Ok((input, data));
+        }
+
+        let (input, data) = take_till(|c| prefixes.is_prefix(c))(input)?;
+        let data = GraficData {
+            compression_method: CompressionMethod::None,
+            data: data.chars().filter(|c| !c.is_whitespace()).collect(),
+        };
+        Ok((input, data))
+    }
+}
+
+fn parse_dg<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplHostCommand> {
+    move |input| {
+        let (input, _) = tilde(prefixes, "DG")(input)?;
+        let (input, (name, _, total_bytes, _, row_bytes, _)) = (
+            take_till(|c| c == ','),
+            char(','),
+            parse_usize,
+            char(','),
+            parse_usize,
+            char(','),
+        )
+            .parse(input)?;
+        let (input, data) = parse_download_data(prefixes)(input)?;
+
+        Ok((
+            input,
+            ZplHostCommand::DownloadGraphics {
+                name: name.to_string(),
+                total_bytes,
+                row_bytes,
+                data,
+            },
+        ))
+    }
+}
+
+fn parse_dy<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplHostCommand> {
+    move |input| {
+        let (input, _) = tilde(prefixes, "DY")(input)?;
+        let (input, (name, _, format, _, extension, _, total_bytes, _, row_bytes, _)) = (
+            take_till(|c| c == ','),
+            char(','),
+            alpha1,
+            char(','),
+            alphanumeric1,
+            char(','),
+            parse_usize,
+            char(','),
+            opt(parse_usize),
+            char(','),
+        )
+            .parse(input)?;
+
+        let format = match format {
+            "A" => DownloadFormat::Ascii,
+            "B" => DownloadFormat::Binary,
+            "C" => DownloadFormat::Compressed,
+            "P" => DownloadFormat::Png,
+            _ => return IResult::Err(nom::Err::Error(Error::new(input, ErrorKind::NoneOf))),
+        };
+
+        // binary payloads may contain command prefixes, take them by size
+        let (input, data) = match format {
+            DownloadFormat::Ascii => parse_download_data(prefixes)(input)?,
+            _ => {
+                let (input, data) = take_bytes(total_bytes)(input)?;
+                let data = GraficData {
+                    compression_method: CompressionMethod::None,
+                    data: data.into(),
+                };
+                (input, data)
+            }
+        };
+
+        Ok((
+            input,
+            ZplHostCommand::DownloadObject {
+                name: name.to_string(),
+                format,
+                extension: extension.to_string(),
+                total_bytes,
+                row_bytes,
+                data,
+            },
+        ))
+    }
+}
+
+fn parse_du<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplHostCommand> {
+    move |input| {
+        let (input, _) = tilde(prefixes, "DU")(input)?;
+        let (input, (name, _, size, _)) =
+            (take_till(|c| c == ','), char(','), parse_usize, char(',')).parse(input)?;
+        let (input, data) = take_till(|c| prefixes.is_prefix(c))(input)?;
+
+        Ok((
+            input,
+            ZplHostCommand::DownloadUnboundedFont {
+                name: name.to_string(),
+                size,
+                data: data.chars().filter(|c| !c.is_whitespace()).collect(),
+            },
+        ))
+    }
+}
+
+fn parse_ja<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplHostCommand> {
+    move |input| {
+        let (input, _) = tilde(prefixes, "JA")(input)?;
+        Ok((input, ZplHostCommand::CancelAllCommands))
+    }
+}
+
+fn parse_jc<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplHostCommand> {
+    move |input| {
+        let (input, _) = tilde(prefixes, "JC")(input)?;
+        Ok((input, ZplHostCommand::CancelCurrentCommand))
+    }
+}
+
+fn parse_hs<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplHostCommand> {
+    move |input| {
+        let (input, _) = tilde(prefixes, "HS")(input)?;
+        Ok((input, ZplHostCommand::PrintHostStatus))
+    }
+}
+
+fn parse_hi<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplHostCommand> {
+    move |input| {
+        let (input, _) = tilde(prefixes, "HI")(input)?;
+        Ok((input, ZplHostCommand::HostIdentification))
+    }
+}
+
+fn parse_sd<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplHostCommand> {
+    move |input| {
+        let (input, _) = tilde(prefixes, "SD")(input)?;
+        let (input, darkness) = parse_u8(input)?;
+        Ok((input, ZplHostCommand::SetDarkness(darkness)))
+    }
+}
+
+fn parse_ta<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplHostCommand> {
+    move |input| {
+        let (input, _) = tilde(prefixes, "TA")(input)?;
+        let (input, adjust) = parse_isize(input)?;
+        Ok((input, ZplHostCommand::TearOffAdjust(adjust)))
+    }
+}
+
+fn parse_js<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplHostCommand> {
+    move |input| {
+        let (input, _) = tilde(prefixes, "JS")(input)?;
+        let (input, sequence) = alphanumeric1(input)?;
+        Ok((input, ZplHostCommand::BackfeedSequence(sequence.into())))
+    }
+}
+
+/// parse ^XA as start of label definition
+fn parse_xa<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ()> {
+    move |input| {
+        let (input, _) = caret(prefixes, "XA")(input)?;
+        Ok((input, ()))
+    }
+}
+
+/// parse ^XZ as end of label definition
+fn parse_xz<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ()> {
+    move |input| {
+        let (input, _) = caret(prefixes, "XZ")(input)?;
+        Ok((input, ()))
+    }
+}
+
+pub fn parse_command<'a>(
+    prefixes: Prefixes,
+) -> impl Fn(&'a str) -> IResult<&'a str, ZplFormatCommand> {
+    move |input| {
+        alt((
+            alt((
+                parse_fo(prefixes),
+                parse_fd(prefixes),
+                parse_a(prefixes),
+                parse_fg(prefixes),
+                parse_ft(prefixes),
+                parse_ll(prefixes),
+                parse_ls(prefixes),
+                parse_pw(prefixes),
+                parse_fs(prefixes),
+                parse_cf(prefixes),
+                parse_gb(prefixes),
+                parse_fr(prefixes),
+                parse_by(prefixes),
+                parse_bc(prefixes),
+                parse_be(prefixes),
+                parse_ci(prefixes),
+                parse_fh(prefixes),
+                parse_fb(prefixes),
+                parse_sl(prefixes),
+                parse_fc(prefixes),
+                parse_st(prefixes),
+            )),
+            alt((
+                parse_df(prefixes),
+                parse_xf(prefixes),
+                parse_fn(prefixes),
+                parse_lh(prefixes),
+                parse_lt(prefixes),
+                parse_po(prefixes),
+                parse_lr(prefixes),
+                parse_gc(prefixes),
+                parse_ge(prefixes),
+                parse_gd(prefixes),
+                parse_fw(prefixes),
+                parse_jm(prefixes),
+                // add more commands here
+            )),
+            alt((
+                parse_b3(prefixes),
+                parse_bq(prefixes),
+                parse_bx(prefixes),
+                parse_b7(prefixes),
+                parse_bf(prefixes),
+                parse_b8(prefixes),
+                parse_b9(prefixes),
+                parse_bu(prefixes),
+                parse_bs(prefixes),
+                parse_b2(prefixes),
+                parse_bi(prefixes),
+                parse_bj(prefixes),
+                parse_ba(prefixes),
+                parse_bk(prefixes),
+                parse_b1(prefixes),
+                parse_bm(prefixes),
+                parse_bp(prefixes),
+                parse_bo(prefixes),
+                parse_bd(prefixes),
+                parse_br(prefixes),
+                // add more barcodes here
+            )),
+        ))
+        .parse(input)
+    }
+}
+
+pub fn parse_host_command<'a>(
+    prefixes: Prefixes,
+) -> impl Fn(&'a str) -> IResult<&'a str, ZplHostCommand> {
+    move |input| {
+        alt((
+            parse_dg(prefixes),
+            parse_dy(prefixes),
+            parse_du(prefixes),
+            parse_ja(prefixes),
+            parse_jc(prefixes),
+            parse_hs(prefixes),
+            parse_hi(prefixes),
+            parse_sd(prefixes),
+            parse_ta(prefixes),
+            parse_js(prefixes), // add more host commands here
+        ))
+        .parse(input)
+    }
+}
+
+/// parse a format or host command
+fn parse_any_command<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ZplCommand> {
+    move |input| {
+        alt((
+            map(parse_command(prefixes), ZplCommand::Format),
+            map(parse_host_command(prefixes), ZplCommand::Host),
+        ))
+        .parse(input)
+    }
+}
+
+/// Command codes understood by `parse_command` or skipped as ignored
+const KNOWN_COMMANDS: &[&str] = &[
+    "FO", "FD", "A", "GF", "FT", "LL", "LS", "PW", "FS", "CF", "GB", "FR", "BY", "BC", "BE", "CI",
+    "FH", "FB", "SL", "FC", "ST", "FX", "MD", "MM", "PQ", "DG", "DY", "DU", "JA", "JC", "HS", "HI",
+    "SD", "TA", "JS", "CC", "CT", "DF", "XF", "FN", "LH", "LT", "PO", "LR", "GC", "GE", "GD", "FW",
+    "JM", "B3", "BQ", "BX", "B7", "BF", "B8", "B9", "BU", "BS", "B2", "BI", "BJ", "BA", "BK", "B1",
+    "BM", "BP", "BO", "B0", "BD", "BR",
+];
+
+/// Split any command into prefix, command code and the raw parameters up
+/// to the next command prefix. The prefix is reported as the default ^ or ~.
+fn parse_unknown<'a>(
+    prefixes: Prefixes,
+) -> impl Fn(&'a str) -> IResult<&'a str, (char, &'a str, &'a str)> {
+    move |input| {
+        let (input, prefix) = satisfy(|c| prefixes.is_prefix(c))(input)?;
+        let prefix = match prefix == prefixes.caret {
+            true => '^',
+            false => '~',
+        };
+        let (input, code) = alt((
+            tag("A"),
+            take_while_m_n(2, 2, |c: char| c.is_ascii_alphanumeric()),
+        ))
+        .parse(input)?;
+        let (_, params) = take_till(|c| prefixes.is_prefix(c))(input)?;
+
+        // trailing whitespace separates commands and is not part of the parameters
+        let params = params.trim_end();
+        let input = &input[params.len()..];
+
+        Ok((input, (prefix, code, params)))
+    }
+}
+
+/// Skip whitespace, comments and commands without effect on the label
+fn skip_ignored<'a>(prefixes: Prefixes) -> impl Fn(&'a str) -> IResult<&'a str, ()> {
+    move |input| {
+        let (input, _) = many0(preceded(
+            multispace0,
+            alt((
+                parse_fx(prefixes),
+                parse_md(prefixes),
+                parse_mm(prefixes),
+                parse_pq(prefixes),
+            )),
+        ))
+        .parse(input)?;
+        let (input, _) = multispace0(input)?;
+        Ok((input, ()))
+    }
+}
+
+/// Parse a single ZPL item, together with the input it was parsed from
+fn parse_zpl_item<'a>(
+    prefixes: Prefixes,
+) -> impl Fn(&'a str) -> IResult<&'a str, (&'a str, ZplCommand)> {
+    move |input| {
+        let (input, _) = skip_ignored(prefixes)(input)?;
+
+        // STOP on ^XZ (terminator)
+        if peek(parse_xz(prefixes)).parse(input).is_ok() {
+            return Err(nom::Err::Error(nom::error::Error::new(
+                input,
+                ErrorKind::Eof,
+            )));
+        }
+
+        let prefix_change = map(parse_prefix_change(prefixes), |prefixes| {
+            ZplCommand::Format(prefixes.command())
+        });
+        consumed(alt((prefix_change, cut(parse_any_command(prefixes))))).parse(input)
+    }
+}
+
+/// Internal parser - returns IResult, follows prefix changes on the way
+fn parse_zpl_intern(
+    mut prefixes: Prefixes,
+    mut input: &str,
+) -> IResult<&str, Vec<(&str, ZplCommand)>> {
+    let mut commands = Vec::new();
+    loop {
+        match parse_zpl_item(prefixes)(input) {
+            Ok((rest, (consumed, command))) => {
+                if let ZplCommand::Format(ZplFormatCommand::ChangePrefix { caret, tilde }) = command
+                {
+                    prefixes = Prefixes { caret, tilde };
+                }
+                commands.push((consumed, command));
+                input = rest;
+            }
+            Err(nom::Err::Error(_)) if !commands.is_empty() => break,
+            Err(err) => return Err(err),
+        }
+    }
+    Ok((input, commands))
+}
+
+/// A label (^XA ... ^XZ) and the prefixes active at its start
+struct LabelInput<'a> {
+    input: &'a str,
+    prefixes: Prefixes,
+    /// false if the input ended before ^XZ
+    terminated: bool,
+}
+
+/// Scan to the end of a label, following prefix changes on the way.
+/// Returns the input after ^XZ and the prefixes active there.
+fn find_label_end(mut prefixes: Prefixes, input: &str) -> Option<(&str, Prefixes)> {
+    let mut rest = input;
+    while let Some(pos) = rest.find(|c| prefixes.is_prefix(c)) {
+        rest = &rest[pos..];
+        if let Ok((remain, _)) = parse_xz(prefixes)(rest) {
+            return Some((remain, prefixes));
+        }
+        match parse_prefix_change(prefixes)(rest) {
+            Ok((remain, changed)) => (rest, prefixes) = (remain, changed),
+            Err(_) => rest = &rest[rest.chars().next().map_or(1, char::len_utf8)..],
+        }
+    }
+    None
+}
+
+fn missing_xz(index: &LineIndex, label: &str) -> ParseError {
+    let error = ParseError {
+        kind: ParseErrorKind::MissingCommand,
+        message: "^XZ".to_string(),
+        location: None,
+    };
+    error.with_location(index.locate(label))
+}
 
 // extract all parts starting with ^XA and ending with ^XZ
-fn find_labels(input: &str) -> Vec<Result<&str, ParseError>> {
+fn find_labels(input: &str) -> Vec<LabelInput<'_>> {
     let mut labels = Vec::new();
     let mut rest = input;
+    let mut prefixes = Prefixes::default();
+
+    while let Some(pos) = rest.find(|c| prefixes.is_prefix(c)) {
+        rest = &rest[pos..];
+        if let Ok((remain, changed)) = parse_prefix_change(prefixes)(rest) {
+            (rest, prefixes) = (remain, changed);
+        } else if let Ok((after_xa, _)) = parse_xa(prefixes)(rest) {
+            let Some((remain, changed)) = find_label_end(prefixes, after_xa) else {
+                labels.push(LabelInput {
+                    input: rest,
+                    prefixes,
+                    terminated: false,
+                });
+                break;
+            };
+            let input = &rest[..rest.len() - remain.len()];
+            labels.push(LabelInput {
+                input,
+                prefixes,
+                terminated: true,
+            });
+            (rest, prefixes) = (remain, changed);
+        } else {
+            rest = &rest[rest.chars().next().map_or(1, char::len_utf8)..];
+        }
+    }
+
+    labels
+}
+
+fn missing_xa() -> ParseError {
+    ParseError {
+        kind: ParseErrorKind::MissingCommand,
+        message: "^XA".to_string(),
+        location: None,
+    }
+}
+
+/// convert a nom error into a ParseError pointing into the source
+fn located_error(index: &LineIndex, err: nom::Err<nom::error::Error<&str>>) -> ParseError {
+    let location = match &err {
+        nom::Err::Error(e) | nom::Err::Failure(e) => index.locate(e.input),
+        nom::Err::Incomplete(_) => None,
+    };
+    ParseError::from(err).with_location(location)
+}
+
+/// Prefixes changed before the label, as command on its ^XA. Keeps the
+/// label self-contained, commands are serialized with default prefixes.
+fn initial_prefixes(
+    label: &LabelInput,
+    xa: &str,
+    index: &LineIndex,
+) -> Option<Spanned<ZplFormatCommand>> {
+    (label.prefixes != Prefixes::default()).then(|| Spanned {
+        node: label.prefixes.command(),
+        span: index.span(xa).unwrap_or_default(),
+    })
+}
+
+/// parse a single label starting with ^XA and ending with ^XZ,
+/// host commands inside the label are dropped
+fn parse_label(
+    label: &LabelInput,
+    index: &LineIndex,
+) -> Result<Vec<Spanned<ZplFormatCommand>>, ParseError> {
+    if !label.terminated {
+        return Err(missing_xz(index, label.input));
+    }
+
+    // strip ^XA
+    let (input, _) =
+        parse_xa(label.prefixes)(label.input).map_err(|err| located_error(index, err))?;
+    let xa = &label.input[..label.input.len() - input.len()];
+
+    // parse content
+    let (_, commands) =
+        parse_zpl_intern(label.prefixes, input).map_err(|err| located_error(index, err))?;
+
+    let commands = initial_prefixes(label, xa, index)
+        .into_iter()
+        .chain(
+            commands
+                .into_iter()
+                .filter_map(|(consumed, command)| match command {
+                    ZplCommand::Format(node) => Some(Spanned {
+                        node,
+                        span: index.span(consumed).unwrap_or_default(),
+                    }),
+                    _ => None,
+                }),
+        )
+        .collect();
+
+    Ok(commands)
+}
+
+fn strip_spans(commands: Vec<Spanned<ZplFormatCommand>>) -> Vec<ZplFormatCommand> {
+    commands.into_iter().map(|command| command.node).collect()
+}
+
+/// Parse the last label (^XA ... ^XZ) found in `input`.
+pub fn parse_zpl(input: &str) -> Result<Vec<ZplFormatCommand>, ParseError> {
+    parse_zpl_spanned(input).map(strip_spans)
+}
+
+/// Like [`parse_zpl`], but every command carries the span of `input` it
+/// was parsed from.
+pub fn parse_zpl_spanned(input: &str) -> Result<Vec<Spanned<ZplFormatCommand>>, ParseError> {
+    let index = LineIndex::new(input);
+
+    // extract labels
+    let labels = find_labels(input);
+    let label = labels.last().ok_or_else(missing_xa)?;
+
+    parse_label(label, &index)
+}
+
+/// Parse every label (^XA ... ^XZ) found in `input`, in stream order.
+///
+/// Returns one command list per label. Fails on the first label that
+/// cannot be parsed.
+pub fn parse_zpl_labels(input: &str) -> Result<Vec<Vec<ZplFormatCommand>>, ParseError> {
+    let labels = parse_zpl_labels_spanned(input)?;
+    Ok(labels.into_iter().map(strip_spans).collect())
+}
+
+/// Like [`parse_zpl_labels`], but every command carries the span of
+/// `input` it was parsed from. Spans are relative to the whole `input`.
+pub fn parse_zpl_labels_spanned(
+    input: &str,
+) -> Result<Vec<Vec<Spanned<ZplFormatCommand>>>, ParseError> {
+    let index = LineIndex::new(input);
+    let labels = find_labels(input);
+    if labels.is_empty() {
+        return Err(missing_xa());
+    }
+
+    labels
+        .iter()
+        .map(|label| parse_label(label, &index))
+        .collect()
+}
+
+/// Parse a whole ZPL stream into format and host commands, in stream order.
+///
+/// Labels are delimited by [`ZplCommand::StartFormat`] and
+/// [`ZplCommand::EndFormat`]. Host commands may appear between labels as
+/// well as inside of them, as may [`ZplFormatCommand::ChangePrefix`].
+pub fn parse_zpl_stream(input: &str) -> Result<Vec<ZplCommand>, ParseError> {
+    let index = LineIndex::new(input);
+    let mut commands = Vec::new();
+    let mut rest = input;
+    let mut prefixes = Prefixes::default();
+
+    loop {
+        (rest, _) =
+            multispace0::<_, Error<&str>>(rest).map_err(|err| located_error(&index, err))?;
+        if rest.is_empty() {
+            break;
+        }
+
+        if let Ok((remain, changed)) = parse_prefix_change(prefixes)(rest) {
+            commands.push(ZplCommand::Format(changed.command()));
+            (rest, prefixes) = (remain, changed);
+            continue;
+        }
+
+        if let Ok((remain, host)) = parse_host_command(prefixes)(rest) {
+            commands.push(ZplCommand::Host(host));
+            rest = remain;
+            continue;
+        }
+
+        let (label_input, _) =
+            parse_xa(prefixes)(rest).map_err(|err| located_error(&index, err))?;
+        let Some((remain, changed)) = find_label_end(prefixes, label_input) else {
+            return Err(missing_xz(&index, rest));
+        };
+
+        let label_input = &label_input[..label_input.len() - remain.len()];
+        let (_, items) =
+            parse_zpl_intern(prefixes, label_input).map_err(|err| located_error(&index, err))?;
+        commands.push(ZplCommand::StartFormat);
+        commands.extend(items.into_iter().map(|(_, command)| command));
+        commands.push(ZplCommand::EndFormat);
+        (rest, prefixes) = (remain, changed);
+    }
+
+    Ok(commands)
+}
+
+/// Result of [`parse_zpl_lenient`]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LenientParse {
+    /// one command list per label, in stream order
+    pub labels: Vec<Vec<Spanned<ZplFormatCommand>>>,
+    /// everything the parser had to skip or guess
+    pub warnings: Vec<ParseWarning>,
+}
+
+/// parse a single label, turning everything unparsable into `Unknown`
+fn parse_label_lenient(
+    label: &LabelInput,
+    index: &LineIndex,
+    warnings: &mut Vec<ParseWarning>,
+) -> Vec<Spanned<ZplFormatCommand>> {
+    let mut prefixes = label.prefixes;
+    let spanned = |(consumed, node): (&str, ZplFormatCommand)| Spanned {
+        node,
+        span: index.span(consumed).unwrap_or_default(),
+    };
+
+    // strip ^XA
+    let mut input = parse_xa(prefixes)(label.input).map_or(label.input, |(input, _)| input);
+    let xa = &label.input[..label.input.len() - input.len()];
+    let mut commands: Vec<_> = initial_prefixes(label, xa, index).into_iter().collect();
+
+    loop {
+        input = skip_ignored(prefixes)(input).map_or(input, |(input, _)| input);
+
+        // STOP on ^XZ (terminator)
+        if input.is_empty() || peek(parse_xz(prefixes)).parse(input).is_ok() {
+            break;
+        }
+
+        if let Ok((rest, (consumed, changed))) =
+            consumed(parse_prefix_change(prefixes)).parse(input)
+        {
+            commands.push(spanned((consumed, changed.command())));
+            (input, prefixes) = (rest, changed);
+        } else if let Ok((rest, command)) = consumed(parse_command(prefixes)).parse(input) {
+            commands.push(spanned(command));
+            input = rest;
+        } else if let Ok((rest, _)) = parse_host_command(prefixes)(input) {
+            // valid, but not part of the format
+            input = rest;
+        } else if let Ok((rest, (prefix, code, params))) = parse_unknown(prefixes)(input) {
+            let consumed = &input[..input.len() - rest.len()];
+            let (kind, message) = match KNOWN_COMMANDS.contains(&code) {
+                true => (
+                    ParseWarningKind::MalformedCommand,
+                    consumed.chars().take(10).collect(),
+                ),
+                false => (ParseWarningKind::UnknownCommand, format!("{prefix}{code}")),
+            };
+            warnings.push(ParseWarning {
+                kind,
+                message,
+                location: index.locate(input),
+            });
+            let unknown = ZplFormatCommand::Unknown {
+                code: format!("{prefix}{code}"),
+                params: params.to_string(),
+            };
+            commands.push(spanned((consumed, unknown)));
+            input = rest;
+        } else {
+            // no command at all, skip ahead to the next command prefix
+            let skip = input
+                .char_indices()
+                .skip(1)
+                .find(|(_, c)| prefixes.is_prefix(*c))
+                .map_or(input.len(), |(idx, _)| idx);
+            warnings.push(ParseWarning {
+                kind: ParseWarningKind::UnexpectedInput,
+                message: input.chars().take(10).collect(),
+                location: index.locate(input),
+            });
+            input = &input[skip..];
+        }
+    }
+
+    commands
+}
+
+/// Parse every label (^XA ... ^XZ) found in `input` without failing.
+///
+/// Commands that are not supported or cannot be parsed become
+/// [`ZplFormatCommand::Unknown`] and are reported as warnings, like a
+/// printer ignoring commands it does not know.
+pub fn parse_zpl_lenient(input: &str) -> LenientParse {
+    let index = LineIndex::new(input);
+    let mut result = LenientParse::default();
+
+    for label in find_labels(input) {
+        if !label.terminated {
+            // missing ^XZ, parse up to the end of the input
+            let err = missing_xz(&index, label.input);
+            result.warnings.push(ParseWarning {
+                kind: ParseWarningKind::MissingCommand,
+                message: err.message,
+                location: err.location,
+            });
+        }
+        let commands = parse_label_lenient(&label, &index, &mut result.warnings);
+        result.labels.push(commands);
+    }
+
+    if result.labels.is_empty() {
+        let err = missing_xa();
+        result.warnings.push(ParseWarning {
+            kind: ParseWarningKind::MissingCommand,
+            message: err.message,
+            location: err.location,
+        });
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        BarcodeType, ClockFormat, ClockLanguage, ClockMode, Code128Mode, Color, DataBarType,
+        Justification, Location, MsiCheckDigit, ParseError, ParseErrorKind, ParseWarning,
+        ParseWarningKind, QrErrorCorrection, TextBlockJustification,
+        commands::{
+            BackfeedSequence, CompressionMethod, CompressionType, DiagonalOrientation,
+            DownloadFormat, GraficData, Orientation, ZplCommand, ZplFormatCommand, ZplHostCommand,
+        },
+        parse::{
+            Prefixes, parse_a, parse_b1, parse_b2, parse_b3, parse_b7, parse_b8, parse_b9,
+            parse_ba, parse_bc, parse_bd, parse_be, parse_bf, parse_bi, parse_bj, parse_bk,
+            parse_bm, parse_bo, parse_bp, parse_bq, parse_br, parse_bs, parse_bu, parse_bx,
+            parse_by, parse_cf, parse_ci, parse_df, parse_fb, parse_fc, parse_fd, parse_fg,
+            parse_fh, parse_fn, parse_fo, parse_fr, parse_ft, parse_fw, parse_fx, parse_gb,
+            parse_gc, parse_gd, parse_ge, parse_host_command, parse_jm, parse_lh, parse_ll,
+            parse_lr, parse_ls, parse_lt, parse_md, parse_mm, parse_po, parse_pq, parse_pw,
+            parse_sl, parse_st, parse_xf, parse_zpl, parse_zpl_intern, parse_zpl_labels,
+            parse_zpl_labels_spanned, parse_zpl_lenient, parse_zpl_spanned, parse_zpl_stream,
+        },
+    };
+
+    #[test]
+    fn parse_ll_test() {
+        let input = "^LL236^LS0";
+        let (remain, zpl) = parse_ll(Prefixes::default())(input).unwrap();
+        assert_eq!(remain, "^LS0");
+        assert_eq!(zpl, ZplFormatCommand::LabelLength(236));
+    }
+
+    #[test]
+    fn parse_pw_test() {
+        let input = "^PW685^LL236";
+        let (remain, zpl) = parse_pw(Prefixes::default())(input).unwrap();
+        assert_eq!(remain, "^LL236");
+        assert_eq!(zpl, ZplFormatCommand::PrintWidth(685));
+    }
+
+    #[test]
+    fn parse_cf_test() {
+        let input = "^CF0,60";
+        let (remain, zpl) = parse_cf(Prefixes::default())(input).unwrap();
+        assert_eq!(remain, "");
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::ChangeFont {
+                name: '0',
+                height: 60,
+                width: 60,
+            }
+        );
+
+        let input = "^CF0,60,30";
+        let (remain, zpl) = parse_cf(Prefixes::default())(input).unwrap();
+        assert_eq!(remain, "");
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::ChangeFont {
+                name: '0',
+                height: 60,
+                width: 30,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_ls_test() {
+        let input = "^LS0^FT86";
+        let (remain, zpl) = parse_ls(Prefixes::default())(input).unwrap();
+        assert_eq!(remain, "^FT86");
+        assert_eq!(zpl, ZplFormatCommand::LabelShift(0));
+
+        let (_, zpl) = parse_ls(Prefixes::default())("^LS-20").unwrap();
+        assert_eq!(zpl, ZplFormatCommand::LabelShift(-20));
+    }
+
+    #[test]
+    fn parse_lt_test() {
+        let (remain, zpl) = parse_lt(Prefixes::default())("^LT-15^FO").unwrap();
+        assert_eq!(remain, "^FO");
+        assert_eq!(zpl, ZplFormatCommand::LabelTop(-15));
+    }
+
+    #[test]
+    fn parse_po_test() {
+        let (remain, zpl) = parse_po(Prefixes::default())("^POI^FO").unwrap();
+        assert_eq!(remain, "^FO");
+        assert_eq!(zpl, ZplFormatCommand::PrintOrientation { inverted: true });
+
+        let (_, zpl) = parse_po(Prefixes::default())("^PON").unwrap();
+        assert_eq!(zpl, ZplFormatCommand::PrintOrientation { inverted: false });
+    }
+
+    #[test]
+    fn parse_lr_test() {
+        let (remain, zpl) = parse_lr(Prefixes::default())("^LRY^FO").unwrap();
+        assert_eq!(remain, "^FO");
+        assert_eq!(zpl, ZplFormatCommand::LabelReverse(true));
+
+        let (_, zpl) = parse_lr(Prefixes::default())("^LRN").unwrap();
+        assert_eq!(zpl, ZplFormatCommand::LabelReverse(false));
+    }
+
+    #[test]
+    fn parse_jm_test() {
+        let (remain, zpl) = parse_jm(Prefixes::default())("^JMB^FO").unwrap();
+        assert_eq!(remain, "^FO");
+        assert_eq!(zpl, ZplFormatCommand::HalfResolution(true));
+
+        let (_, zpl) = parse_jm(Prefixes::default())("^JMA").unwrap();
+        assert_eq!(zpl, ZplFormatCommand::HalfResolution(false));
+    }
+
+    #[test]
+    fn parse_lh_test() {
+        let (remain, zpl) = parse_lh(Prefixes::default())("^LH30,40^FO").unwrap();
+        assert_eq!(remain, "^FO");
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::LabelHome {
+                x: Some(30),
+                y: Some(40)
+            }
+        );
+
+        let (_, zpl) = parse_lh(Prefixes::default())("^LH30").unwrap();
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::LabelHome {
+                x: Some(30),
+                y: None
+            }
+        );
+
+        // omitted parameters keep the current label home
+        let (remain, zpl) = parse_lh(Prefixes::default())("^LH,50^FO").unwrap();
+        assert_eq!(remain, "^FO");
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::LabelHome {
+                x: None,
+                y: Some(50)
+            }
+        );
+    }
+
+    #[test]
+    fn parse_a_test() {
+        let input = "^A0N,21,20^FH";
+        let (remain, zpl) = parse_a(Prefixes::default())(input).unwrap();
+        assert_eq!(remain, "^FH");
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::Font {
+                name: '0',
+                orientation: Some(Orientation::Normal),
+                height: 21,
+                width: 20
+            }
+        );
+
+        let (_, zpl) = parse_a(Prefixes::default())("^A0,30,25^FH").unwrap();
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::Font {
+                name: '0',
+                orientation: None,
+                height: 30,
+                width: 25
+            }
+        );
+
+        let (_, zpl) = parse_a(Prefixes::default())("^ADB,18,10").unwrap();
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::Font {
+                name: 'D',
+                orientation: Some(Orientation::BackRotate),
+                height: 18,
+                width: 10
+            }
+        );
+    }
+
+    #[test]
+    fn parse_fw_test() {
+        let (remain, zpl) = parse_fw(Prefixes::default())("^FWR^FO").unwrap();
+        assert_eq!(remain, "^FO");
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::FieldOrientation {
+                orientation: Orientation::Rotate,
+                justification: Justification::Left,
+            }
+        );
+
+        let (_, zpl) = parse_fw(Prefixes::default())("^FWI,1").unwrap();
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::FieldOrientation {
+                orientation: Orientation::Invert,
+                justification: Justification::Right,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_fo_test() {
+        let input = "^FO349,327^FT";
+        let (remain, zpl) = parse_fo(Prefixes::default())(input).unwrap();
+        assert_eq!(remain, "^FT");
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::FieldOrigin {
+                x: 349,
+                y: 327,
+                justification: None
+            }
+        );
+        let input = "^FO349,327,2^FT";
+        let (remain, zpl) = parse_fo(Prefixes::default())(input).unwrap();
+        assert_eq!(remain, "^FT");
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::FieldOrigin {
+                x: 349,
+                y: 327,
+                justification: Some(Justification::Auto)
+            }
+        );
+    }
+
+    #[test]
+    fn parse_ft_test() {
+        let input = "^FT349,327";
+        let (remain, zpl) = parse_ft(Prefixes::default())(input).unwrap();
+        assert_eq!(remain, "");
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::FieldTypeset {
+                x: 349,
+                y: 327,
+                justification: None
+            }
+        );
+        let input = "^FT349,327,2";
+        let (remain, zpl) = parse_ft(Prefixes::default())(input).unwrap();
+        assert_eq!(remain, "");
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::FieldTypeset {
+                x: 349,
+                y: 327,
+                justification: Some(Justification::Auto)
+            }
+        );
+    }
+
+    #[test]
+    fn parse_fd_test() {
+        let input = "^FDText^FS";
+        let (remain, zpl) = parse_fd(Prefixes::default())(input).unwrap();
+        assert_eq!(remain, "^FS");
+        assert_eq!(zpl, ZplFormatCommand::FieldData("Text".into()));
+    }
+
+    #[test]
+    fn parse_gf_test() {
+        let data = "eJytk7ENg0AMRQ8BAinFNenZBMpshdkgK1CnyAp4g2SEG4ESIYLjozr7LkqKmO7JenxsUxhVhWk1sthrVOE+fO+yGLtcwtWbOgT14TqHqDxcwmZH68BAiBr0uShMZhd2lSS6ZnbZXaCVczWbSEUVttMSohNdEeAZoowe2NEovocIQbyQ/YREN1GT76KXeIhduhxECH9DKdce51KL7LwLBQLvcuobHcAsJ3HBthPzynlefSWnuvHsc5HCrryhTG0ovUe97eRNRJfz4b5UJW8VNPrv3f/yp6VccVdm7jqXGd7xtuh/";
+        let checksum = ":E957";
+        let input = format!("^GFA,309,988,19,:Z64:{data}{checksum}^FT");
+        let (remain, zpl) = parse_fg(Prefixes::default())(&input).unwrap();
+        assert_eq!(remain, "^FT");
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::GraphicField {
+                compression_type: CompressionType::Ascii,
+                data_bytes: 309,
+                total_bytes: 988,
+                row_bytes: 19,
+                data: GraficData {
+                    compression_method: CompressionMethod::Zlib,
+                    data: data.into()
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn parse_gb_test() {
+        let input = "^GB100,100,100^FS".to_string();
+        let (remain, zpl) = parse_gb(Prefixes::default())(&input).unwrap();
+        assert_eq!(remain, "^FS");
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::GraphicalBox {
+                width: 100,
+                height: 100,
+                thickness: 100,
+                color: Color::Black,
+                rounding: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_gc_test() {
+        let (remain, zpl) = parse_gc(Prefixes::default())("^GC100,10,W^FS").unwrap();
+        assert_eq!(remain, "^FS");
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::GraphicalCircle {
+                diameter: 100,
+                thickness: 10,
+                color: Color::White,
+            }
+        );
+
+        let (_, zpl) = parse_gc(Prefixes::default())("^GC^FS").unwrap();
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::GraphicalCircle {
+                diameter: 3,
+                thickness: 1,
+                color: Color::Black,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_ge_test() {
+        let (remain, zpl) = parse_ge(Prefixes::default())("^GE300,100,10,B^FS").unwrap();
+        assert_eq!(remain, "^FS");
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::GraphicalEllipse {
+                width: 300,
+                height: 100,
+                thickness: 10,
+                color: Color::Black,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_gd_test() {
+        let (remain, zpl) = parse_gd(Prefixes::default())("^GD200,100,5,,L^FS").unwrap();
+        assert_eq!(remain, "^FS");
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::GraphicalDiagonalLine {
+                width: 200,
+                height: 100,
+                thickness: 5,
+                color: Color::Black,
+                orientation: DiagonalOrientation::Left,
+            }
+        );
+
+        let (_, zpl) = parse_gd(Prefixes::default())("^GD200,100,5,W,/^FS").unwrap();
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::GraphicalDiagonalLine {
+                width: 200,
+                height: 100,
+                thickness: 5,
+                color: Color::White,
+                orientation: DiagonalOrientation::Right,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_fr_test() {
+        let input = "^FR^FDTest^FS".to_string();
+        let (remain, zpl) = parse_fr(Prefixes::default())(&input).unwrap();
+        assert_eq!(remain, "^FDTest^FS");
+        assert_eq!(zpl, ZplFormatCommand::Inverted);
+    }
+
+    #[test]
+    fn parse_by_test() {
+        let input = "^BY5,2,270^FO100,550".to_string();
+        let (remain, zpl) = parse_by(Prefixes::default())(&input).unwrap();
+        assert_eq!(remain, "^FO100,550");
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::BarcodeConfig {
+                width: 5,
+                width_ratio: 2.,
+                height: 270
+            }
+        );
+
+        let (_, zpl) = parse_by(Prefixes::default())("^BY0").unwrap();
+        assert!(matches!(
+            zpl,
+            ZplFormatCommand::BarcodeConfig { width: 1, .. }
+        ));
+        let (_, zpl) = parse_by(Prefixes::default())("^BY20").unwrap();
+        assert!(matches!(
+            zpl,
+            ZplFormatCommand::BarcodeConfig { width: 10, .. }
+        ));
+    }
+
+    #[test]
+    fn parse_bc_test() {
+        let input = "^BCN,50,Y,N,N,A^FD12345678^FS".to_string();
+        let (remain, zpl) = parse_bc(Prefixes::default())(&input).unwrap();
+        assert_eq!(remain, "^FD12345678^FS");
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::Barcode(BarcodeType::Code128 {
+                orientation: Orientation::Normal,
+                height: Some(50),
+                show_text: true,
+                text_above: false,
+                check_digit: false,
+                mode: Code128Mode::Auto
+            })
+        );
+    }
+
+    #[test]
+    fn parse_bc_blank_test() {
+        let input = "^BC^FD12345678^FS".to_string();
+        let (remain, zpl) = parse_bc(Prefixes::default())(&input).unwrap();
+        assert_eq!(remain, "^FD12345678^FS");
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::Barcode(BarcodeType::Code128 {
+                orientation: Orientation::Normal,
+                height: None,
+                show_text: true,
+                text_above: false,
+                check_digit: false,
+                mode: Code128Mode::Normal
+            })
+        );
+    }
 
-    while let Some(xa_pos) = rest.find("^XA") {
-        let after_xa = &rest[xa_pos + 3..];
-        if let Some(xz_pos) = after_xa.find("^XZ") {
-            let end = xa_pos + 3 + xz_pos + 3;
-            labels.push(Ok(&rest[xa_pos..end]));
-            rest = &rest[end..];
-        } else {
-            labels.push(Err(ParseError {
-                kind: ParseErrorKind::MissingCommand,
-                message: "^XZ".to_string(),
-            }));
-            break;
+    #[test]
+    fn parse_bc_field_number_test() {
+        // the parameters end at the next command, not only at ^FD
+        for input in ["^BCN,50^FN1^FS", "^BCN,50^FH^FD>;12_3E^FS"] {
+            let (remain, zpl) = parse_bc(Prefixes::default())(input).unwrap();
+            assert_eq!(remain, &input[7..]);
+            assert!(matches!(
+                zpl,
+                ZplFormatCommand::Barcode(BarcodeType::Code128 {
+                    height: Some(50),
+                    ..
+                })
+            ));
         }
     }
 
-    labels
-}
-
-pub fn parse_zpl(input: &str) -> Result<Vec<ZplFormatCommand>, ParseError> {
-    // extract labels
-    let labels = find_labels(input);
-    let input = labels.last().ok_or(ParseError {
-        kind: ParseErrorKind::MissingCommand,
-        message: "^XA".to_string(),
-    })?;
-
-    let input = input.as_deref().map_err(|err| err.clone())?;
-
-    // strip ^XA
-    let (input, _) = parse_xa(input)?;
-
-    // parse content
-    let (_, commands) = parse_zpl_intern(input)
-        .map_err(|err| <nom::Err<nom::error::Error<&str>> as Into<ParseError>>::into(err))?;
-
-    Ok(commands)
-}
-
-#[cfg(test)]
-mod tests {
-    use std::collections::HashMap;
+    #[test]
+    fn parse_bc_defaults_test() {
+        let input = "^BCN,50^FDABC^FS".to_string();
+        let (remain, zpl) = parse_bc(Prefixes::default())(&input).unwrap();
+        assert_eq!(remain, "^FDABC^FS");
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::Barcode(BarcodeType::Code128 {
+                orientation: Orientation::Normal,
+                height: Some(50),
+                show_text: true,
+                text_above: false,
+                check_digit: false,
+                mode: Code128Mode::Normal
+            })
+        );
+    }
 
-    use crate::{
-        BarcodeType, ClockFormat, ClockLanguage, ClockMode, Code128Mode, Color, Justification,
-        ParseError, ParseErrorKind, TextBlockJustification,
-        commands::{CompressionMethod, CompressionType, GraficData, Orientation, ZplFormatCommand},
-        parse::{
-            parse_a, parse_bc, parse_be, parse_by, parse_cf, parse_ci, parse_fb, parse_fc,
-            parse_fd, parse_fg, parse_fh, parse_fo, parse_fr, parse_ft, parse_fx, parse_gb,
-            parse_ll, parse_ls, parse_md, parse_mm, parse_pq, parse_pw, parse_sl, parse_st,
-            parse_zpl, parse_zpl_intern,
-        },
-    };
+    #[test]
+    fn parse_be_test() {
+        let input = "^BEN,50,Y,N^FD12345678^FS".to_string();
+        let (remain, zpl) = parse_be(Prefixes::default())(&input).unwrap();
+        assert_eq!(remain, "^FD12345678^FS");
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::Barcode(BarcodeType::Ean13 {
+                orientation: Orientation::Normal,
+                height: Some(50),
+                show_text: true,
+                text_above: false,
+            })
+        );
+    }
 
     #[test]
-    fn parse_ll_test() {
-        let input = "^LL236^LS0";
-        let (remain, zpl) = parse_ll(input).unwrap();
-        assert_eq!(remain, "^LS0");
-        assert_eq!(zpl, ZplFormatCommand::LabelLength(236));
+    fn parse_be_blank_test() {
+        let input = "^BE^FD12345678^FS".to_string();
+        let (remain, zpl) = parse_be(Prefixes::default())(&input).unwrap();
+        assert_eq!(remain, "^FD12345678^FS");
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::Barcode(BarcodeType::Ean13 {
+                orientation: Orientation::Normal,
+                height: None,
+                show_text: true,
+                text_above: true,
+            })
+        );
     }
 
     #[test]
-    fn parse_pw_test() {
-        let input = "^PW685^LL236";
-        let (remain, zpl) = parse_pw(input).unwrap();
-        assert_eq!(remain, "^LL236");
-        assert_eq!(zpl, ZplFormatCommand::PrintWidth(685));
+    fn parse_barcode_field_hex_test() {
+        // commands between a barcode and its ^FD are kept
+        for barcode in [
+            "^BCN,50",
+            "^BEN,50",
+            "^B3N,N,50",
+            "^BQN,2,5",
+            "^BXN,10,200,,,,~",
+            "^B7N,6,5",
+            "^BFN,8,12",
+            "^B8N,60",
+            "^B9N,60",
+            "^BUN,70",
+            "^BSN,137",
+            "^B2N,80",
+            "^BIN,40",
+            "^BJN,50",
+            "^BAN,60",
+            "^BKN,N,50",
+            "^B1N,Y,40",
+            "^BMN,D",
+            "^BPN,Y,70",
+            "^BON,4",
+            "^BD2",
+            "^BRN,6,3",
+        ] {
+            let input = format!("^XA^FO10,10{barcode}^FH^FD_41^FS^XZ");
+            let commands = parse_zpl(&input).unwrap();
+            assert!(
+                commands
+                    .iter()
+                    .any(|command| matches!(command, ZplFormatCommand::Barcode(_))),
+                "{barcode}"
+            );
+            assert!(
+                commands
+                    .iter()
+                    .any(|command| matches!(command, ZplFormatCommand::FieldHexIndicator { .. })),
+                "{barcode}"
+            );
+        }
     }
 
     #[test]
-    fn parse_cf_test() {
-        let input = "^CF0,60";
-        let (remain, zpl) = parse_cf(input).unwrap();
-        assert_eq!(remain, "");
+    fn parse_b3_test() {
+        let (remain, zpl) = parse_b3(Prefixes::default())("^B3R,Y,80,N,Y^FDCODE39^FS").unwrap();
+        assert_eq!(remain, "^FDCODE39^FS");
         assert_eq!(
             zpl,
-            ZplFormatCommand::ChangeFont {
-                name: '0',
-                height: 60,
-                width: 60,
-            }
+            ZplFormatCommand::Barcode(BarcodeType::Code39 {
+                orientation: Orientation::Rotate,
+                check_digit: true,
+                height: Some(80),
+                show_text: false,
+                text_above: true,
+            })
         );
 
-        let input = "^CF0,60,30";
-        let (remain, zpl) = parse_cf(input).unwrap();
-        assert_eq!(remain, "");
+        let (_, zpl) = parse_b3(Prefixes::default())("^B3^FDCODE39^FS").unwrap();
         assert_eq!(
             zpl,
-            ZplFormatCommand::ChangeFont {
-                name: '0',
-                height: 60,
-                width: 30,
-            }
+            ZplFormatCommand::Barcode(BarcodeType::Code39 {
+                orientation: Orientation::Normal,
+                check_digit: false,
+                height: None,
+                show_text: true,
+                text_above: false,
+            })
         );
     }
 
     #[test]
-    fn parse_ls_test() {
-        let input = "^LS0^FT86";
-        let (remain, zpl) = parse_ls(input).unwrap();
-        assert_eq!(remain, "^FT86");
-        assert_eq!(zpl, ZplFormatCommand::LabelShift(0));
+    fn parse_bq_test() {
+        let (remain, zpl) = parse_bq(Prefixes::default())("^BQN,2,10,H,3^FDHA,QR^FS").unwrap();
+        assert_eq!(remain, "^FDHA,QR^FS");
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::Barcode(BarcodeType::Qr {
+                orientation: Orientation::Normal,
+                model: 2,
+                magnification: Some(10),
+                error_correction: QrErrorCorrection::High,
+                mask: 3,
+            })
+        );
+
+        let (_, zpl) = parse_bq(Prefixes::default())("^BQ,,20,X,9^FDQA,QR^FS").unwrap();
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::Barcode(BarcodeType::Qr {
+                orientation: Orientation::Normal,
+                model: 2,
+                magnification: Some(10),
+                error_correction: QrErrorCorrection::Medium,
+                mask: 7,
+            })
+        );
+
+        let (_, zpl) = parse_bq(Prefixes::default())("^BQ^FDQA,QR^FS").unwrap();
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::Barcode(BarcodeType::Qr {
+                orientation: Orientation::Normal,
+                model: 2,
+                magnification: None,
+                error_correction: QrErrorCorrection::Quality,
+                mask: 7,
+            })
+        );
     }
 
     #[test]
-    fn parse_a_test() {
-        let input = "^A0N,21,20^FH";
-        let (remain, zpl) = parse_a(input).unwrap();
-        assert_eq!(remain, "^FH");
+    fn parse_bx_test() {
+        let (remain, zpl) =
+            parse_bx(Prefixes::default())("^BXR,8,200,24,24,6,_,2^FD_1010123^FS").unwrap();
+        assert_eq!(remain, "^FD_1010123^FS");
         assert_eq!(
             zpl,
-            ZplFormatCommand::Font {
-                name: '0',
+            ZplFormatCommand::Barcode(BarcodeType::DataMatrix {
+                orientation: Orientation::Rotate,
+                element_height: Some(8),
+                quality: 200,
+                columns: Some(24),
+                rows: Some(24),
+                format_id: 6,
+                escape_char: '_',
+                rectangular: true,
+            })
+        );
+
+        let (_, zpl) = parse_bx(Prefixes::default())("^BXN,10,200^FDDM^FS").unwrap();
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::Barcode(BarcodeType::DataMatrix {
                 orientation: Orientation::Normal,
-                height: 21,
-                width: 20
-            }
+                element_height: Some(10),
+                quality: 200,
+                columns: None,
+                rows: None,
+                format_id: 6,
+                escape_char: '~',
+                rectangular: false,
+            })
         );
     }
 
     #[test]
-    fn parse_fo_test() {
-        let input = "^FO349,327^FT";
-        let (remain, zpl) = parse_fo(input).unwrap();
-        assert_eq!(remain, "^FT");
+    fn parse_b7_test() {
+        let (remain, zpl) = parse_b7(Prefixes::default())("^B7N,6,5,8,20,Y^FDPDF417^FS").unwrap();
+        assert_eq!(remain, "^FDPDF417^FS");
         assert_eq!(
             zpl,
-            ZplFormatCommand::FieldOrigin {
-                x: 349,
-                y: 327,
-                justification: Justification::Left
-            }
+            ZplFormatCommand::Barcode(BarcodeType::Pdf417 {
+                orientation: Orientation::Normal,
+                row_height: Some(6),
+                security_level: 5,
+                columns: Some(8),
+                rows: Some(20),
+                truncate: true,
+            })
         );
-        let input = "^FO349,327,2^FT";
-        let (remain, zpl) = parse_fo(input).unwrap();
-        assert_eq!(remain, "^FT");
+
+        let (_, zpl) = parse_b7(Prefixes::default())("^B7R,,9,40,2^FDPDF417^FS").unwrap();
         assert_eq!(
             zpl,
-            ZplFormatCommand::FieldOrigin {
-                x: 349,
-                y: 327,
-                justification: Justification::Auto
-            }
+            ZplFormatCommand::Barcode(BarcodeType::Pdf417 {
+                orientation: Orientation::Rotate,
+                row_height: None,
+                security_level: 8,
+                columns: None,
+                rows: None,
+                truncate: false,
+            })
         );
     }
 
     #[test]
-    fn parse_ft_test() {
-        let input = "^FT349,327";
-        let (remain, zpl) = parse_ft(input).unwrap();
-        assert_eq!(remain, "");
+    fn parse_bf_test() {
+        let (remain, zpl) = parse_bf(Prefixes::default())("^BFN,8,12^FDMICRO^FS").unwrap();
+        assert_eq!(remain, "^FDMICRO^FS");
         assert_eq!(
             zpl,
-            ZplFormatCommand::FieldTypeset {
-                x: 349,
-                y: 327,
-                justification: Justification::Left
-            }
+            ZplFormatCommand::Barcode(BarcodeType::MicroPdf417 {
+                orientation: Orientation::Normal,
+                row_height: Some(8),
+                mode: 12,
+            })
         );
-        let input = "^FT349,327,2";
-        let (remain, zpl) = parse_ft(input).unwrap();
-        assert_eq!(remain, "");
+
+        let (_, zpl) = parse_bf(Prefixes::default())("^BF^FDMICRO^FS").unwrap();
         assert_eq!(
             zpl,
-            ZplFormatCommand::FieldTypeset {
-                x: 349,
-                y: 327,
-                justification: Justification::Auto
-            }
+            ZplFormatCommand::Barcode(BarcodeType::MicroPdf417 {
+                orientation: Orientation::Normal,
+                row_height: None,
+                mode: 0,
+            })
         );
     }
 
     #[test]
-    fn parse_fd_test() {
-        let input = "^FDText^FS";
-        let (remain, zpl) = parse_fd(input).unwrap();
-        assert_eq!(remain, "^FS");
-        assert_eq!(zpl, ZplFormatCommand::FieldData("Text".into()));
+    fn parse_upc_ean_test() {
+        let (remain, zpl) = parse_b8(Prefixes::default())("^B8N,60,Y,N^FD1234567^FS").unwrap();
+        assert_eq!(remain, "^FD1234567^FS");
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::Barcode(BarcodeType::Ean8 {
+                orientation: Orientation::Normal,
+                height: Some(60),
+                show_text: true,
+                text_above: false,
+            })
+        );
+
+        let (_, zpl) = parse_bu(Prefixes::default())("^BUR,70,N,Y,N^FD07000002198^FS").unwrap();
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::Barcode(BarcodeType::UpcA {
+                orientation: Orientation::Rotate,
+                height: Some(70),
+                show_text: false,
+                text_above: true,
+                check_digit: false,
+            })
+        );
+
+        let (_, zpl) = parse_b9(Prefixes::default())("^B9^FD1230000045^FS").unwrap();
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::Barcode(BarcodeType::UpcE {
+                orientation: Orientation::Normal,
+                height: None,
+                show_text: true,
+                text_above: false,
+                check_digit: true,
+            })
+        );
     }
 
     #[test]
-    fn parse_gf_test() {
-        let data = "eJytk7ENg0AMRQ8BAinFNenZBMpshdkgK1CnyAp4g2SEG4ESIYLjozr7LkqKmO7JenxsUxhVhWk1sthrVOE+fO+yGLtcwtWbOgT14TqHqDxcwmZH68BAiBr0uShMZhd2lSS6ZnbZXaCVczWbSEUVttMSohNdEeAZoowe2NEovocIQbyQ/YREN1GT76KXeIhduhxECH9DKdce51KL7LwLBQLvcuobHcAsJ3HBthPzynlefSWnuvHsc5HCrryhTG0ovUe97eRNRJfz4b5UJW8VNPrv3f/yp6VccVdm7jqXGd7xtuh/";
-        let checksum = ":E957";
-        let input = format!("^GFA,309,988,19,:Z64:{data}{checksum}^FT");
-        let (remain, zpl) = parse_fg(&input).unwrap();
-        assert_eq!(remain, "^FT");
+    fn parse_bs_test() {
+        let (remain, zpl) = parse_bs(Prefixes::default())("^BSN,137,Y,N^FD04414^FS").unwrap();
+        assert_eq!(remain, "^FD04414^FS");
         assert_eq!(
             zpl,
-            ZplFormatCommand::GraphicField {
-                compression_type: CompressionType::Ascii,
-                data_bytes: 309,
-                total_bytes: 988,
-                row_bytes: 19,
-                data: GraficData {
-                    compression_method: CompressionMethod::Zlib,
-                    data: data.into()
-                }
-            }
+            ZplFormatCommand::Barcode(BarcodeType::UpcEanExtension {
+                orientation: Orientation::Normal,
+                height: Some(137),
+                show_text: true,
+                text_above: false,
+            })
+        );
+
+        let (_, zpl) = parse_bs(Prefixes::default())("^BS^FD12^FS").unwrap();
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::Barcode(BarcodeType::UpcEanExtension {
+                orientation: Orientation::Normal,
+                height: None,
+                show_text: true,
+                text_above: true,
+            })
         );
     }
 
     #[test]
-    fn parse_gb_test() {
-        let input = format!("^GB100,100,100^FS");
-        let (remain, zpl) = parse_gb(&input).unwrap();
-        assert_eq!(remain, "^FS");
+    fn parse_aztec_maxicode_test() {
+        let (remain, zpl) = parse_bo(Prefixes::default())("^BON,4,N,23,N,1^FDAZTEC^FS").unwrap();
+        assert_eq!(remain, "^FDAZTEC^FS");
         assert_eq!(
             zpl,
-            ZplFormatCommand::GraphicalBox {
-                width: 100,
-                height: 100,
-                thickness: 100,
-                color: Color::Black,
-                rounding: 0,
-            }
+            ZplFormatCommand::Barcode(BarcodeType::Aztec {
+                orientation: Orientation::Normal,
+                magnification: Some(4),
+                extended_channel: false,
+                size: 23,
+                menu_symbol: false,
+                symbol_count: 1,
+            })
+        );
+
+        // ^B0 is an alias, unsupported sizes fall back to the default
+        let (_, zpl) = parse_bo(Prefixes::default())("^B0R,,Y,150,Y,30,ID^FDAZTEC^FS").unwrap();
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::Barcode(BarcodeType::Aztec {
+                orientation: Orientation::Rotate,
+                magnification: None,
+                extended_channel: true,
+                size: 0,
+                menu_symbol: true,
+                symbol_count: 26,
+            })
+        );
+
+        let (remain, zpl) = parse_bd(Prefixes::default())("^BD3,2,3^FD066826000abc^FS").unwrap();
+        assert_eq!(remain, "^FD066826000abc^FS");
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::Barcode(BarcodeType::MaxiCode {
+                mode: 3,
+                symbol_number: 2,
+                symbol_count: 3,
+            })
+        );
+
+        let (_, zpl) = parse_bd(Prefixes::default())("^BD^FD1^FS").unwrap();
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::Barcode(BarcodeType::MaxiCode {
+                mode: 2,
+                symbol_number: 1,
+                symbol_count: 1,
+            })
         );
     }
 
     #[test]
-    fn parse_fr_test() {
-        let input = format!("^FR^FDTest^FS");
-        let (remain, zpl) = parse_fr(&input).unwrap();
-        assert_eq!(remain, "^FDTest^FS");
-        assert_eq!(zpl, ZplFormatCommand::Inverted);
-    }
+    fn parse_databar_test() {
+        let (remain, zpl) =
+            parse_br(Prefixes::default())("^BRN,6,3,2,40,8^FD(01)98898765432106(3202)012345^FS")
+                .unwrap();
+        assert_eq!(remain, "^FD(01)98898765432106(3202)012345^FS");
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::Barcode(BarcodeType::DataBar {
+                orientation: Orientation::Normal,
+                symbology: DataBarType::Expanded,
+                magnification: 3,
+                separator_height: 2,
+                height: 40,
+                segment_width: 8,
+            })
+        );
 
-    #[test]
-    fn parse_by_test() {
-        let input = format!("^BY5,2,270^FO100,550");
-        let (remain, zpl) = parse_by(&input).unwrap();
-        assert_eq!(remain, "^FO100,550");
+        // odd segment widths are rounded down, unknown types fall back to omnidirectional
+        let (_, zpl) = parse_br(Prefixes::default())("^BRR,13,,,,7^FD0123456789012^FS").unwrap();
         assert_eq!(
             zpl,
-            ZplFormatCommand::BarcodeConfig {
-                width: 5,
-                width_ratio: 2.,
-                height: 270
-            }
+            ZplFormatCommand::Barcode(BarcodeType::DataBar {
+                orientation: Orientation::Rotate,
+                symbology: DataBarType::Omnidirectional,
+                magnification: 2,
+                separator_height: 1,
+                height: 25,
+                segment_width: 6,
+            })
         );
     }
 
     #[test]
-    fn parse_bc_test() {
-        let input = format!("^BCN,50,Y,N,N,A^FD12345678^FS");
-        let (remain, zpl) = parse_bc(&input).unwrap();
-        assert_eq!(remain, "^FD12345678^FS");
+    fn parse_legacy_linear_test() {
+        let (remain, zpl) = parse_ba(Prefixes::default())("^BAN,60,Y,N,Y^FDCODE93^FS").unwrap();
+        assert_eq!(remain, "^FDCODE93^FS");
         assert_eq!(
             zpl,
-            ZplFormatCommand::Barcode(BarcodeType::Code128 {
+            ZplFormatCommand::Barcode(BarcodeType::Code93 {
                 orientation: Orientation::Normal,
-                height: Some(50),
+                height: Some(60),
                 show_text: true,
                 text_above: false,
+                check_digit: true,
+            })
+        );
+
+        let (_, zpl) = parse_bk(Prefixes::default())("^BKR,N,50,Y,N,B,D^FD123^FS").unwrap();
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::Barcode(BarcodeType::Codabar {
+                orientation: Orientation::Rotate,
                 check_digit: false,
-                mode: Code128Mode::Auto
+                height: Some(50),
+                show_text: true,
+                text_above: false,
+                start: 'B',
+                stop: 'D',
             })
         );
-    }
 
-    #[test]
-    fn parse_bc_blank_test() {
-        let input = format!("^BC^FD12345678^FS");
-        let (remain, zpl) = parse_bc(&input).unwrap();
-        assert_eq!(remain, "^FD12345678^FS");
+        let (_, zpl) = parse_bk(Prefixes::default())("^BK^FD123^FS").unwrap();
+        assert!(matches!(
+            zpl,
+            ZplFormatCommand::Barcode(BarcodeType::Codabar {
+                start: 'A',
+                stop: 'A',
+                ..
+            })
+        ));
+
+        let (_, zpl) = parse_b1(Prefixes::default())("^B1N,Y,40^FD12-3^FS").unwrap();
         assert_eq!(
             zpl,
-            ZplFormatCommand::Barcode(BarcodeType::Code128 {
+            ZplFormatCommand::Barcode(BarcodeType::Code11 {
                 orientation: Orientation::Normal,
-                height: None,
+                single_check_digit: true,
+                height: Some(40),
                 show_text: true,
+                text_above: false,
+            })
+        );
+
+        let (_, zpl) = parse_bm(Prefixes::default())("^BMB,D,,N,Y,Y^FD1234^FS").unwrap();
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::Barcode(BarcodeType::Msi {
+                orientation: Orientation::BackRotate,
+                check_digit: MsiCheckDigit::Mod11Mod10,
+                height: None,
+                show_text: false,
                 text_above: true,
+                text_check_digit: true,
+            })
+        );
+
+        let (_, zpl) = parse_bm(Prefixes::default())("^BMN^FD1234^FS").unwrap();
+        assert!(matches!(
+            zpl,
+            ZplFormatCommand::Barcode(BarcodeType::Msi {
+                check_digit: MsiCheckDigit::Mod10,
+                text_check_digit: false,
+                ..
+            })
+        ));
+
+        let (_, zpl) = parse_bp(Prefixes::default())("^BPI,Y,70^FD12AB^FS").unwrap();
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::Barcode(BarcodeType::Plessey {
+                orientation: Orientation::Invert,
                 check_digit: true,
-                mode: Code128Mode::Normal
+                height: Some(70),
+                show_text: true,
+                text_above: false,
             })
         );
     }
 
     #[test]
-    fn parse_be_test() {
-        let input = format!("^BEN,50,Y,N^FD12345678^FS");
-        let (remain, zpl) = parse_be(&input).unwrap();
-        assert_eq!(remain, "^FD12345678^FS");
+    fn parse_two_of_five_test() {
+        let (remain, zpl) = parse_b2(Prefixes::default())("^B2N,80,Y,N,Y^FD1234^FS").unwrap();
+        assert_eq!(remain, "^FD1234^FS");
         assert_eq!(
             zpl,
-            ZplFormatCommand::Barcode(BarcodeType::Ean13 {
+            ZplFormatCommand::Barcode(BarcodeType::Interleaved2Of5 {
                 orientation: Orientation::Normal,
-                height: Some(50),
+                height: Some(80),
                 show_text: true,
                 text_above: false,
+                check_digit: true,
             })
         );
-    }
 
-    #[test]
-    fn parse_be_blank_test() {
-        let input = format!("^BE^FD12345678^FS");
-        let (remain, zpl) = parse_be(&input).unwrap();
-        assert_eq!(remain, "^FD12345678^FS");
+        let (_, zpl) = parse_b2(Prefixes::default())("^B2R^FD1234^FS").unwrap();
         assert_eq!(
             zpl,
-            ZplFormatCommand::Barcode(BarcodeType::Ean13 {
-                orientation: Orientation::Normal,
+            ZplFormatCommand::Barcode(BarcodeType::Interleaved2Of5 {
+                orientation: Orientation::Rotate,
                 height: None,
                 show_text: true,
+                text_above: false,
+                check_digit: false,
+            })
+        );
+
+        let (_, zpl) = parse_bj(Prefixes::default())("^BJN,50,N,Y^FD1234^FS").unwrap();
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::Barcode(BarcodeType::Standard2Of5 {
+                orientation: Orientation::Normal,
+                height: Some(50),
+                show_text: false,
                 text_above: true,
             })
         );
+
+        let (_, zpl) = parse_bi(Prefixes::default())("^BIB,40^FD1234^FS").unwrap();
+        assert_eq!(
+            zpl,
+            ZplFormatCommand::Barcode(BarcodeType::Industrial2Of5 {
+                orientation: Orientation::BackRotate,
+                height: Some(40),
+                show_text: true,
+                text_above: false,
+            })
+        );
     }
 
     #[test]
     fn parse_fx_test() {
         let input = "^FX this is a comment and even a ^FO may appear here\r\n^FT";
-        let (remain, zpl) = parse_fx(input).unwrap();
+        let (remain, _zpl) = parse_fx(Prefixes::default())(input).unwrap();
         assert_eq!(remain, "^FT")
     }
 
     #[test]
     fn parse_mm_test() {
         let input = "^MMT";
-        let (remain, zpl) = parse_mm(&input).unwrap();
+        let (remain, _zpl) = parse_mm(Prefixes::default())(input).unwrap();
         assert_eq!(remain, "");
 
         let input = "^MMT,Y";
-        let (remain, zpl) = parse_mm(&input).unwrap();
+        let (remain, _zpl) = parse_mm(Prefixes::default())(input).unwrap();
         assert_eq!(remain, "")
     }
 
     #[test]
     fn parse_md_test() {
         let input = "^MD-30";
-        let (remain, zpl) = parse_md(&input).unwrap();
+        let (remain, _zpl) = parse_md(Prefixes::default())(input).unwrap();
         assert_eq!(remain, "");
     }
 
     #[test]
     fn parse_fh_test() {
         let input = "^FH\\";
-        let (remain, zpl) = parse_fh(&input).unwrap();
+        let (remain, zpl) = parse_fh(Prefixes::default())(input).unwrap();
         assert_eq!(remain, "");
-        assert_eq!(zpl, ZplFormatCommand::FieldHexIndicator { char: '\\' })
+        assert_eq!(zpl, ZplFormatCommand::FieldHexIndicator { char: '\\' });
+
+        let (remain, zpl) = parse_fh(Prefixes::default())("^FH^FD_41").unwrap();
+        assert_eq!(remain, "^FD_41");
+        assert_eq!(zpl, ZplFormatCommand::FieldHexIndicator { char: '_' });
     }
 
     #[test]
     fn parse_ci_test() {
         let input = "^CI28";
-        let (remain, zpl) = parse_ci(&input).unwrap();
+        let (remain, zpl) = parse_ci(Prefixes::default())(input).unwrap();
         assert_eq!(remain, "");
         assert_eq!(
             zpl,
@@ -975,7 +3444,7 @@ mod tests {
         );
 
         let input = "^CI0,36,21";
-        let (remain, zpl) = parse_ci(&input).unwrap();
+        let (remain, zpl) = parse_ci(Prefixes::default())(input).unwrap();
         assert_eq!(remain, "");
         assert_eq!(
             zpl,
@@ -989,7 +3458,7 @@ mod tests {
     #[test]
     fn should_error_on_parse_ci_test() {
         let input = "^CI0,1";
-        let err = parse_ci(&input).unwrap_err();
+        let err = parse_ci(Prefixes::default())(input).unwrap_err();
         assert_eq!(
             err,
             nom::Err::Error(nom::error::Error {
@@ -1002,7 +3471,7 @@ mod tests {
     #[test]
     fn parse_fb_test() {
         let input = "^FB500,5";
-        let (remain, zpl) = parse_fb(&input).unwrap();
+        let (remain, zpl) = parse_fb(Prefixes::default())(input).unwrap();
         assert_eq!(remain, "");
         assert_eq!(
             zpl,
@@ -1016,7 +3485,7 @@ mod tests {
         );
 
         let input = "^FB500,5,1,R,1";
-        let (remain, zpl) = parse_fb(&input).unwrap();
+        let (remain, zpl) = parse_fb(Prefixes::default())(input).unwrap();
         assert_eq!(remain, "");
         assert_eq!(
             zpl,
@@ -1033,18 +3502,18 @@ mod tests {
     #[test]
     fn parse_pq_test() {
         let input = "^PQ10";
-        let (remain, _) = parse_pq(&input).unwrap();
+        let (remain, _) = parse_pq(Prefixes::default())(input).unwrap();
         assert_eq!(remain, "");
 
         let input = "^PQ10,0,0,Y,N";
-        let (remain, _) = parse_pq(&input).unwrap();
+        let (remain, _) = parse_pq(Prefixes::default())(input).unwrap();
         assert_eq!(remain, "");
     }
 
     #[test]
     fn parse_sl_test() {
         let input = "^SLT";
-        let (remain, zpl) = parse_sl(&input).unwrap();
+        let (remain, zpl) = parse_sl(Prefixes::default())(input).unwrap();
         assert_eq!(remain, "");
 
         assert_eq!(
@@ -1056,7 +3525,7 @@ mod tests {
         );
 
         let input = "^SLT,4";
-        let (remain, zpl) = parse_sl(&input).unwrap();
+        let (remain, zpl) = parse_sl(Prefixes::default())(input).unwrap();
         assert_eq!(remain, "");
 
         assert_eq!(
@@ -1071,7 +3540,7 @@ mod tests {
     #[test]
     fn parse_fc_test() {
         let input = "^FC%,+";
-        let (remain, zpl) = parse_fc(&input).unwrap();
+        let (remain, zpl) = parse_fc(Prefixes::default())(input).unwrap();
         assert_eq!(remain, "");
 
         assert_eq!(
@@ -1084,7 +3553,7 @@ mod tests {
         );
 
         let input = "^FC";
-        let (remain, zpl) = parse_fc(&input).unwrap();
+        let (remain, zpl) = parse_fc(Prefixes::default())(input).unwrap();
         assert_eq!(remain, "");
 
         assert_eq!(
@@ -1097,7 +3566,7 @@ mod tests {
         );
 
         let input = "^FC,+";
-        let (remain, zpl) = parse_fc(&input).unwrap();
+        let (remain, zpl) = parse_fc(Prefixes::default())(input).unwrap();
         assert_eq!(remain, "");
 
         assert_eq!(
@@ -1113,7 +3582,7 @@ mod tests {
     #[test]
     fn parse_st_test() {
         let input = "^ST";
-        let (remain, zpl) = parse_st(&input).unwrap();
+        let (remain, zpl) = parse_st(Prefixes::default())(input).unwrap();
         assert_eq!(remain, "");
 
         assert_eq!(
@@ -1130,7 +3599,7 @@ mod tests {
         );
 
         let input = "^ST5,,2025,,10,,A";
-        let (remain, zpl) = parse_st(&input).unwrap();
+        let (remain, zpl) = parse_st(Prefixes::default())(input).unwrap();
         assert_eq!(remain, "");
 
         assert_eq!(
@@ -1154,7 +3623,14 @@ mod tests {
         ^LS0
         ^FT86,78^A0N,51,51^FD#1001#^FS^XZ";
 
-        let (remain, commands) = parse_zpl_intern(input).unwrap();
+        let (remain, commands) = parse_zpl_intern(Prefixes::default(), input).unwrap();
+        let commands: Vec<_> = commands
+            .into_iter()
+            .map(|(_, command)| match command {
+                ZplCommand::Format(command) => command,
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect();
 
         assert_eq!(remain, "^XZ");
         assert_eq!(
@@ -1166,11 +3642,11 @@ mod tests {
                 ZplFormatCommand::FieldTypeset {
                     x: 86,
                     y: 78,
-                    justification: Justification::Left
+                    justification: None
                 },
                 ZplFormatCommand::Font {
                     name: '0',
-                    orientation: Orientation::Normal,
+                    orientation: Some(Orientation::Normal),
                     height: 51,
                     width: 51,
                 },
@@ -1188,7 +3664,8 @@ mod tests {
             err,
             ParseError {
                 kind: ParseErrorKind::MissingCommand,
-                message: "^XA".to_string()
+                message: "^XA".to_string(),
+                location: None
             }
         )
     }
@@ -1201,11 +3678,129 @@ mod tests {
             err,
             ParseError {
                 kind: ParseErrorKind::MissingCommand,
-                message: "^XZ".to_string()
+                message: "^XZ".to_string(),
+                location: Some(Location {
+                    offset: 0,
+                    line: 1,
+                    column: 1
+                })
+            }
+        )
+    }
+
+    #[test]
+    fn parse_zpl_labels_test() {
+        let input = "^XA^FO10,10^FDFirst^FS^XZ\r\n^XA^FO20,20^FDSecond^FS^XZ";
+        let labels = parse_zpl_labels(input).unwrap();
+        assert_eq!(
+            labels,
+            vec![
+                vec![
+                    ZplFormatCommand::FieldOrigin {
+                        x: 10,
+                        y: 10,
+                        justification: None
+                    },
+                    ZplFormatCommand::FieldData("First".to_string()),
+                    ZplFormatCommand::FieldSeparator
+                ],
+                vec![
+                    ZplFormatCommand::FieldOrigin {
+                        x: 20,
+                        y: 20,
+                        justification: None
+                    },
+                    ZplFormatCommand::FieldData("Second".to_string()),
+                    ZplFormatCommand::FieldSeparator
+                ],
+            ]
+        );
+
+        // parse_zpl keeps returning the last label only
+        assert_eq!(parse_zpl(input).unwrap(), labels[1]);
+    }
+
+    #[test]
+    fn should_error_on_missing_xz_in_later_label() {
+        let input = "^XA^FDFirst^FS^XZ^XA^FDSecond^FS";
+        let err = parse_zpl_labels(input).unwrap_err();
+        assert_eq!(
+            err,
+            ParseError {
+                kind: ParseErrorKind::MissingCommand,
+                message: "^XZ".to_string(),
+                location: Some(Location {
+                    offset: 17,
+                    line: 1,
+                    column: 18
+                })
+            }
+        )
+    }
+
+    #[test]
+    fn should_error_on_missing_xa_for_labels() {
+        let input = "^FDTest^FS";
+        let err = parse_zpl_labels(input).unwrap_err();
+        assert_eq!(
+            err,
+            ParseError {
+                kind: ParseErrorKind::MissingCommand,
+                message: "^XA".to_string(),
+                location: None
             }
         )
     }
 
+    #[test]
+    fn parse_zpl_labels_spanned_test() {
+        let input = "^XA^FDFirst^FS^XZ\n^XA\n  ^FO20,20^FS\n^XZ";
+        let labels = parse_zpl_labels_spanned(input).unwrap();
+        assert_eq!(labels.len(), 2);
+
+        let fo = &labels[1][0];
+        assert_eq!(
+            fo.node,
+            ZplFormatCommand::FieldOrigin {
+                x: 20,
+                y: 20,
+                justification: None
+            }
+        );
+        assert_eq!(&input[fo.span.start.offset..fo.span.end.offset], "^FO20,20");
+        assert_eq!(
+            fo.span.start,
+            Location {
+                offset: 24,
+                line: 3,
+                column: 3
+            }
+        );
+        assert_eq!(
+            fo.span.end,
+            Location {
+                offset: 32,
+                line: 3,
+                column: 11
+            }
+        );
+    }
+
+    #[test]
+    fn should_locate_failing_command() {
+        let input = "^XA\r\n^FO10,10\r\n^GFX,1,1,1,:Z64:AA^FS\r\n^XZ";
+        let err = parse_zpl_spanned(input).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::InvalidSyntax);
+        assert_eq!(
+            err.location,
+            Some(Location {
+                offset: 15,
+                line: 3,
+                column: 1
+            })
+        );
+    }
+
     #[test]
     fn should_error_on_invalid_syntax_command() {
         let input = "^XAInvalidCommand^XZ";
@@ -1214,7 +3809,12 @@ mod tests {
             err,
             ParseError {
                 kind: ParseErrorKind::InvalidSyntax,
-                message: "InvalidCom".to_string()
+                message: "InvalidCom".to_string(),
+                location: Some(Location {
+                    offset: 3,
+                    line: 1,
+                    column: 4
+                })
             }
         )
     }
@@ -1227,9 +3827,333 @@ mod tests {
             err,
             ParseError {
                 kind: ParseErrorKind::InvalidSyntax,
-                message: "^Unknown^C".to_string()
+                message: "^Unknown^C".to_string(),
+                location: Some(Location {
+                    offset: 11,
+                    line: 1,
+                    column: 12
+                })
+            }
+        );
+    }
+
+    #[test]
+    fn parse_zpl_lenient_test() {
+        let input = "^XA^FO10,10^CWX,E:FONT.FNT\n^FDText^FS^FOab^FS\nInvalid^XZ";
+        let parsed = parse_zpl_lenient(input);
+        let commands: Vec<_> = parsed.labels[0].iter().map(|c| c.node.clone()).collect();
+        assert_eq!(
+            commands,
+            vec![
+                ZplFormatCommand::FieldOrigin {
+                    x: 10,
+                    y: 10,
+                    justification: None
+                },
+                ZplFormatCommand::Unknown {
+                    code: "^CW".to_string(),
+                    params: "X,E:FONT.FNT".to_string()
+                },
+                ZplFormatCommand::FieldData("Text".to_string()),
+                ZplFormatCommand::FieldSeparator,
+                ZplFormatCommand::Unknown {
+                    code: "^FO".to_string(),
+                    params: "ab".to_string()
+                },
+                ZplFormatCommand::FieldSeparator,
+            ]
+        );
+        assert_eq!(
+            parsed.warnings,
+            vec![
+                ParseWarning {
+                    kind: ParseWarningKind::UnknownCommand,
+                    message: "^CW".to_string(),
+                    location: Some(Location {
+                        offset: 11,
+                        line: 1,
+                        column: 12
+                    })
+                },
+                ParseWarning {
+                    kind: ParseWarningKind::MalformedCommand,
+                    message: "^FOab".to_string(),
+                    location: Some(Location {
+                        offset: 37,
+                        line: 2,
+                        column: 11
+                    })
+                },
+                ParseWarning {
+                    kind: ParseWarningKind::UnexpectedInput,
+                    message: "Invalid^XZ".to_string(),
+                    location: Some(Location {
+                        offset: 46,
+                        line: 3,
+                        column: 1
+                    })
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_zpl_lenient_missing_xz_test() {
+        let input = "^XA^FDText^FS";
+        let parsed = parse_zpl_lenient(input);
+        let commands: Vec<_> = parsed.labels[0].iter().map(|c| c.node.clone()).collect();
+        assert_eq!(
+            commands,
+            vec![
+                ZplFormatCommand::FieldData("Text".to_string()),
+                ZplFormatCommand::FieldSeparator,
+            ]
+        );
+        assert_eq!(parsed.warnings[0].kind, ParseWarningKind::MissingCommand);
+    }
+
+    #[test]
+    fn parse_zpl_lenient_keeps_every_label() {
+        let input = std::fs::read_to_string("../zpl/examples/render_with_images.txt").unwrap();
+        let parsed = parse_zpl_lenient(&input);
+        assert_eq!(parsed.labels.len(), 2);
+        assert!(!parsed.warnings.is_empty());
+
+        let last: Vec<_> = parsed.labels[1].iter().map(|c| c.node.clone()).collect();
+        assert_eq!(last, parse_zpl(&input).unwrap());
+    }
+
+    #[test]
+    fn parse_host_command_test() {
+        let cases = [
+            ("~JA", ZplHostCommand::CancelAllCommands),
+            ("~JC", ZplHostCommand::CancelCurrentCommand),
+            ("~HS", ZplHostCommand::PrintHostStatus),
+            ("~HI", ZplHostCommand::HostIdentification),
+            ("~SD15", ZplHostCommand::SetDarkness(15)),
+            ("~TA000", ZplHostCommand::TearOffAdjust(0)),
+            ("~TA-10", ZplHostCommand::TearOffAdjust(-10)),
+            (
+                "~JSN",
+                ZplHostCommand::BackfeedSequence(BackfeedSequence::Normal),
+            ),
+            (
+                "~JS20",
+                ZplHostCommand::BackfeedSequence(BackfeedSequence::Percent(20)),
+            ),
+        ];
+        for (input, expected) in cases {
+            let (remain, command) = parse_host_command(Prefixes::default())(input).unwrap();
+            assert_eq!(remain, "", "{input}");
+            assert_eq!(command, expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn parse_download_commands_test() {
+        let input = "~DGR:SAMPLE.GRF,00080,010,\nFFFFFFFFFFFFFFFFFFFF\n8000FFFF0000FFFF0001\n^XA";
+        let (remain, command) = parse_host_command(Prefixes::default())(input).unwrap();
+        assert_eq!(remain, "^XA");
+        assert_eq!(
+            command,
+            ZplHostCommand::DownloadGraphics {
+                name: "R:SAMPLE.GRF".to_string(),
+                total_bytes: 80,
+                row_bytes: 10,
+                data: GraficData {
+                    compression_method: CompressionMethod::None,
+                    data: "FFFFFFFFFFFFFFFFFFFF8000FFFF0000FFFF0001".to_string(),
+                },
+            }
+        );
+
+        let input = "~DYR:LOGO,A,G,64,8,:Z64:eJxjYGBgAAAABAAB:1A2B~JA";
+        let (remain, command) = parse_host_command(Prefixes::default())(input).unwrap();
+        assert_eq!(remain, "~JA");
+        assert_eq!(
+            command,
+            ZplHostCommand::DownloadObject {
+                name: "R:LOGO".to_string(),
+                format: DownloadFormat::Ascii,
+                extension: "G".to_string(),
+                total_bytes: 64,
+                row_bytes: Some(8),
+                data: GraficData {
+                    compression_method: CompressionMethod::Zlib,
+                    data: "eJxjYGBgAAAABAAB".to_string(),
+                },
+            }
+        );
+
+        // binary sizes are bytes, not chars
+        let input = "~DYR:IMG,B,G,4,,\u{e9}\u{1}A^XA";
+        let (remain, command) = parse_host_command(Prefixes::default())(input).unwrap();
+        assert_eq!(remain, "^XA");
+        let ZplHostCommand::DownloadObject { data, .. } = command else {
+            panic!("expected download object");
+        };
+        assert_eq!(data.data, "\u{e9}\u{1}A");
+        assert!(parse_host_command(Prefixes::default())("~DYR:IMG,B,G,1,,\u{e9}^XA").is_err());
+
+        let input = "~DUR:KANJI,4,00FF10AB^XA";
+        let (remain, command) = parse_host_command(Prefixes::default())(input).unwrap();
+        assert_eq!(remain, "^XA");
+        assert_eq!(
+            command,
+            ZplHostCommand::DownloadUnboundedFont {
+                name: "R:KANJI".to_string(),
+                size: 4,
+                data: "00FF10AB".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_zpl_stream_test() {
+        let input = "~JA\n^XA\n~SD15\n^PW100^FDText^FS\n^XZ\n~HS\n^XA^FS^XZ";
+        let commands = parse_zpl_stream(input).unwrap();
+        assert_eq!(
+            commands,
+            vec![
+                ZplCommand::Host(ZplHostCommand::CancelAllCommands),
+                ZplCommand::StartFormat,
+                ZplCommand::Host(ZplHostCommand::SetDarkness(15)),
+                ZplCommand::Format(ZplFormatCommand::PrintWidth(100)),
+                ZplCommand::Format(ZplFormatCommand::FieldData("Text".to_string())),
+                ZplCommand::Format(ZplFormatCommand::FieldSeparator),
+                ZplCommand::EndFormat,
+                ZplCommand::Host(ZplHostCommand::PrintHostStatus),
+                ZplCommand::StartFormat,
+                ZplCommand::Format(ZplFormatCommand::FieldSeparator),
+                ZplCommand::EndFormat,
+            ]
+        );
+
+        // host commands inside of a label do not disturb format parsing
+        assert_eq!(
+            parse_zpl(input).unwrap(),
+            vec![ZplFormatCommand::FieldSeparator]
+        );
+        let labels = parse_zpl_labels(input).unwrap();
+        assert_eq!(labels[0].len(), 3);
+    }
+
+    #[test]
+    fn parse_zpl_stream_errors() {
+        let err = parse_zpl_stream("~JA\n^XA^FS").unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::MissingCommand);
+        assert_eq!(err.location.map(|l| l.offset), Some(4));
+
+        let err = parse_zpl_stream("~JA garbage").unwrap_err();
+        assert_eq!(err.location.map(|l| l.offset), Some(4));
+    }
+
+    #[test]
+    fn parse_prefix_change_test() {
+        // changed caret, ^ is plain data now
+        let input = "^XA^CC+\n+FO10,10+FDa^b+FS+XZ";
+        assert_eq!(
+            parse_zpl(input).unwrap(),
+            vec![
+                ZplFormatCommand::ChangePrefix {
+                    caret: '+',
+                    tilde: '~'
+                },
+                ZplFormatCommand::FieldOrigin {
+                    x: 10,
+                    y: 10,
+                    justification: None
+                },
+                ZplFormatCommand::FieldData("a^b".to_string()),
+                ZplFormatCommand::FieldSeparator,
+            ]
+        );
+
+        // changes persist into following labels
+        let input = "~CC!!XA!FDa!FS!XZ\n!XA!CT#!FDb!FS!XZ#HS^XA";
+        let labels = parse_zpl_labels(input).unwrap();
+        assert_eq!(labels.len(), 2);
+        assert_eq!(
+            labels[1][..3],
+            [
+                ZplFormatCommand::ChangePrefix {
+                    caret: '!',
+                    tilde: '~'
+                },
+                ZplFormatCommand::ChangePrefix {
+                    caret: '!',
+                    tilde: '#'
+                },
+                ZplFormatCommand::FieldData("b".to_string()),
+            ]
+        );
+
+        // every parse starts with the default prefixes
+        assert_eq!(
+            parse_zpl("^XA^FS^XZ").unwrap(),
+            vec![ZplFormatCommand::FieldSeparator]
+        );
+
+        // ^XA is no command with the changed caret
+        assert!(parse_zpl_stream(input).is_err());
+        let commands = parse_zpl_stream(input.trim_end_matches("^XA")).unwrap();
+        assert_eq!(
+            commands.last(),
+            Some(&ZplCommand::Host(ZplHostCommand::PrintHostStatus))
+        );
+        assert_eq!(
+            commands[0],
+            ZplCommand::Format(ZplFormatCommand::ChangePrefix {
+                caret: '!',
+                tilde: '~'
+            })
+        );
+    }
+
+    #[test]
+    fn parse_prefix_change_lenient_test() {
+        let input = "^XA^CC+\n+CWX,E:FONT.FNT+FDText+FS+XZ";
+        let parsed = parse_zpl_lenient(input);
+        let commands: Vec<_> = parsed.labels[0].iter().map(|c| c.node.clone()).collect();
+        assert_eq!(
+            commands[1],
+            ZplFormatCommand::Unknown {
+                code: "^CW".to_string(),
+                params: "X,E:FONT.FNT".to_string()
+            }
+        );
+        assert_eq!(parsed.warnings[0].message, "^CW");
+        assert_eq!(commands.len(), 4);
+    }
+
+    #[test]
+    fn parse_stored_format_test() {
+        let (remain, command) = parse_df(Prefixes::default())("^DFR:LABEL.ZPL^FS").unwrap();
+        assert_eq!(remain, "^FS");
+        assert_eq!(
+            command,
+            ZplFormatCommand::StoreFormat {
+                name: "R:LABEL.ZPL".to_string()
             }
         );
+
+        let (remain, command) = parse_xf(Prefixes::default())("^XFLABEL\n^FS").unwrap();
+        assert_eq!(remain, "\n^FS");
+        assert_eq!(
+            command,
+            ZplFormatCommand::RecallFormat {
+                name: "LABEL".to_string()
+            }
+        );
+        assert!(parse_xf(Prefixes::default())("^XF^FS").is_err());
+
+        let (remain, command) = parse_fn(Prefixes::default())("^FN12^FS").unwrap();
+        assert_eq!(remain, "^FS");
+        assert_eq!(command, ZplFormatCommand::FieldNumber(12));
+
+        let (remain, command) = parse_fn(Prefixes::default())("^FN1\"Name\"^FS").unwrap();
+        assert_eq!(remain, "^FS");
+        assert_eq!(command, ZplFormatCommand::FieldNumber(1));
     }
 
     #[test]
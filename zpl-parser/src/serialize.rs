@@ -0,0 +1,871 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::{
+    BackfeedSequence, BarcodeType, ClockFormat, ClockMode, Code128Mode, Color, CompressionMethod,
+    CompressionType, DataBarType, DiagonalOrientation, DownloadFormat, GraficData, Justification,
+    MsiCheckDigit, Orientation, QrErrorCorrection, TextBlockJustification, ZplCommand,
+    ZplFormatCommand, ZplHostCommand,
+};
+
+/// Serialize the commands of a single label, including ^XA and ^XZ.
+///
+/// Commands following a [`ZplFormatCommand::ChangePrefix`] are written
+/// with the changed prefixes.
+pub fn to_zpl(commands: &[ZplFormatCommand]) -> String {
+    let (mut caret, mut tilde) = ('^', '~');
+    let mut zpl = String::from("^XA");
+    for command in commands {
+        if let ZplFormatCommand::ChangePrefix {
+            caret: new_caret,
+            tilde: new_tilde,
+        } = *command
+        {
+            zpl.push_str(&prefix_change((caret, tilde), (new_caret, new_tilde)));
+            (caret, tilde) = (new_caret, new_tilde);
+            continue;
+        }
+
+        // commands are displayed with the default prefixes
+        let command = command.to_string();
+        let mut chars = command.chars();
+        match chars.next() {
+            Some('^') => zpl.push(caret),
+            Some('~') => zpl.push(tilde),
+            Some(c) => zpl.push(c),
+            None => {}
+        }
+        zpl.push_str(chars.as_str());
+    }
+    zpl.push(caret);
+    zpl.push_str("XZ");
+    zpl
+}
+
+/// ^CC and ^CT switching the prefixes `(caret, tilde)` to `to`
+fn prefix_change((caret, tilde): (char, char), to: (char, char)) -> String {
+    let mut zpl = String::new();
+    if to.0 != caret {
+        zpl.push_str(&format!("{caret}CC{}", to.0));
+    }
+    if to.1 != tilde {
+        zpl.push_str(&format!("{tilde}CT{}", to.1));
+    }
+    zpl
+}
+
+/// CRC-16/XMODEM, used as checksum of `:Z64:` encoded data
+fn crc16(data: &str) -> u16 {
+    data.bytes().fold(0u16, |crc, byte| {
+        (0..8).fold(crc ^ ((byte as u16) << 8), |crc, _| match crc & 0x8000 {
+            0 => crc << 1,
+            _ => (crc << 1) ^ 0x1021,
+        })
+    })
+}
+
+fn yes_no(value: bool) -> char {
+    match value {
+        true => 'Y',
+        false => 'N',
+    }
+}
+
+fn color(color: &Color) -> char {
+    match color {
+        Color::Black => 'B',
+        Color::White => 'W',
+    }
+}
+
+fn opt<T: Display>(value: &Option<T>) -> String {
+    value.as_ref().map(T::to_string).unwrap_or_default()
+}
+
+impl Display for GraficData {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.compression_method {
+            CompressionMethod::None => write!(f, "{}", self.data),
+            CompressionMethod::Zlib => write!(f, ":Z64:{}:{:04X}", self.data, crc16(&self.data)),
+        }
+    }
+}
+
+impl Display for Orientation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let orientation = match self {
+            Orientation::Normal => 'N',
+            Orientation::Rotate => 'R',
+            Orientation::Invert => 'I',
+            Orientation::BackRotate => 'B',
+        };
+        write!(f, "{orientation}")
+    }
+}
+
+impl Display for BarcodeType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BarcodeType::Code39 {
+                orientation,
+                check_digit,
+                height,
+                show_text,
+                text_above,
+            } => write!(
+                f,
+                "^B3{orientation},{},{},{},{}",
+                yes_no(*check_digit),
+                opt(height),
+                yes_no(*show_text),
+                yes_no(*text_above)
+            ),
+            BarcodeType::Code128 {
+                orientation,
+                height,
+                show_text,
+                text_above,
+                check_digit,
+                mode,
+            } => {
+                let mode = match mode {
+                    Code128Mode::Normal => 'N',
+                    Code128Mode::Ucc => 'U',
+                    Code128Mode::Auto => 'A',
+                    Code128Mode::Ean => 'D',
+                };
+                write!(
+                    f,
+                    "^BC{orientation},{},{},{},{},{mode}",
+                    opt(height),
+                    yes_no(*show_text),
+                    yes_no(*text_above),
+                    yes_no(*check_digit)
+                )
+            }
+            BarcodeType::Pdf417 {
+                orientation,
+                row_height,
+                security_level,
+                columns,
+                rows,
+                truncate,
+            } => write!(
+                f,
+                "^B7{orientation},{},{security_level},{},{},{}",
+                opt(row_height),
+                opt(columns),
+                opt(rows),
+                yes_no(*truncate)
+            ),
+            BarcodeType::MicroPdf417 {
+                orientation,
+                row_height,
+                mode,
+            } => write!(f, "^BF{orientation},{},{mode}", opt(row_height)),
+            BarcodeType::Ean8 {
+                orientation,
+                height,
+                show_text,
+                text_above,
+            } => write!(
+                f,
+                "^B8{orientation},{},{},{}",
+                opt(height),
+                yes_no(*show_text),
+                yes_no(*text_above)
+            ),
+            BarcodeType::UpcA {
+                orientation,
+                height,
+                show_text,
+                text_above,
+                check_digit,
+            } => write!(
+                f,
+                "^BU{orientation},{},{},{},{}",
+                opt(height),
+                yes_no(*show_text),
+                yes_no(*text_above),
+                yes_no(*check_digit)
+            ),
+            BarcodeType::UpcE {
+                orientation,
+                height,
+                show_text,
+                text_above,
+                check_digit,
+            } => write!(
+                f,
+                "^B9{orientation},{},{},{},{}",
+                opt(height),
+                yes_no(*show_text),
+                yes_no(*text_above),
+                yes_no(*check_digit)
+            ),
+            BarcodeType::Ean13 {
+                orientation,
+                height,
+                show_text,
+                text_above,
+            } => write!(
+                f,
+                "^BE{orientation},{},{},{}",
+                opt(height),
+                yes_no(*show_text),
+                yes_no(*text_above)
+            ),
+            BarcodeType::UpcEanExtension {
+                orientation,
+                height,
+                show_text,
+                text_above,
+            } => write!(
+                f,
+                "^BS{orientation},{},{},{}",
+                opt(height),
+                yes_no(*show_text),
+                yes_no(*text_above)
+            ),
+            BarcodeType::Interleaved2Of5 {
+                orientation,
+                height,
+                show_text,
+                text_above,
+                check_digit,
+            } => write!(
+                f,
+                "^B2{orientation},{},{},{},{}",
+                opt(height),
+                yes_no(*show_text),
+                yes_no(*text_above),
+                yes_no(*check_digit)
+            ),
+            BarcodeType::Standard2Of5 {
+                orientation,
+                height,
+                show_text,
+                text_above,
+            } => write!(
+                f,
+                "^BJ{orientation},{},{},{}",
+                opt(height),
+                yes_no(*show_text),
+                yes_no(*text_above)
+            ),
+            BarcodeType::Industrial2Of5 {
+                orientation,
+                height,
+                show_text,
+                text_above,
+            } => write!(
+                f,
+                "^BI{orientation},{},{},{}",
+                opt(height),
+                yes_no(*show_text),
+                yes_no(*text_above)
+            ),
+            BarcodeType::Code93 {
+                orientation,
+                height,
+                show_text,
+                text_above,
+                check_digit,
+            } => write!(
+                f,
+                "^BA{orientation},{},{},{},{}",
+                opt(height),
+                yes_no(*show_text),
+                yes_no(*text_above),
+                yes_no(*check_digit)
+            ),
+            BarcodeType::Codabar {
+                orientation,
+                check_digit,
+                height,
+                show_text,
+                text_above,
+                start,
+                stop,
+            } => write!(
+                f,
+                "^BK{orientation},{},{},{},{},{start},{stop}",
+                yes_no(*check_digit),
+                opt(height),
+                yes_no(*show_text),
+                yes_no(*text_above)
+            ),
+            BarcodeType::Code11 {
+                orientation,
+                single_check_digit,
+                height,
+                show_text,
+                text_above,
+            } => write!(
+                f,
+                "^B1{orientation},{},{},{},{}",
+                yes_no(*single_check_digit),
+                opt(height),
+                yes_no(*show_text),
+                yes_no(*text_above)
+            ),
+            BarcodeType::Msi {
+                orientation,
+                check_digit,
+                height,
+                show_text,
+                text_above,
+                text_check_digit,
+            } => write!(
+                f,
+                "^BM{orientation},{},{},{},{},{}",
+                match check_digit {
+                    MsiCheckDigit::None => 'A',
+                    MsiCheckDigit::Mod10 => 'B',
+                    MsiCheckDigit::Mod10Mod10 => 'C',
+                    MsiCheckDigit::Mod11Mod10 => 'D',
+                },
+                opt(height),
+                yes_no(*show_text),
+                yes_no(*text_above),
+                yes_no(*text_check_digit)
+            ),
+            BarcodeType::Plessey {
+                orientation,
+                check_digit,
+                height,
+                show_text,
+                text_above,
+            } => write!(
+                f,
+                "^BP{orientation},{},{},{},{}",
+                yes_no(*check_digit),
+                opt(height),
+                yes_no(*show_text),
+                yes_no(*text_above)
+            ),
+            BarcodeType::DataBar {
+                orientation,
+                symbology,
+                magnification,
+                separator_height,
+                height,
+                segment_width,
+            } => {
+                let symbology = match symbology {
+                    DataBarType::Omnidirectional => 1,
+                    DataBarType::Truncated => 2,
+                    DataBarType::Stacked => 3,
+                    DataBarType::StackedOmnidirectional => 4,
+                    DataBarType::Limited => 5,
+                    DataBarType::Expanded => 6,
+                    DataBarType::UpcA => 7,
+                    DataBarType::UpcE => 8,
+                    DataBarType::Ean13 => 9,
+                    DataBarType::Ean8 => 10,
+                    DataBarType::Gs1128CcAb => 11,
+                    DataBarType::Gs1128CcC => 12,
+                };
+                write!(
+                    f,
+                    "^BR{orientation},{symbology},{magnification},{separator_height},{height},{segment_width}"
+                )
+            }
+            BarcodeType::Qr {
+                orientation,
+                model,
+                magnification,
+                error_correction,
+                mask,
+            } => {
+                let error_correction = match error_correction {
+                    QrErrorCorrection::High => 'H',
+                    QrErrorCorrection::Quality => 'Q',
+                    QrErrorCorrection::Medium => 'M',
+                    QrErrorCorrection::Low => 'L',
+                };
+                write!(
+                    f,
+                    "^BQ{orientation},{model},{},{error_correction},{mask}",
+                    opt(magnification)
+                )
+            }
+            BarcodeType::Aztec {
+                orientation,
+                magnification,
+                extended_channel,
+                size,
+                menu_symbol,
+                symbol_count,
+            } => write!(
+                f,
+                "^BO{orientation},{},{},{size},{},{symbol_count}",
+                opt(magnification),
+                yes_no(*extended_channel),
+                yes_no(*menu_symbol)
+            ),
+            BarcodeType::MaxiCode {
+                mode,
+                symbol_number,
+                symbol_count,
+            } => write!(f, "^BD{mode},{symbol_number},{symbol_count}"),
+            BarcodeType::DataMatrix {
+                orientation,
+                element_height,
+                quality,
+                columns,
+                rows,
+                format_id,
+                escape_char,
+                rectangular,
+            } => write!(
+                f,
+                "^BX{orientation},{},{quality},{},{},{format_id},{escape_char},{}",
+                opt(element_height),
+                opt(columns),
+                opt(rows),
+                if *rectangular { 2 } else { 1 }
+            ),
+        }
+    }
+}
+
+impl Display for ZplFormatCommand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ZplFormatCommand::LabelLength(length) => write!(f, "^LL{length}"),
+            ZplFormatCommand::PrintWidth(width) => write!(f, "^PW{width}"),
+            ZplFormatCommand::LabelShift(shift) => write!(f, "^LS{shift}"),
+            ZplFormatCommand::LabelTop(top) => write!(f, "^LT{top}"),
+            ZplFormatCommand::LabelHome { x, y } => write!(f, "^LH{},{}", opt(x), opt(y)),
+            ZplFormatCommand::PrintOrientation { inverted } => match inverted {
+                true => write!(f, "^POI"),
+                false => write!(f, "^PON"),
+            },
+            ZplFormatCommand::LabelReverse(reverse) => write!(f, "^LR{}", yes_no(*reverse)),
+            ZplFormatCommand::HalfResolution(half) => match half {
+                true => write!(f, "^JMB"),
+                false => write!(f, "^JMA"),
+            },
+            ZplFormatCommand::BarcodeConfig {
+                width,
+                width_ratio,
+                height,
+            } => write!(f, "^BY{width},{width_ratio},{height}"),
+            ZplFormatCommand::Barcode(barcode) => write!(f, "{barcode}"),
+            ZplFormatCommand::ChangeFont {
+                name,
+                height,
+                width,
+            } => write!(f, "^CF{name},{height},{width}"),
+            ZplFormatCommand::Font {
+                name,
+                orientation,
+                height,
+                width,
+            } => write!(f, "^A{name}{},{height},{width}", opt(orientation)),
+            ZplFormatCommand::FieldOrientation {
+                orientation,
+                justification,
+            } => {
+                let justification = Some(*justification).filter(|j| *j != Justification::Left);
+                write!(f, "^FW{orientation}{}", FieldJustification(justification))
+            }
+            ZplFormatCommand::FieldOrigin {
+                x,
+                y,
+                justification,
+            } => write!(f, "^FO{x},{y}{}", FieldJustification(*justification)),
+            ZplFormatCommand::FieldTypeset {
+                x,
+                y,
+                justification,
+            } => write!(f, "^FT{x},{y}{}", FieldJustification(*justification)),
+            ZplFormatCommand::FieldData(data) => write!(f, "^FD{data}"),
+            ZplFormatCommand::GraphicField {
+                compression_type,
+                data_bytes,
+                total_bytes,
+                row_bytes,
+                data,
+            } => {
+                let compression_type = match compression_type {
+                    CompressionType::Ascii => 'A',
+                    CompressionType::Binary => 'B',
+                    CompressionType::Compressed => 'C',
+                };
+                write!(
+                    f,
+                    "^GF{compression_type},{data_bytes},{total_bytes},{row_bytes},{data}"
+                )
+            }
+            ZplFormatCommand::GraphicalBox {
+                width,
+                height,
+                thickness,
+                color: c,
+                rounding,
+            } => write!(f, "^GB{width},{height},{thickness},{},{rounding}", color(c)),
+            ZplFormatCommand::GraphicalCircle {
+                diameter,
+                thickness,
+                color: c,
+            } => write!(f, "^GC{diameter},{thickness},{}", color(c)),
+            ZplFormatCommand::GraphicalEllipse {
+                width,
+                height,
+                thickness,
+                color: c,
+            } => write!(f, "^GE{width},{height},{thickness},{}", color(c)),
+            ZplFormatCommand::GraphicalDiagonalLine {
+                width,
+                height,
+                thickness,
+                color: c,
+                orientation,
+            } => {
+                let orientation = match orientation {
+                    DiagonalOrientation::Right => 'R',
+                    DiagonalOrientation::Left => 'L',
+                };
+                write!(
+                    f,
+                    "^GD{width},{height},{thickness},{},{orientation}",
+                    color(c)
+                )
+            }
+            ZplFormatCommand::Inverted => write!(f, "^FR"),
+            ZplFormatCommand::FieldHexIndicator { char } => write!(f, "^FH{char}"),
+            ZplFormatCommand::CharacterSet { num, mapping } => {
+                write!(f, "^CI{num}")?;
+                let mut mapping: Vec<_> = mapping.iter().collect();
+                mapping.sort();
+                for (from, to) in mapping {
+                    write!(f, ",{from},{to}")?;
+                }
+                Ok(())
+            }
+            ZplFormatCommand::FieldBlock {
+                width,
+                lines,
+                line_spacing,
+                justification,
+                hanging_indent,
+            } => {
+                let justification = match justification {
+                    TextBlockJustification::Left => 'L',
+                    TextBlockJustification::Center => 'C',
+                    TextBlockJustification::Right => 'R',
+                    TextBlockJustification::Justified => 'J',
+                };
+                write!(
+                    f,
+                    "^FB{width},{lines},{line_spacing},{justification},{hanging_indent}"
+                )
+            }
+            ZplFormatCommand::RealTimeClockMode { mode, language } => {
+                let mode = match mode {
+                    ClockMode::Start => "S".to_string(),
+                    ClockMode::Now => "T".to_string(),
+                    ClockMode::Resolution(n) => n.to_string(),
+                };
+                // languages are numbered from 1 in declaration order
+                let language = *language as u8 + 1;
+                write!(f, "^SL{mode},{language}")
+            }
+            ZplFormatCommand::RealTimeClockEscapeChar {
+                first,
+                second,
+                third,
+            } => {
+                write!(f, "^FC{first}")?;
+                if let Some(second) = second {
+                    write!(f, ",{second}")?;
+                }
+                if let Some(third) = third {
+                    write!(f, ",{third}")?;
+                }
+                Ok(())
+            }
+            ZplFormatCommand::FieldSeparator => write!(f, "^FS"),
+            ZplFormatCommand::SetRealTimeClock {
+                month,
+                day,
+                year,
+                hour,
+                minute,
+                second,
+                format,
+            } => {
+                let format = match format {
+                    ClockFormat::AM => 'A',
+                    ClockFormat::PM => 'P',
+                    ClockFormat::Military => 'M',
+                };
+                write!(
+                    f,
+                    "^ST{},{},{},{},{},{},{format}",
+                    opt(month),
+                    opt(day),
+                    opt(year),
+                    opt(hour),
+                    opt(minute),
+                    opt(second)
+                )
+            }
+            ZplFormatCommand::StoreFormat { name } => write!(f, "^DF{name}"),
+            ZplFormatCommand::RecallFormat { name } => write!(f, "^XF{name}"),
+            ZplFormatCommand::FieldNumber(number) => write!(f, "^FN{number}"),
+            ZplFormatCommand::ChangePrefix { caret, tilde } => {
+                write!(f, "{}", prefix_change(('^', '~'), (*caret, *tilde)))
+            }
+            ZplFormatCommand::Unknown { code, params } => write!(f, "{code}{params}"),
+        }
+    }
+}
+
+/// optional third parameter of ^FO and ^FT
+struct FieldJustification(Option<Justification>);
+
+impl Display for FieldJustification {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            None => Ok(()),
+            Some(Justification::Left) => write!(f, ",0"),
+            Some(Justification::Right) => write!(f, ",1"),
+            Some(Justification::Auto) => write!(f, ",2"),
+        }
+    }
+}
+
+impl Display for ZplHostCommand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ZplHostCommand::CancelAllCommands => write!(f, "~JA"),
+            ZplHostCommand::CancelCurrentCommand => write!(f, "~JC"),
+            ZplHostCommand::PrintHostStatus => write!(f, "~HS"),
+            ZplHostCommand::HostIdentification => write!(f, "~HI"),
+            ZplHostCommand::DownloadGraphics {
+                name,
+                total_bytes,
+                row_bytes,
+                data,
+            } => write!(f, "~DG{name},{total_bytes},{row_bytes},{data}"),
+            ZplHostCommand::DownloadObject {
+                name,
+                format,
+                extension,
+                total_bytes,
+                row_bytes,
+                data,
+            } => {
+                let format = match format {
+                    DownloadFormat::Ascii => 'A',
+                    DownloadFormat::Binary => 'B',
+                    DownloadFormat::Compressed => 'C',
+                    DownloadFormat::Png => 'P',
+                };
+                write!(
+                    f,
+                    "~DY{name},{format},{extension},{total_bytes},{},{data}",
+                    opt(row_bytes)
+                )
+            }
+            ZplHostCommand::DownloadUnboundedFont { name, size, data } => {
+                write!(f, "~DU{name},{size},{data}")
+            }
+            ZplHostCommand::SetDarkness(darkness) => write!(f, "~SD{darkness:02}"),
+            ZplHostCommand::TearOffAdjust(adjust) => write!(f, "~TA{adjust:03}"),
+            ZplHostCommand::BackfeedSequence(sequence) => match sequence {
+                BackfeedSequence::After => write!(f, "~JSA"),
+                BackfeedSequence::Before => write!(f, "~JSB"),
+                BackfeedSequence::Normal => write!(f, "~JSN"),
+                BackfeedSequence::Off => write!(f, "~JSO"),
+                BackfeedSequence::Percent(percent) => write!(f, "~JS{percent}"),
+            },
+        }
+    }
+}
+
+impl Display for ZplCommand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ZplCommand::StartFormat => write!(f, "^XA"),
+            ZplCommand::EndFormat => write!(f, "^XZ"),
+            ZplCommand::Format(command) => write!(f, "{command}"),
+            ZplCommand::Host(command) => write!(f, "{command}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        BarcodeType, ClockFormat, ClockLanguage, ClockMode, Code128Mode, GraficData, Orientation,
+        ZplFormatCommand, parse_zpl, parse_zpl_lenient, parse_zpl_stream,
+        serialize::{crc16, to_zpl},
+    };
+
+    fn assert_round_trip(commands: Vec<ZplFormatCommand>) {
+        let zpl = to_zpl(&commands);
+        assert_eq!(parse_zpl(&zpl).unwrap(), commands, "{zpl}");
+    }
+
+    #[test]
+    fn crc16_test() {
+        assert_eq!(crc16("123456789"), 0x31C3);
+    }
+
+    #[test]
+    fn serialize_commands_test() {
+        assert_eq!(
+            ZplFormatCommand::Barcode(BarcodeType::Code128 {
+                orientation: Orientation::Rotate,
+                height: None,
+                show_text: true,
+                text_above: false,
+                check_digit: false,
+                mode: Code128Mode::Auto,
+            })
+            .to_string(),
+            "^BCR,,Y,N,N,A"
+        );
+        assert_eq!(
+            ZplFormatCommand::SetRealTimeClock {
+                month: Some(5),
+                day: None,
+                year: Some(2025),
+                hour: None,
+                minute: Some(10),
+                second: None,
+                format: ClockFormat::AM,
+            }
+            .to_string(),
+            "^ST5,,2025,,10,,A"
+        );
+        assert_eq!(
+            ZplFormatCommand::RealTimeClockMode {
+                mode: ClockMode::Now,
+                language: ClockLanguage::German,
+            }
+            .to_string(),
+            "^SLT,4"
+        );
+        assert_eq!(
+            GraficData {
+                compression_method: crate::CompressionMethod::Zlib,
+                data: "eJz".to_string(),
+            }
+            .to_string(),
+            format!(":Z64:eJz:{:04X}", crc16("eJz"))
+        );
+    }
+
+    #[test]
+    fn round_trip_parser_inputs() {
+        // inputs of the single command parser tests
+        let inputs = [
+            "^LL236^LS0",
+            "^LH30,30",
+            "^LH,50^LH30",
+            "^LS-20^LT10",
+            "^POI^LRY",
+            "^JMB^JMA",
+            "^FO10,10^B3N,Y,80,Y,N^FDCODE39^FS^FO10,10^B3B,N,,N,Y^FD123^FS",
+            "^FO10,10^BQN,2,5,H,7^FDHA,QR^FS^FO10,10^BQN,1,,L,0^FDLM,N123^FS",
+            "^FO10,10^BXN,10,200,,,6,~,1^FDDM^FS^FO10,10^BXB,,0,16,16,2,_,2^FD_1010^FS",
+            "^FO10,10^B7N,6,5,8,20,Y^FDPDF417^FS^FO10,10^B7R,,0,,,N^FDPDF417^FS",
+            "^FO10,10^BFN,8,12^FDMICRO^FS^FO10,10^BFI,,0^FDMICRO^FS",
+            "^FO10,10^B8N,60,Y,N^FD1234567^FS^FO10,10^B8B,,N,Y^FD1^FS",
+            "^FO10,10^BUN,70,Y,N,Y^FD07000002198^FS^FO10,10^B9R,,N,N,N^FD1230000045^FS",
+            "^FO10,10^BSN,137,Y,Y^FD04414^FS^FO10,10^BSI,,N,N^FD12^FS",
+            "^FO10,10^B2N,80,Y,N,Y^FD1234^FS^FO10,10^B2R,,N,Y,N^FD1^FS",
+            "^FO10,10^BJN,50,N,Y^FD1234^FS^FO10,10^BIB,40,Y,N^FD1234^FS",
+            "^FO10,10^BAN,60,Y,N,Y^FDCODE93^FS^FO10,10^BKR,Y,50,N,Y,B,D^FD123^FS",
+            "^FO10,10^B1N,Y,40,Y,N^FD12-3^FS^FO10,10^BMN,D,60,Y,N,Y^FD1234^FS",
+            "^FO10,10^BMB,A,,N,Y,N^FD1^FS^FO10,10^BPI,Y,70,Y,N^FD12AB^FS",
+            "^FO10,10^BON,4,N,0,N,1^FDAZTEC^FS^FO10,10^BOR,,Y,204,Y,3^FDAZTEC^FS",
+            "^FO10,10^BD2,1,1^FD002840100450000HELLO^FS^FO10,10^BD4,2,3^FDFREE TEXT^FS",
+            "^FO10,10^BRN,6,3,2,40,8^FD(01)98898765432106(15)991231^FS",
+            "^FO10,10^BRR,1,2,1,25,22^FD0123456789012^FS^FO10,10^BRI,11,1,2,60,22^FD(01)1|CC^FS",
+            "^FWR^FO10,10^A0,30,30^FDRotated^FS^FWI,1^FO10,10^ADB,18,10^FDBack^FS",
+            "^FO10,10^GC100,10,B^FS^FO10,10^GE300,100,10,W^FS",
+            "^FO10,10^GD200,100,5,B,L^FS^FO10,10^GD200,100,5,W,R^FS",
+            "^PON^LRN",
+            "^PW685",
+            "^CF0,60",
+            "^CF0,60,30",
+            "^A0N,21,20",
+            "^FO349,327",
+            "^FO349,327,2",
+            "^FT349,327",
+            "^FT349,327,1",
+            "^FDText^FS",
+            "^GFA,309,988,19,:Z64:eJytk7ENg0AMRQ8BAinFNenZBMpshdkgK1CnyAp4g2SEG4ESIYLjozr7LkqKmO7JenxsUxhVhWk1sthrVOE+fO+yGLtcwtWbOgT14TqHqDxcwmZH68BAiBr0uShMZhd2lSS6ZnbZXaCVczWbSEUVttMSohNdEeAZoowe2NEovocIQbyQ/YREN1GT76KXeIhduhxECH9DKdce51KL7LwLBQLvcuobHcAsJ3HBthPzynlefSWnuvHsc5HCrryhTG0ovUe97eRNRJfz4b5UJW8VNPrv3f/yp6VccVdm7jqXGd7xtuh/:E957^FS",
+            "^GB100,100,100^FS",
+            "^GB100,50,3,W,2^FS",
+            "^FR^FDTest^FS",
+            "^BY5,2,270",
+            "^BY2,2.5,10",
+            "^BCN,50,Y,N,N,A^FD12345678^FS",
+            "^BC^FD12345678^FS",
+            "^BEN,50,Y,N^FD12345678^FS",
+            "^BE^FD12345678^FS",
+            "^FH\\",
+            "^CI28",
+            "^CI0,36,21",
+            "^FB500,5",
+            "^FB500,5,1,R,1",
+            "^SLT",
+            "^SLT,4",
+            "^FC%,+",
+            "^FC%",
+            "^FC,+",
+            "^ST",
+            "^ST5,,2025,,10,,A",
+            "^DFR:LABEL.ZPL^FS^FO10,10^FN1^FS",
+            "^XFR:LABEL.ZPL^FS^FN1^FDValue^FS",
+        ];
+        for input in inputs {
+            let commands = parse_zpl(&format!("^XA{input}^XZ")).unwrap();
+            assert_round_trip(commands);
+        }
+    }
+
+    #[test]
+    fn round_trip_example_labels() {
+        // lenient, so commands not supported yet round trip as `Unknown`
+        let labels = |input: &str| -> Vec<Vec<ZplFormatCommand>> {
+            parse_zpl_lenient(input)
+                .labels
+                .into_iter()
+                .map(|label| label.into_iter().map(|c| c.node).collect())
+                .collect()
+        };
+        for file in ["render_with_images.txt", "zpl_real_live.txt", "reverse.txt"] {
+            let input = std::fs::read_to_string(format!("../zpl/examples/{file}")).unwrap();
+            let expected = labels(&input);
+            let zpl: String = expected.iter().map(|label| to_zpl(label)).collect();
+            assert_eq!(labels(&zpl), expected, "{file}");
+        }
+    }
+
+    #[test]
+    fn round_trip_prefix_change() {
+        let commands = parse_zpl("^XA^CC+\n+FO10,10+FDa^b+FS+CT#+XZ").unwrap();
+        assert_eq!(to_zpl(&commands), "^XA^CC++FO10,10+FDa^b+FS~CT#+XZ");
+        assert_round_trip(commands);
+
+        // prefixes changed before ^XA are part of the label
+        let commands = parse_zpl("~CC!!XA!FDa!FS!XZ").unwrap();
+        assert_eq!(to_zpl(&commands), "^XA^CC!!FDa!FS!XZ");
+        assert_round_trip(commands);
+    }
+
+    #[test]
+    fn round_trip_stream() {
+        let input = "~JA\n^XA~SD15^PW100^FDText^FS^XZ~DGR:A.GRF,2,1,FF00~TA-10~JS20^XA^FS^XZ";
+        let commands = parse_zpl_stream(input).unwrap();
+        let zpl: String = commands.iter().map(ToString::to_string).collect();
+        assert_eq!(parse_zpl_stream(&zpl).unwrap(), commands);
+    }
+}
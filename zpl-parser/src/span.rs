@@ -0,0 +1,126 @@
+use std::fmt;
+
+/// Position inside the parsed input.
+///
+/// `offset` is the byte offset from the start of the input, `line` and
+/// `column` are 1-based, columns are counted in characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Location {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Range of the input a command was parsed from. `end` is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: Location,
+    pub end: Location,
+}
+
+/// A parsed value together with the part of the input it was parsed from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spanned<T> {
+    pub node: T,
+    pub span: Span,
+}
+
+/// Maps byte offsets of a source string to line/column locations.
+pub(crate) struct LineIndex<'a> {
+    source: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub(crate) fn new(source: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+        Self {
+            source,
+            line_starts,
+        }
+    }
+
+    pub(crate) fn location(&self, offset: usize) -> Location {
+        let offset = offset.min(self.source.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let line_start = self.line_starts[line - 1];
+        let column = self.source[line_start..offset].chars().count() + 1;
+        Location {
+            offset,
+            line,
+            column,
+        }
+    }
+
+    /// Byte offset of `fragment` in the source, if `fragment` is a slice of it.
+    pub(crate) fn offset_of(&self, fragment: &str) -> Option<usize> {
+        let start = self.source.as_ptr() as usize;
+        let end = start + self.source.len();
+        let ptr = fragment.as_ptr() as usize;
+        (start <= ptr && ptr + fragment.len() <= end).then(|| ptr - start)
+    }
+
+    pub(crate) fn locate(&self, fragment: &str) -> Option<Location> {
+        self.offset_of(fragment).map(|offset| self.location(offset))
+    }
+
+    pub(crate) fn span(&self, fragment: &str) -> Option<Span> {
+        self.offset_of(fragment).map(|offset| Span {
+            start: self.location(offset),
+            end: self.location(offset + fragment.len()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::span::{LineIndex, Location};
+
+    #[test]
+    fn should_locate_offsets() {
+        let source = "^XA\r\n^FO10,10\n^FDä^FS^XZ";
+        let index = LineIndex::new(source);
+        assert_eq!(
+            index.location(0),
+            Location {
+                offset: 0,
+                line: 1,
+                column: 1
+            }
+        );
+        assert_eq!(
+            index.location(5),
+            Location {
+                offset: 5,
+                line: 2,
+                column: 1
+            }
+        );
+        // 'ä' is two bytes but one column
+        let fs = source.find("^FS").unwrap();
+        assert_eq!(
+            index.location(fs),
+            Location {
+                offset: fs,
+                line: 3,
+                column: 5
+            }
+        );
+    }
+
+    #[test]
+    fn should_not_locate_foreign_fragment() {
+        let source = "^XA^XZ";
+        let index = LineIndex::new(source);
+        assert_eq!(index.locate("other"), None);
+        assert_eq!(index.locate(&source[3..]).map(|l| l.offset), Some(3));
+    }
+}
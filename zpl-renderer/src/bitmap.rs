@@ -24,13 +24,13 @@ impl BitMap {
 impl Drawable for BitMap {
     fn draw(&self, target: &mut tiny_skia::Pixmap) -> Result<(), Box<dyn std::error::Error>> {
         // Create a pixmap with the bitmap content
-        let mut bitmap_pixmap = Pixmap::new(self.width, self.height).unwrap();
+        let mut bitmap_pixmap = Pixmap::new(self.width, self.height).ok_or("Empty bitmap")?;
 
         // Fill with black (or whatever color you want for the "1" pixels)
         bitmap_pixmap.fill(Color::BLACK);
 
         // Create and apply mask (0 = transparent, 255 = opaque)
-        let mut mask = Mask::new(self.width, self.height).unwrap();
+        let mut mask = Mask::new(self.width, self.height).ok_or("Empty bitmap")?;
         for (i, &pixel) in self.pixels.iter().enumerate() {
             // ZPL: 0 = white (transparent), 1 = black (opaque)
             mask.data_mut()[i] = if pixel == 1 { 255 } else { 0 };
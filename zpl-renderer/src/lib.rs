@@ -1,17 +1,19 @@
 mod bitmap;
+mod rotated;
 mod shapes;
 mod text;
 
 use std::{collections::HashMap, error::Error};
 
 use fontdue::Font;
-use tiny_skia::{Color, Pixmap};
+use tiny_skia::{Color, Pixmap, PixmapPaint, Transform};
 use zpl_interpreter::{ZplElement, ZplLabel};
-use zpl_parser::{Color as ZplColor, Justification};
+use zpl_parser::{Color as ZplColor, Justification, Orientation};
 
 use crate::{
     bitmap::BitMap,
-    shapes::{RectDim, Rectangle},
+    rotated::Rotated,
+    shapes::{DiagonalLine, Ellipse, RectDim, Rectangle},
     text::{FontConfig, Text},
 };
 
@@ -23,13 +25,14 @@ pub struct Position {
 
 pub(crate) trait Drawable {
     fn draw(&self, target: &mut Pixmap) -> Result<(), Box<dyn Error>>;
-    fn draw_inverted(&self, target: &mut Pixmap) {
+    fn draw_inverted(&self, target: &mut Pixmap) -> Result<(), Box<dyn Error>> {
         // 1. Render field to mask
         let mut mask = Pixmap::new(target.width(), target.height()).unwrap();
-        self.draw(&mut mask).unwrap();
+        self.draw(&mut mask)?;
 
         // 2. Apply reverse print against destination
         self.invert_field(target, &mask);
+        Ok(())
     }
 
     fn invert_field(&self, target: &mut Pixmap, mask: &Pixmap) {
@@ -67,14 +70,74 @@ pub(crate) trait Drawable {
     }
 }
 
+/// Draw a field, reverse printed if `inverted`. A field that cannot be
+/// drawn, like an empty bitmap, is left out and reported in `warnings`.
+fn draw_field(
+    field: &impl Drawable,
+    target: &mut Pixmap,
+    inverted: bool,
+    warnings: &mut Vec<String>,
+) {
+    let drawn = match inverted {
+        true => field.draw_inverted(target),
+        false => field.draw(target),
+    };
+    if let Err(err) = drawn {
+        warnings.push(format!("field left out: {err}"));
+    }
+}
+
+/// Human readable line of a barcode on a white background, `position` is the
+/// top left (or top center) of the text
+fn barcode_line(
+    content: &str,
+    justification: Justification,
+    font_config: FontConfig,
+    position: Position,
+) -> (Rectangle, Text) {
+    let font_height = font_config.font_height();
+    let text = Text::new(content.to_string(), font_config.clone(), position, None);
+
+    let rect_width = text.width() as f32;
+    let rect_height = font_height * 1.2;
+    let line_thickness = rect_height.min(rect_width) - 0.1;
+    let dim = RectDim::new(rect_width, rect_height, line_thickness);
+    let x = match justification {
+        Justification::Left => position.x,
+        Justification::Right => todo!(),
+        Justification::Auto => position.x.saturating_sub(rect_width as usize / 2),
+    };
+    let rect_pos = Position::new(x, (position.y as f32 - rect_height / 7.) as usize);
+    let rect = Rectangle::new(rect_pos, dim, ZplColor::White);
+
+    // the text starts where its background starts
+    let text = Text::new(
+        content.to_string(),
+        font_config,
+        Position::new(x, position.y),
+        None,
+    );
+    (rect, text)
+}
+
 pub struct RenderOutput {
     pub png: Vec<u8>,
+    /// fields left out of the image, see [`ZplLabel::warnings`]
+    pub warnings: Vec<String>,
 }
 
 pub fn render(label: &ZplLabel) -> RenderOutput {
-    // Create a pixmap
-    let width = label.width as u32;
-    let height = label.height as u32;
+    let (pixmap, warnings) = render_pixmap(label);
+    let png = pixmap.encode_png().expect("encode png");
+    RenderOutput { png, warnings }
+}
+
+fn render_pixmap(label: &ZplLabel) -> (Pixmap, Vec<String>) {
+    let mut warnings = label.warnings.clone();
+
+    // Create a pixmap, large enough to hold content shifted out of the label
+    let width = label.width as u32 + (-label.offset_x).max(0) as u32;
+    let height = label.height as u32 + (-label.offset_y).max(0) as u32;
     let mut pixmap = Pixmap::new(width, height).expect("Failed to create pixmap");
 
     // White background
@@ -84,18 +147,15 @@ pub fn render(label: &ZplLabel) -> RenderOutput {
     let mut font_data = HashMap::new();
     let adwaita: &'static [u8] = include_bytes!("../../fonts/Oswald/Oswald-Medium.ttf");
     let font = Font::from_bytes(adwaita as &[u8], fontdue::FontSettings::default()).unwrap();
-    let scale = 0.85;
-    font_data.insert('0', (font, scale));
+    font_data.insert('0', font);
 
     let ocrb: &'static [u8] = include_bytes!("../../fonts/AdwaitaMono/AdwaitaMono-Regular.ttf");
     let font = Font::from_bytes(ocrb as &[u8], fontdue::FontSettings::default()).unwrap();
-    let scale = 1.0;
-    font_data.insert('A', (font, scale));
+    font_data.insert('A', font);
 
     let ocrb: &'static [u8] = include_bytes!("../../fonts/OCRB/OCR-B.ttf");
     let font = Font::from_bytes(ocrb as &[u8], fontdue::FontSettings::default()).unwrap();
-    let scale = 1.0;
-    font_data.insert(';', (font, scale));
+    font_data.insert(';', font);
 
     for el in &label.elements {
         match el {
@@ -103,29 +163,27 @@ pub fn render(label: &ZplLabel) -> RenderOutput {
                 x,
                 y,
                 font_name,
-                font_width,
                 font_height,
                 content,
-                justification,
                 inverted,
                 field_block,
+                orientation,
+                typeset,
+                ..
             } => {
-                let position = Position::new(*x as usize, *y as usize);
-                let (font, scale) = font_data.get(font_name).unwrap().clone();
-                let font_config = FontConfig::new(font, *font_width, *font_height, scale, false);
+                let position = Position::new(*x, *y);
+                let font = font_data.get(font_name).unwrap().clone();
+                let font_config = FontConfig::new(font, *font_height);
                 let field_box = field_block.as_ref().map(|fb| fb.into());
-                let text = Text::new(
-                    content.clone(),
-                    font_config,
-                    position,
-                    *justification,
-                    field_box,
+                let text = Text::new(content.clone(), font_config, position, field_box)
+                    .with_orientation(*orientation, *typeset);
+                // ^FR on a ^LRY label prints the field normal again
+                draw_field(
+                    &text,
+                    &mut pixmap,
+                    *inverted != label.reverse,
+                    &mut warnings,
                 );
-                if *inverted {
-                    text.draw_inverted(&mut pixmap);
-                } else {
-                    text.draw(&mut pixmap).unwrap();
-                }
             }
             ZplElement::Rectangle {
                 x,
@@ -134,23 +192,91 @@ pub fn render(label: &ZplLabel) -> RenderOutput {
                 height,
                 thickness,
                 color,
-                rounding,
                 inverted,
+                ..
             } => {
-                let position = Position::new(*x as usize, *y as usize);
-                let dim = RectDim::new(*width as f32, *height as f32, *thickness as f32, *rounding);
+                let position = Position::new(*x, *y);
+                let dim = RectDim::new(*width as f32, *height as f32, *thickness as f32);
                 let rectangle = Rectangle::new(position, dim, *color);
-                if *inverted {
-                    rectangle.draw_inverted(&mut pixmap);
-                } else {
-                    rectangle.draw(&mut pixmap).unwrap();
-                }
+                draw_field(
+                    &rectangle,
+                    &mut pixmap,
+                    *inverted != label.reverse,
+                    &mut warnings,
+                );
+            }
+            ZplElement::Circle {
+                x,
+                y,
+                diameter,
+                thickness,
+                color,
+                inverted,
+            } => {
+                let position = Position::new(*x, *y);
+                let circle = Ellipse::circle(position, *diameter as f32, *thickness as f32, *color);
+                draw_field(
+                    &circle,
+                    &mut pixmap,
+                    *inverted != label.reverse,
+                    &mut warnings,
+                );
+            }
+            ZplElement::Ellipse {
+                x,
+                y,
+                width,
+                height,
+                thickness,
+                color,
+                inverted,
+            } => {
+                let position = Position::new(*x, *y);
+                let ellipse = Ellipse::new(
+                    position,
+                    *width as f32,
+                    *height as f32,
+                    *thickness as f32,
+                    *color,
+                );
+                draw_field(
+                    &ellipse,
+                    &mut pixmap,
+                    *inverted != label.reverse,
+                    &mut warnings,
+                );
+            }
+            ZplElement::DiagonalLine {
+                x,
+                y,
+                width,
+                height,
+                thickness,
+                color,
+                orientation,
+                inverted,
+            } => {
+                let position = Position::new(*x, *y);
+                let line = DiagonalLine::new(
+                    position,
+                    *width as f32,
+                    *height as f32,
+                    *thickness as f32,
+                    *color,
+                    *orientation,
+                );
+                draw_field(
+                    &line,
+                    &mut pixmap,
+                    *inverted != label.reverse,
+                    &mut warnings,
+                );
             }
             ZplElement::Image { x, y, bmp } => {
                 let position = Position::new(*x, *y);
                 let pixels = bmp.pixels.clone();
                 let bitmap = BitMap::new(position, bmp.width as u32, bmp.height as u32, pixels);
-                bitmap.draw(&mut pixmap).unwrap();
+                draw_field(&bitmap, &mut pixmap, label.reverse, &mut warnings);
             }
             ZplElement::Barcode { x, y, content } => {
                 let position = Position::new(*x, *y);
@@ -158,46 +284,278 @@ pub fn render(label: &ZplLabel) -> RenderOutput {
                 let pixels = bitmap.pixels.clone();
                 let bitmap =
                     BitMap::new(position, bitmap.width as u32, bitmap.height as u32, pixels);
-                bitmap.draw(&mut pixmap).unwrap();
+                draw_field(&bitmap, &mut pixmap, label.reverse, &mut warnings);
 
                 for text_element in content.text_elements() {
                     let font_width = content.font_width;
                     let font_height = font_width;
-                    let (font, scale) = font_data.get(&';').unwrap().clone();
-                    let font_config = FontConfig::new(font, font_width, font_height, scale, false);
-                    let position =
-                        Position::new(text_element.text_x as usize, text_element.text_y as usize);
+                    let font = font_data.get(&';').unwrap().clone();
+                    let font_config = FontConfig::new(font, font_height);
+
+                    if text_element.orientation == Orientation::Normal {
+                        // lines left of or above the label start at its edge
+                        let position = Position::new(
+                            text_element.text_x.max(0) as usize,
+                            text_element.text_y.max(0) as usize,
+                        );
+                        let (rect, text) = barcode_line(
+                            &text_element.text,
+                            text_element.justification,
+                            font_config,
+                            position,
+                        );
+                        draw_field(&rect, &mut pixmap, label.reverse, &mut warnings);
+                        draw_field(&text, &mut pixmap, label.reverse, &mut warnings);
+                        continue;
+                    }
+
+                    // lay out the line with enough margin around the anchor
+                    // for any justification, then rotate it into place
                     let text = Text::new(
                         text_element.text.clone(),
-                        font_config,
-                        position,
-                        text_element.justification,
+                        font_config.clone(),
+                        Position::new(0, 0),
                         None,
                     );
+                    let margin = text.width() + (font_height * 2.) as usize;
+                    let anchor = Position::new(margin, margin);
+                    let (rect, text) = barcode_line(
+                        &text_element.text,
+                        text_element.justification,
+                        font_config,
+                        anchor,
+                    );
+                    let line = Rotated::new(
+                        vec![&rect, &text],
+                        (2 * margin as u32, 2 * margin as u32),
+                        (anchor.x as f32, anchor.y as f32),
+                        (text_element.text_x as f32, text_element.text_y as f32),
+                        text_element.orientation,
+                    );
+                    draw_field(&line, &mut pixmap, label.reverse, &mut warnings);
+                }
+            }
+        }
+    }
 
-                    let rect_width = text.width() as f32;
-                    let rect_height = font_height * 1.2;
-                    let line_thickness = rect_height.min(rect_width) - 0.1;
-                    let dim = RectDim::new(rect_width, rect_height, line_thickness, 0);
-                    let rect_pos = {
-                        let mut y = position.y as f32;
-                        y = y - rect_height / 7.;
-                        let x = match text_element.justification {
-                            Justification::Left => position.x,
-                            Justification::Right => todo!(),
-                            Justification::Auto => position.x - rect_width as usize / 2,
-                        };
-                        Position::new(x, y as usize)
-                    };
-                    let rect = Rectangle::new(rect_pos, dim, ZplColor::White);
+    if label.offset_x != 0 || label.offset_y != 0 {
+        // apply ^LS and ^LT, everything outside of the label is clipped
+        let mut shifted =
+            Pixmap::new(label.width as u32, label.height as u32).expect("Failed to create pixmap");
+        shifted.fill(Color::WHITE);
+        shifted.draw_pixmap(
+            label.offset_x as i32,
+            label.offset_y as i32,
+            pixmap.as_ref(),
+            &PixmapPaint::default(),
+            Transform::identity(),
+            None,
+        );
+        pixmap = shifted;
+    }
+
+    // ^POI, reversing the pixel order rotates the label by 180°
+    if label.inverted {
+        pixmap.pixels_mut().reverse();
+    }
+
+    (pixmap, warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use tiny_skia::{Color, Pixmap};
+    use zpl_interpreter::{ZplElement, interpret};
+    use zpl_parser::parse_zpl;
+
+    use crate::{Position, bitmap::BitMap, draw_field, render_pixmap};
+
+    fn is_black(pixmap: &Pixmap, x: u32, y: u32) -> bool {
+        pixmap.pixel(x, y).unwrap() == Color::BLACK.premultiply().to_color_u8()
+    }
+
+    #[test]
+    fn should_shift_and_clip_label() {
+        // negative ^LS moves right, the box is clipped at the right edge
+        let input = "^XA^PW40^LL40^LS-35^LT-5^FO0,10^GB10,10,10^FS^XZ";
+        let pixmap = render_pixmap(&interpret(&parse_zpl(input).unwrap())).0;
+        assert_eq!((pixmap.width(), pixmap.height()), (40, 40));
+        assert!(!is_black(&pixmap, 34, 5));
+        assert!(is_black(&pixmap, 35, 5));
+        assert!(is_black(&pixmap, 39, 14));
+        assert!(!is_black(&pixmap, 39, 15));
+
+        // positive ^LS moves left, content left of the label is cut off
+        let input = "^XA^PW40^LL40^LS5^FO0,0^GB10,10,10^FS^XZ";
+        let pixmap = render_pixmap(&interpret(&parse_zpl(input).unwrap())).0;
+        assert!(is_black(&pixmap, 0, 0));
+        assert!(is_black(&pixmap, 4, 0));
+        assert!(!is_black(&pixmap, 5, 0));
+    }
 
-                    rect.draw(&mut pixmap).unwrap();
-                    text.draw(&mut pixmap).unwrap();
+    #[test]
+    fn should_draw_barcode_text_near_label_edge() {
+        // the leading digit of UPC and EAN is drawn left of the bars, near
+        // the edge the barcode moves in so its background stays off the bars
+        for input in [
+            "^XA^FO10,10^BUN,50^FD03600029145^FS^XZ",
+            "^XA^FO10,10^B9N,50^FD1^FS^XZ",
+            "^XA^FO10,10^BEN,50^FD12345678^FS^XZ",
+            "^XA^FO0,0^BEN,50^FD12345678^FS^XZ",
+        ] {
+            let pixmap = render_pixmap(&interpret(&parse_zpl(input).unwrap())).0;
+            let (_, top, _, _) = ink_bounds(&pixmap);
+            let guard = (0..pixmap.width())
+                .find(|x| is_black(&pixmap, *x, top))
+                .unwrap();
+            assert!(
+                (top..top + 50).all(|y| is_black(&pixmap, guard, y)),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn should_draw_barcodes_with_zero_module_width() {
+        // ^BY0 is raised to the smallest module width of one dot
+        for input in [
+            "^XA^BY0^FO10,10^BCN,50,N^FD123^FS^XZ",
+            "^XA^BY0^FO10,10^B3N,N,50,N^FD123^FS^XZ",
+            "^XA^BY0^FO10,10^B2N,50,N^FD1234^FS^XZ",
+        ] {
+            let pixmap = render_pixmap(&interpret(&parse_zpl(input).unwrap())).0;
+            assert!(is_black(&pixmap, 10, 30), "{input}");
+        }
+    }
+
+    #[test]
+    fn should_skip_empty_bitmaps() {
+        let mut pixmap = Pixmap::new(10, 10).unwrap();
+        let mut warnings = Vec::new();
+        let bitmap = BitMap::new(Position::new(0, 0), 0, 10, vec![]);
+        draw_field(&bitmap, &mut pixmap, false, &mut warnings);
+        draw_field(&bitmap, &mut pixmap, true, &mut warnings);
+        assert_eq!(warnings, ["field left out: Empty bitmap"; 2]);
+    }
+
+    #[test]
+    fn should_draw_circle_and_ellipse() {
+        let input = "^XA^PW100^LL100^FO0,0^GC40,5^FS^FO50,0^GE50,20,10^FS^XZ";
+        let pixmap = render_pixmap(&interpret(&parse_zpl(input).unwrap())).0;
+        assert!(is_black(&pixmap, 2, 20));
+        assert!(!is_black(&pixmap, 20, 20));
+        assert!(!is_black(&pixmap, 0, 0));
+        // the thickness reaches the center, the ellipse is filled
+        assert!(is_black(&pixmap, 75, 10));
+        assert!(!is_black(&pixmap, 50, 0));
+
+        // a reversed filled circle on a box leaves a white hole
+        let input = "^XA^PW40^LL40^FO0,0^GB40,40,40^FS^FO0,0^FR^GC40,20^FS^XZ";
+        let pixmap = render_pixmap(&interpret(&parse_zpl(input).unwrap())).0;
+        assert!(!is_black(&pixmap, 20, 20));
+        assert!(is_black(&pixmap, 1, 1));
+    }
+
+    #[test]
+    fn should_draw_diagonal_lines() {
+        let input = "^XA^PW40^LL20^FO0,0^GD40,20,4,,R^FS^XZ";
+        let pixmap = render_pixmap(&interpret(&parse_zpl(input).unwrap())).0;
+        assert!(is_black(&pixmap, 38, 0));
+        assert!(is_black(&pixmap, 1, 19));
+        assert!(is_black(&pixmap, 19, 10));
+        assert!(!is_black(&pixmap, 1, 0));
+        assert!(!is_black(&pixmap, 38, 19));
+
+        let input = "^XA^PW40^LL20^FO0,0^GD40,20,4,,L^FS^XZ";
+        let pixmap = render_pixmap(&interpret(&parse_zpl(input).unwrap())).0;
+        assert!(is_black(&pixmap, 1, 0));
+        assert!(is_black(&pixmap, 38, 19));
+        assert!(!is_black(&pixmap, 38, 0));
+    }
+
+    /// bounding box (left, top, right, bottom) of everything not white
+    fn ink_bounds(pixmap: &Pixmap) -> (u32, u32, u32, u32) {
+        let white = Color::WHITE.premultiply().to_color_u8();
+        let mut bounds = (u32::MAX, u32::MAX, 0, 0);
+        for y in 0..pixmap.height() {
+            for x in 0..pixmap.width() {
+                if pixmap.pixel(x, y).unwrap() != white {
+                    bounds.0 = bounds.0.min(x);
+                    bounds.1 = bounds.1.min(y);
+                    bounds.2 = bounds.2.max(x);
+                    bounds.3 = bounds.3.max(y);
                 }
             }
         }
+        bounds
     }
 
-    let png = pixmap.encode_png().expect("encode png");
-    RenderOutput { png }
+    #[test]
+    fn should_rotate_text() {
+        // ^FO places the top left corner of the rotated text
+        let input = "^XA^PW300^LL300^FO50,60^A0R,40,40^FDHHHH^FS^XZ";
+        let pixmap = render_pixmap(&interpret(&parse_zpl(input).unwrap())).0;
+        let (left, top, right, bottom) = ink_bounds(&pixmap);
+        assert!(left >= 50 && right <= 90, "{left} {right}");
+        assert!(top >= 60 && bottom - top > right - left, "{top} {bottom}");
+
+        // ^FT with ^FW default, bottom up text ends at the anchor
+        let input = "^XA^PW300^LL300^FWB^FT100,200^A0,40,40^FDHHHH^FS^XZ";
+        let pixmap = render_pixmap(&interpret(&parse_zpl(input).unwrap())).0;
+        let (left, top, right, bottom) = ink_bounds(&pixmap);
+        assert!(left >= 60 && right <= 100, "{left} {right}");
+        assert!(
+            bottom <= 200 && bottom - top > right - left,
+            "{top} {bottom}"
+        );
+
+        // upside down text is left of and below the ^FT anchor
+        let input = "^XA^PW300^LL300^FT200,100^A0I,40,40^FDHHHH^FS^XZ";
+        let pixmap = render_pixmap(&interpret(&parse_zpl(input).unwrap())).0;
+        let (left, top, right, bottom) = ink_bounds(&pixmap);
+        assert!(right <= 200 && left < 200, "{left} {right}");
+        assert!(top >= 100 && bottom <= 140, "{top} {bottom}");
+    }
+
+    #[test]
+    fn should_rotate_barcode_with_text() {
+        let input = "^XA^PW300^LL300^BY2^FO100,10^BCB,60,Y,N,N^FD1234^FS^XZ";
+        let label = interpret(&parse_zpl(input).unwrap());
+        let ZplElement::Barcode { content, .. } = &label.elements[0] else {
+            panic!("expected barcode");
+        };
+        let pixmap = render_pixmap(&label).0;
+        let (left, top, right, bottom) = ink_bounds(&pixmap);
+
+        // bars left of the human readable line, everything inside the field
+        assert_eq!(left, 100);
+        assert!(right > 160 && right < 100 + content.bitmap.width as u32);
+        assert!(top >= 10 && bottom < 10 + content.bitmap.height as u32);
+    }
+
+    #[test]
+    fn should_rotate_inverted_label() {
+        let input = "^XA^PW40^LL30^POI^FO0,0^GB10,10,10^FS^XZ";
+        let pixmap = render_pixmap(&interpret(&parse_zpl(input).unwrap())).0;
+        assert!(!is_black(&pixmap, 0, 0));
+        assert!(is_black(&pixmap, 39, 29));
+        assert!(is_black(&pixmap, 30, 20));
+        assert!(!is_black(&pixmap, 29, 20));
+        assert!(!is_black(&pixmap, 30, 19));
+    }
+
+    #[test]
+    fn should_reverse_all_fields() {
+        // the overlapping part of both boxes is printed white
+        let input = "^XA^PW40^LL40^LRY^FO0,0^GB20,20,20^FS^FO10,10^GB20,20,20^FS^XZ";
+        let pixmap = render_pixmap(&interpret(&parse_zpl(input).unwrap())).0;
+        assert!(is_black(&pixmap, 5, 5));
+        assert!(!is_black(&pixmap, 15, 15));
+        assert!(is_black(&pixmap, 25, 25));
+
+        // ^FR reverts a field of a ^LRY label to normal printing
+        let input = "^XA^PW40^LL40^LRY^FO0,0^GB20,20,20^FS^FO10,10^FR^GB20,20,20^FS^XZ";
+        let pixmap = render_pixmap(&interpret(&parse_zpl(input).unwrap())).0;
+        assert!(is_black(&pixmap, 15, 15));
+    }
 }
@@ -0,0 +1,70 @@
+use std::error::Error;
+
+use tiny_skia::{Pixmap, PixmapPaint, Transform};
+use zpl_parser::Orientation;
+
+use crate::Drawable;
+
+/// Rotation of a field in `orientation`, clockwise around the origin
+pub(crate) fn orientation_transform(orientation: Orientation) -> Transform {
+    match orientation {
+        Orientation::Normal => Transform::identity(),
+        Orientation::Rotate => Transform::from_row(0., 1., -1., 0., 0., 0.),
+        Orientation::Invert => Transform::from_row(-1., 0., 0., -1., 0., 0.),
+        Orientation::BackRotate => Transform::from_row(0., -1., 1., 0., 0., 0.),
+    }
+}
+
+/// Drawables laid out unrotated on their own canvas, drawn rotated around
+/// `anchor` (a point on that canvas) which ends up at `position`.
+pub(crate) struct Rotated<'a> {
+    drawables: Vec<&'a dyn Drawable>,
+    width: u32,
+    height: u32,
+    anchor: (f32, f32),
+    position: (f32, f32),
+    orientation: Orientation,
+}
+
+impl<'a> Rotated<'a> {
+    pub(crate) fn new(
+        drawables: Vec<&'a dyn Drawable>,
+        (width, height): (u32, u32),
+        anchor: (f32, f32),
+        position: (f32, f32),
+        orientation: Orientation,
+    ) -> Self {
+        Self {
+            drawables,
+            width,
+            height,
+            anchor,
+            position,
+            orientation,
+        }
+    }
+}
+
+impl Drawable for Rotated<'_> {
+    fn draw(&self, target: &mut Pixmap) -> Result<(), Box<dyn Error>> {
+        let mut canvas = Pixmap::new(self.width, self.height).ok_or("Invalid size")?;
+        for drawable in &self.drawables {
+            drawable.draw(&mut canvas)?;
+        }
+
+        let (x, y) = self.position;
+        let transform = orientation_transform(self.orientation)
+            .post_translate(x, y)
+            .pre_translate(-self.anchor.0, -self.anchor.1);
+
+        target.draw_pixmap(
+            0,
+            0,
+            canvas.as_ref(),
+            &PixmapPaint::default(),
+            transform,
+            None,
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use tiny_skia::{FillRule, PathBuilder, Transform};
+use zpl_parser::{Color, DiagonalOrientation};
+
+use super::paint;
+use crate::{Drawable, Position};
+
+pub(crate) struct DiagonalLine {
+    position: Position,
+    width: f32,
+    height: f32,
+    line_thickness: f32,
+    color: Color,
+    orientation: DiagonalOrientation,
+}
+
+impl DiagonalLine {
+    pub(crate) fn new(
+        position: Position,
+        width: f32,
+        height: f32,
+        line_thickness: f32,
+        color: Color,
+        orientation: DiagonalOrientation,
+    ) -> Self {
+        Self {
+            position,
+            width,
+            height,
+            line_thickness,
+            color,
+            orientation,
+        }
+    }
+}
+
+impl Drawable for DiagonalLine {
+    fn draw(&self, target: &mut tiny_skia::Pixmap) -> Result<(), Box<dyn std::error::Error>> {
+        let left = self.position.x as f32;
+        let right = left + self.width;
+        let top = self.position.y as f32;
+        let bottom = top + self.height;
+        let thickness = self.line_thickness.min(self.width);
+
+        // the thickness is measured horizontally, the line is a
+        // parallelogram between the top and bottom edge of the box
+        let (top_x, bottom_x) = match self.orientation {
+            DiagonalOrientation::Right => (right - thickness, left),
+            DiagonalOrientation::Left => (left, right - thickness),
+        };
+
+        let mut pb = PathBuilder::new();
+        pb.move_to(top_x, top);
+        pb.line_to(top_x + thickness, top);
+        pb.line_to(bottom_x + thickness, bottom);
+        pb.line_to(bottom_x, bottom);
+        pb.close();
+        let path = pb.finish().ok_or("invalid diagonal line size")?;
+
+        // printers have no gray dots, keep the slanted edges crisp
+        let mut paint = paint(self.color);
+        paint.anti_alias = false;
+        target.fill_path(
+            &path,
+            &paint,
+            FillRule::Winding,
+            Transform::identity(),
+            None,
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use tiny_skia::{FillRule, PathBuilder, Rect, Stroke, Transform};
+use zpl_parser::Color;
+
+use super::paint;
+use crate::{Drawable, Position};
+
+pub(crate) struct Ellipse {
+    position: Position,
+    width: f32,
+    height: f32,
+    line_thickness: f32,
+    color: Color,
+}
+
+impl Ellipse {
+    pub(crate) fn new(
+        position: Position,
+        width: f32,
+        height: f32,
+        line_thickness: f32,
+        color: Color,
+    ) -> Self {
+        Self {
+            position,
+            width,
+            height,
+            line_thickness,
+            color,
+        }
+    }
+
+    /// ^GC, an ellipse with the same width and height
+    pub(crate) fn circle(
+        position: Position,
+        diameter: f32,
+        line_thickness: f32,
+        color: Color,
+    ) -> Self {
+        Self::new(position, diameter, diameter, line_thickness, color)
+    }
+}
+
+impl Drawable for Ellipse {
+    fn draw(&self, target: &mut tiny_skia::Pixmap) -> Result<(), Box<dyn std::error::Error>> {
+        let rect = Rect::from_xywh(
+            self.position.x as f32,
+            self.position.y as f32,
+            self.width,
+            self.height,
+        )
+        .ok_or("invalid ellipse size")?;
+        let paint = paint(self.color);
+
+        // the line is drawn inside the bounds, a thickness reaching the
+        // center fills the whole ellipse
+        let inset = self.line_thickness / 2.0;
+        if inset >= self.width / 2. || inset >= self.height / 2. {
+            let path = PathBuilder::from_oval(rect).ok_or("invalid ellipse size")?;
+            target.fill_path(
+                &path,
+                &paint,
+                FillRule::Winding,
+                Transform::identity(),
+                None,
+            );
+            return Ok(());
+        }
+
+        let rect = rect.inset(inset, inset).ok_or("invalid ellipse size")?;
+        let path = PathBuilder::from_oval(rect).ok_or("invalid ellipse size")?;
+        let stroke = Stroke {
+            width: self.line_thickness,
+            ..Default::default()
+        };
+        target.stroke_path(&path, &paint, &stroke, Transform::identity(), None);
+
+        Ok(())
+    }
+}
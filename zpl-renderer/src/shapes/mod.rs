@@ -1,3 +1,18 @@
+mod diagonal_line;
+mod ellipse;
 mod rectangle;
 
-pub use rectangle::*;
+pub(crate) use diagonal_line::*;
+pub(crate) use ellipse::*;
+pub(crate) use rectangle::*;
+use tiny_skia::Paint;
+use zpl_parser::Color;
+
+fn paint(color: Color) -> Paint<'static> {
+    let mut paint = Paint::default();
+    match color {
+        Color::Black => paint.set_color_rgba8(0, 0, 0, 255),
+        Color::White => paint.set_color_rgba8(255, 255, 255, 255),
+    }
+    paint
+}
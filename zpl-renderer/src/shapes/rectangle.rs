@@ -1,22 +1,21 @@
-use tiny_skia::{Paint, PathBuilder, Rect, Stroke, Transform};
+use tiny_skia::{PathBuilder, Rect, Stroke, Transform};
 use zpl_parser::Color;
 
+use super::paint;
 use crate::{Drawable, Position};
 
 pub(crate) struct RectDim {
     width: f32,
     height: f32,
     line_thickness: f32,
-    rounding: u8,
 }
 
 impl RectDim {
-    pub(crate) fn new(width: f32, height: f32, line_thickness: f32, rounding: u8) -> Self {
+    pub(crate) fn new(width: f32, height: f32, line_thickness: f32) -> Self {
         Self {
             width,
             height,
             line_thickness,
-            rounding,
         }
     }
 }
@@ -63,14 +62,12 @@ impl Drawable for Rectangle {
         pb.push_rect(rect);
         let path = pb.finish().unwrap();
 
-        let mut paint = Paint::default();
-        match self.color {
-            Color::Black => paint.set_color_rgba8(0, 0, 0, 255),
-            Color::White => paint.set_color_rgba8(255, 255, 255, 255),
-        }
+        let paint = paint(self.color);
 
-        let mut stroke = Stroke::default();
-        stroke.width = self.dim.line_thickness;
+        let stroke = Stroke {
+            width: self.dim.line_thickness,
+            ..Default::default()
+        };
 
         target.stroke_path(&path, &paint, &stroke, Transform::identity(), None);
 
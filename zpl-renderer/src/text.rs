@@ -4,9 +4,9 @@ use std::{error::Error, fmt::Debug};
 use fontdue::Font;
 use tiny_skia::{IntSize, Pixmap, PixmapPaint, Transform};
 use zpl_interpreter::FieldBlock;
-use zpl_parser::{Justification, TextBlockJustification};
+use zpl_parser::{Orientation, TextBlockJustification};
 
-use crate::{Drawable, Position};
+use crate::{Drawable, Position, rotated::orientation_transform};
 
 impl Position {
     pub(crate) fn new(x: usize, y: usize) -> Self {
@@ -17,27 +17,16 @@ impl Position {
 #[derive(Debug, Clone)]
 pub struct FontConfig {
     font: Font,
-    font_width: f32,
     font_height: f32,
-    bold: bool,
-    scale: f32,
 }
 
 impl FontConfig {
-    pub(crate) fn new(
-        font: Font,
-        font_width: f32,
-        font_height: f32,
-        scale: f32,
-        bold: bool,
-    ) -> Self {
-        Self {
-            font,
-            font_width,
-            font_height,
-            bold,
-            scale,
-        }
+    pub(crate) fn new(font: Font, font_height: f32) -> Self {
+        Self { font, font_height }
+    }
+
+    pub(crate) fn font_height(&self) -> f32 {
+        self.font_height
     }
 }
 
@@ -96,7 +85,7 @@ impl Glyph {
     }
 
     fn position_next_to(&mut self, previous: &Self) {
-        self.x = (previous.x + previous.advance_width.round() as usize) as usize;
+        self.x = previous.x + previous.advance_width.round() as usize;
         let height_diff = previous.height as isize - self.height as isize;
         let ymin_diff = previous.ymin - self.ymin;
         self.y = (previous.y as isize + height_diff + ymin_diff) as usize;
@@ -152,6 +141,7 @@ pub struct FieldBox {
 }
 
 impl FieldBox {
+    #[cfg(test)]
     fn new(
         width: usize,
         lines: usize,
@@ -186,8 +176,9 @@ pub struct Text {
     content: String,
     font_config: FontConfig,
     position: Position,
-    justification: Justification,
     field_box: Option<FieldBox>,
+    orientation: Orientation,
+    typeset: bool,
 }
 
 /// Line → word → glyph. Each inner Vec<Glyph> is one word (including its
@@ -200,18 +191,27 @@ impl Text {
         content: String,
         font_config: FontConfig,
         position: Position,
-        justification: Justification,
         field_box: Option<FieldBox>,
     ) -> Self {
         Self {
             content,
             font_config,
             position,
-            justification,
             field_box,
+            orientation: Orientation::Normal,
+            typeset: false,
         }
     }
 
+    /// Rotate the text, `position` stays the top left corner of the unrotated
+    /// text. With `typeset` (^FT) the rotation is around the start of the
+    /// baseline, otherwise (^FO) the rotated text starts at `position`.
+    pub(crate) fn with_orientation(mut self, orientation: Orientation, typeset: bool) -> Self {
+        self.orientation = orientation;
+        self.typeset = typeset;
+        self
+    }
+
     fn right_field_bound(&self) -> Option<usize> {
         self.field_box.as_ref().map(|b| self.position.x + b.width)
     }
@@ -251,7 +251,7 @@ impl Text {
 
             // Space terminates the current word.
             if ch == ' ' {
-                let completed: Vec<Glyph> = word.drain(..).collect();
+                let completed: Vec<Glyph> = std::mem::take(&mut word);
                 if word_was_wrapped {
                     lines.push(vec![completed]);
                 } else {
@@ -279,9 +279,9 @@ impl Text {
     fn check_word_wrap(&self, word: &mut Word) -> bool {
         let last = word.last().expect("check_word_wrap called on empty word");
 
-        if !self
+        if self
             .right_field_bound()
-            .is_some_and(|rb| last.right_bound() > rb)
+            .is_none_or(|rb| last.right_bound() <= rb)
         {
             return false;
         }
@@ -424,153 +424,59 @@ impl Text {
         }
         Ok(())
     }
-}
-
-impl Drawable for Text {
-    fn draw(&self, target: &mut Pixmap) -> Result<(), Box<dyn Error>> {
-        self.draw_words(target)
-    }
-}
-
-fn dilate_bitmap(bitmap: &[u8], width: usize, height: usize) -> Vec<u8> {
-    let mut result = vec![0u8; bitmap.len()];
-
-    for y in 0..height {
-        for x in 0..width {
-            let idx = y * width + x;
-            let mut max_alpha = bitmap[idx];
-
-            // Check 8 neighbors + self
-            for dy in -1..=1 {
-                for dx in -1..=1 {
-                    let nx = x as i32 + dx;
-                    let ny = y as i32 + dy;
-
-                    if nx >= 0 && nx < width as i32 && ny >= 0 && ny < height as i32 {
-                        let neighbor_idx = (ny as usize) * width + (nx as usize);
-                        max_alpha = max_alpha.max(bitmap[neighbor_idx]);
-                    }
-                }
-            }
-
-            result[idx] = max_alpha;
-        }
-    }
-
-    result
-}
-
-fn dilate_bitmap_hybrid(bitmap: &[u8], width: usize, height: usize) -> Vec<u8> {
-    // First pass: Max filter for expansion
-    let mut expanded = vec![0u8; bitmap.len()];
-
-    for y in 0..height {
-        for x in 0..width {
-            let idx = y * width + x;
-            let mut max_alpha = bitmap[idx];
-
-            // 4-connected for clean edges
-            for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
-                let nx = x as i32 + dx;
-                let ny = y as i32 + dy;
-
-                if nx >= 0 && nx < width as i32 && ny >= 0 && ny < height as i32 {
-                    let neighbor_idx = (ny as usize) * width + (nx as usize);
-                    max_alpha = max_alpha.max(bitmap[neighbor_idx]);
-                }
-            }
-
-            expanded[idx] = max_alpha;
-        }
-    }
-
-    // Second pass: Gentle blur for smooth anti-aliasing
-    let mut result = vec![0u8; bitmap.len()];
-
-    for y in 0..height {
-        for x in 0..width {
-            let idx = y * width + x;
-            let mut sum = expanded[idx] as u32 * 4; // Center weight
-            let mut count = 4u32;
 
-            for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
-                let nx = x as i32 + dx;
-                let ny = y as i32 + dy;
-
-                if nx >= 0 && nx < width as i32 && ny >= 0 && ny < height as i32 {
-                    let neighbor_idx = (ny as usize) * width + (nx as usize);
-                    sum += expanded[neighbor_idx] as u32;
-                    count += 1;
-                }
-            }
-
-            result[idx] = (sum / count) as u8;
-        }
+    fn draw_rotated(&self, target: &mut Pixmap) -> Result<(), Box<dyn Error>> {
+        // lay out the unrotated text on its own pixmap, with one line of
+        // margin above for glyphs reaching over the first line
+        let line_height = self.font_config.font_height.round() as usize;
+        let mut text = self.clone();
+        text.position = Position::new(0, line_height);
+        let lines = text.to_lines().len().max(1);
+        let width = text.width().max(1);
+        let size = IntSize::from_wh(width as u32, ((lines + 2) * line_height) as u32)
+            .ok_or("Invalid size")?;
+        let mut pixmap = Pixmap::new(size.width(), size.height()).ok_or("Invalid size")?;
+        text.draw_words(&mut pixmap)?;
+
+        let (x, y) = (self.position.x as f32, self.position.y as f32);
+        let w = width as f32;
+        let h = line_height as f32;
+        let block_height = (lines * line_height) as f32;
+
+        // ^FT keeps the start of the baseline at `y + h`, ^FO moves the top
+        // left corner of the rotated block to `position`
+        let (tx, ty) = match (self.typeset, self.orientation) {
+            (_, Orientation::Normal) => (x, y),
+            (false, Orientation::Rotate) => (x + block_height, y),
+            (false, Orientation::Invert) => (x + w, y + block_height),
+            (false, Orientation::BackRotate) => (x, y + w),
+            (true, Orientation::Rotate) => (x + h, y + h),
+            (true, Orientation::Invert) => (x, y + 2. * h),
+            (true, Orientation::BackRotate) => (x - h, y + h),
+        };
+        let transform = orientation_transform(self.orientation)
+            .post_translate(tx, ty)
+            .pre_translate(0., -h);
+
+        target.draw_pixmap(
+            0,
+            0,
+            pixmap.as_ref(),
+            &PixmapPaint::default(),
+            transform,
+            None,
+        );
+        Ok(())
     }
-
-    result
 }
 
-fn dilate_bitmap_hybrid_passes(
-    bitmap: &[u8],
-    width: usize,
-    height: usize,
-    passes: usize,
-) -> Vec<u8> {
-    let mut current = bitmap.to_vec();
-
-    // Multiple expansion passes for consistent boldness
-    for _ in 0..passes {
-        let mut expanded = vec![0u8; bitmap.len()];
-
-        for y in 0..height {
-            for x in 0..width {
-                let idx = y * width + x;
-                let mut max_alpha = current[idx];
-
-                // 4-connected for clean edges
-                for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
-                    let nx = x as i32 + dx;
-                    let ny = y as i32 + dy;
-
-                    if nx >= 0 && nx < width as i32 && ny >= 0 && ny < height as i32 {
-                        let neighbor_idx = (ny as usize) * width + (nx as usize);
-                        max_alpha = max_alpha.max(current[neighbor_idx]);
-                    }
-                }
-
-                expanded[idx] = max_alpha;
-            }
-        }
-
-        current = expanded;
-    }
-
-    // Final pass: Gentle blur for smooth anti-aliasing
-    let mut result = vec![0u8; bitmap.len()];
-
-    for y in 0..height {
-        for x in 0..width {
-            let idx = y * width + x;
-            let mut sum = current[idx] as u32 * 4; // Center weight
-            let mut count = 4u32;
-
-            for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
-                let nx = x as i32 + dx;
-                let ny = y as i32 + dy;
-
-                if nx >= 0 && nx < width as i32 && ny >= 0 && ny < height as i32 {
-                    let neighbor_idx = (ny as usize) * width + (nx as usize);
-                    sum += current[neighbor_idx] as u32;
-                    count += 1;
-                }
-            }
-
-            result[idx] = (sum / count) as u8;
+impl Drawable for Text {
+    fn draw(&self, target: &mut Pixmap) -> Result<(), Box<dyn Error>> {
+        match self.orientation {
+            Orientation::Normal => self.draw_words(target),
+            _ => self.draw_rotated(target),
         }
     }
-
-    result
 }
 
 #[cfg(test)]
@@ -588,18 +494,15 @@ mod tests {
     fn gen_font_config() -> FontConfig {
         let data = fs::read("../fonts/Oswald/Oswald-Medium.ttf").unwrap();
         let font = fontdue::Font::from_bytes(data, FontSettings::default()).unwrap();
-        FontConfig::new(font, 20., 20., 1., false)
+        FontConfig::new(font, 20.)
     }
 
     #[test]
     fn should_calcualte_correct_width_at_x_0() {
         let input = "This is a test with some content".into();
-        let mut font_config = gen_font_config();
-        let scale = 1.;
-        font_config.scale = scale;
+        let font_config = gen_font_config();
         let position = Position::new(0, 0);
-        let justification = zpl_parser::Justification::Left;
-        let text = Text::new(input, font_config, position, justification, None);
+        let text = Text::new(input, font_config, position, None);
 
         let text_width = text.width();
         assert_eq!(text_width, 248);
@@ -608,12 +511,9 @@ mod tests {
     #[test]
     fn should_calcualte_correct_width() {
         let input = "This is a test with some content".into();
-        let mut font_config = gen_font_config();
-        let scale = 0.85;
-        font_config.scale = scale;
+        let font_config = gen_font_config();
         let position = Position::new(200, 0);
-        let justification = zpl_parser::Justification::Left;
-        let text = Text::new(input, font_config, position, justification, None);
+        let text = Text::new(input, font_config, position, None);
 
         let text_width = text.width();
         assert_eq!(text_width, 248);
@@ -622,13 +522,10 @@ mod tests {
     #[test]
     fn should_respect_fielbox_settings() {
         let input = "This is a test with some content".into();
-        let mut font_config = gen_font_config();
-        let scale = 0.85;
-        font_config.scale = scale;
+        let font_config = gen_font_config();
         let position = Position::new(0, 0);
-        let justification = zpl_parser::Justification::Left;
         let field_box = FieldBox::new(200, 2, 1, TextBlockJustification::Left, 0);
-        let text = Text::new(input, font_config, position, justification, Some(field_box));
+        let text = Text::new(input, font_config, position, Some(field_box));
 
         let text_width = text.width();
         assert_eq!(text_width, 200);
@@ -652,12 +549,9 @@ mod tests {
     #[test]
     fn to_words_test() {
         let input = "S wo".into();
-        let mut font_config = gen_font_config();
-        let scale = 1.;
-        font_config.scale = scale;
+        let font_config = gen_font_config();
         let position = Position::new(0, 0);
-        let justification = zpl_parser::Justification::Left;
-        let text = Text::new(input, font_config.clone(), position, justification, None);
+        let text = Text::new(input, font_config.clone(), position, None);
 
         let lines = text.to_lines();
         let glyph_1 = Glyph::new(&font_config, 'S');
@@ -676,12 +570,9 @@ mod tests {
     #[test]
     fn to_words_with_ending_space_test() {
         let input = "S wo ".into();
-        let mut font_config = gen_font_config();
-        let scale = 1.;
-        font_config.scale = scale;
+        let font_config = gen_font_config();
         let position = Position::new(0, 0);
-        let justification = zpl_parser::Justification::Left;
-        let text = Text::new(input, font_config.clone(), position, justification, None);
+        let text = Text::new(input, font_config.clone(), position, None);
 
         let words = text.to_lines();
         assert!(!words.last().unwrap().is_empty())
@@ -690,19 +581,10 @@ mod tests {
     #[test]
     fn check_word_wrap_test() {
         let input = "".into();
-        let mut font_config = gen_font_config();
-        let scale = 1.;
-        font_config.scale = scale;
+        let font_config = gen_font_config();
         let position = Position::new(0, 0);
-        let justification = zpl_parser::Justification::Left;
         let fielbox = FieldBox::new(200, 2, 1, TextBlockJustification::Left, 0);
-        let text = Text::new(
-            input,
-            font_config.clone(),
-            position,
-            justification,
-            Some(fielbox),
-        );
+        let text = Text::new(input, font_config.clone(), position, Some(fielbox));
 
         let glyph = Glyph {
             ch: 'S',
@@ -728,22 +610,13 @@ mod tests {
     #[test]
     fn word_wrap_test() {
         let input = "This is a test with some content".into();
-        let mut font_config = gen_font_config();
-        let scale = 1.;
-        font_config.scale = scale;
+        let font_config = gen_font_config();
         let position = Position::new(0, 0);
-        let justification = zpl_parser::Justification::Left;
         let fielbox = FieldBox::new(200, 2, 1, TextBlockJustification::Left, 0);
-        let text = Text::new(
-            input,
-            font_config.clone(),
-            position,
-            justification,
-            Some(fielbox),
-        );
+        let text = Text::new(input, font_config.clone(), position, Some(fielbox));
         let lines = text.to_lines();
 
-        let (first_line, second_line) = (lines.first().unwrap(), lines.iter().nth(1).unwrap());
+        let (first_line, second_line) = (lines.first().unwrap(), lines.get(1).unwrap());
         for glyph in first_line.iter().flatten() {
             assert!(glyph.y + glyph.height <= 20)
         }
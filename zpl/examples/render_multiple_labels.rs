@@ -0,0 +1,12 @@
+use zpl::ZplViewer;
+
+fn main() {
+    // two labels in one stream
+    let zpl = "^XA^FO50,50^FDFirst Label^FS^XZ\n^XA^FO50,50^FDSecond Label^FS^XZ";
+    let outputs = ZplViewer::parse_and_render_all(zpl).unwrap();
+    for (idx, out) in outputs.into_iter().enumerate() {
+        let file_name = format!("label_{idx}.png");
+        std::fs::write(&file_name, out.png).expect("write png");
+        println!("Wrote {file_name}");
+    }
+}
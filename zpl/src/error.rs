@@ -22,11 +22,20 @@ impl From<zpl_parser::ParseError> for ZplError {
     }
 }
 
+impl From<zpl_interpreter::UnknownFormat> for ZplError {
+    fn from(value: zpl_interpreter::UnknownFormat) -> Self {
+        Self {
+            kind: ZplErrorKind::InterpretError,
+            message: value.to_string(),
+        }
+    }
+}
+
 impl Display for ZplError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self.kind {
             ZplErrorKind::ParseError(ref parse_error) => write!(f, "Parse error: {parse_error}"),
-            ZplErrorKind::InterpretError => todo!(),
+            ZplErrorKind::InterpretError => write!(f, "Interpret error: {}", self.message),
             ZplErrorKind::RenderError => todo!(),
         }
     }
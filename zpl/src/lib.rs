@@ -1,13 +1,16 @@
-use zpl_interpreter::*;
-use zpl_parser::*;
 use zpl_renderer::*;
 
 mod error;
 
 pub use error::*;
 
-pub use zpl_interpreter::interpret;
-pub use zpl_parser::parse_zpl;
+pub use zpl_interpreter::{
+    DEFAULT_DOTS_PER_MM, FormatStore, UnknownFormat, interpret, interpret_with_resolution,
+};
+pub use zpl_parser::{
+    ParseWarning, parse_zpl, parse_zpl_labels, parse_zpl_lenient, parse_zpl_stream, to_zpl,
+};
+use zpl_parser::{ParseWarningKind, ZplFormatCommand};
 pub use zpl_renderer::render;
 
 pub struct ZplViewer;
@@ -19,4 +22,58 @@ impl ZplViewer {
         let result = render(&label);
         Ok(result)
     }
+
+    /// Render every label (^XA ... ^XZ) in `input` to its own output,
+    /// in the order they appear. Formats stored with ^DF are not rendered,
+    /// but merged into the labels recalling them with ^XF.
+    pub fn parse_and_render_all(input: &str) -> Result<Vec<RenderOutput>, ZplError> {
+        Self::parse_and_render_all_with_resolution(input, DEFAULT_DOTS_PER_MM)
+    }
+
+    /// Like [`ZplViewer::parse_and_render_all`], for a printer with
+    /// `dots_per_mm` dots per millimeter, e.g. 12 for 300 dpi.
+    pub fn parse_and_render_all_with_resolution(
+        input: &str,
+        dots_per_mm: u8,
+    ) -> Result<Vec<RenderOutput>, ZplError> {
+        let labels = parse_zpl_labels(input)?;
+        let mut store = FormatStore::new();
+        let mut result = Vec::new();
+        for commands in &labels {
+            if let Some(commands) = store.resolve(commands)? {
+                result.push(render(&interpret_with_resolution(&commands, dots_per_mm)));
+            }
+        }
+        Ok(result)
+    }
+
+    /// Like [`ZplViewer::parse_and_render_all`], but unsupported or malformed
+    /// commands are skipped instead of failing the whole input. Everything
+    /// that was skipped is returned as warnings, as are labels recalling
+    /// a format that was never stored.
+    pub fn parse_and_render_lenient(input: &str) -> (Vec<RenderOutput>, Vec<ParseWarning>) {
+        let parsed = parse_zpl_lenient(input);
+        let mut warnings = parsed.warnings;
+        let mut store = FormatStore::new();
+        let result = parsed
+            .labels
+            .into_iter()
+            .filter_map(|label| {
+                let commands: Vec<_> = label.iter().map(|command| command.node.clone()).collect();
+                store.resolve(&commands).unwrap_or_else(|err| {
+                    let recall = label.iter().find(|command| {
+                        matches!(command.node, ZplFormatCommand::RecallFormat { .. })
+                    });
+                    warnings.push(ParseWarning {
+                        kind: ParseWarningKind::MissingCommand,
+                        message: format!("^DF{}", err.name),
+                        location: recall.map(|command| command.span.start),
+                    });
+                    None
+                })
+            })
+            .map(|commands| render(&interpret(&commands)))
+            .collect();
+        (result, warnings)
+    }
 }